@@ -0,0 +1,147 @@
+//! Throughput of catalog scoring and of whole `generate_palette` calls.
+//!
+//! colorify is a binary crate, so the modules under test are compiled in
+//! directly. The `generate_palette` group needs the embedding model and is
+//! skipped if it can't be loaded.
+#![allow(dead_code)]
+
+#[path = "../src/cache.rs"]
+mod cache;
+#[path = "../src/color_space.rs"]
+mod color_space;
+#[path = "../src/colors.rs"]
+mod colors;
+#[path = "../src/config.rs"]
+mod config;
+#[path = "../src/constraints.rs"]
+mod constraints;
+#[path = "../src/describe.rs"]
+mod describe;
+#[path = "../src/display.rs"]
+mod display;
+#[path = "../src/embedding.rs"]
+mod embedding;
+#[path = "../src/expansion.rs"]
+mod expansion;
+#[path = "../src/explain.rs"]
+mod explain;
+#[path = "../src/feedback.rs"]
+mod feedback;
+#[path = "../src/index.rs"]
+mod index;
+#[path = "../src/lexical.rs"]
+mod lexical;
+#[path = "../src/matcher.rs"]
+mod matcher;
+#[path = "../src/query.rs"]
+mod query;
+#[path = "../src/swatches.rs"]
+mod swatches;
+#[path = "../src/tags.rs"]
+mod tags;
+
+use criterion::{black_box, criterion_group, criterion_main, BenchmarkId, Criterion, Throughput};
+use index::{dot, BruteForceIndex, EmbeddingMatrix, VectorIndex};
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+
+/// Embedding size of the MiniLM models
+const DIMS: usize = 384;
+
+const MOODS: &[&str] = &[
+    "cozy cabin in winter",
+    "tropical beach sunset",
+    "cyberpunk city at night",
+    "peaceful forest morning",
+    "vintage romance",
+    "energetic summer festival",
+];
+
+fn random_rows(count: usize, rng: &mut StdRng) -> Vec<Vec<f32>> {
+    (0..count)
+        .map(|_| (0..DIMS).map(|_| rng.random::<f32>() - 0.5).collect())
+        .collect()
+}
+
+/// The per-pair cosine the matcher used before vectors were pre-normalized.
+fn cosine(a: &[f32], b: &[f32]) -> f32 {
+    let dot_product: f32 = a.iter().zip(b).map(|(x, y)| x * y).sum();
+    let norm_a = a.iter().map(|x| x * x).sum::<f32>().sqrt();
+    let norm_b = b.iter().map(|x| x * x).sum::<f32>().sqrt();
+    dot_product / (norm_a * norm_b)
+}
+
+fn scoring(c: &mut Criterion) {
+    let mut rng = StdRng::seed_from_u64(7);
+    let query = random_rows(1, &mut rng).remove(0);
+
+    let mut group = c.benchmark_group("scoring");
+    for count in [540, 30_000] {
+        let rows = random_rows(count, &mut rng);
+        let index = BruteForceIndex::new(EmbeddingMatrix::from_rows(&rows).unwrap());
+        group.throughput(Throughput::Elements(count as u64));
+
+        group.bench_with_input(BenchmarkId::new("cosine_scan", count), &rows, |b, rows| {
+            b.iter(|| {
+                let mut scores: Vec<f32> = rows.iter().map(|row| cosine(&query, row)).collect();
+                scores.sort_by(|a, b| b.total_cmp(a));
+                black_box(scores)
+            })
+        });
+        group.bench_with_input(
+            BenchmarkId::new("brute_force_top500", count),
+            &index,
+            |b, index| b.iter(|| black_box(index.search(&query, 500).unwrap())),
+        );
+    }
+    group.finish();
+
+    let a = random_rows(1, &mut rng).remove(0);
+    c.bench_function("dot_384", |b| {
+        b.iter(|| dot(black_box(&a), black_box(&query)))
+    });
+}
+
+fn generate_palette(c: &mut Criterion) {
+    let runtime = tokio::runtime::Runtime::new().unwrap();
+    let generator = runtime.block_on(matcher::MoodPaletteGenerator::new(
+        config::Config::new(),
+        colors::get_color_data(),
+    ));
+    let mut generator = match generator {
+        Ok(generator) => generator,
+        Err(e) => {
+            eprintln!("skipping generate_palette benchmarks: {}", e);
+            return;
+        }
+    };
+
+    let mut group = c.benchmark_group("generate_palette");
+    group.throughput(Throughput::Elements(1));
+    group.bench_function("single", |b| {
+        b.iter(|| {
+            runtime
+                .block_on(generator.generate_palette(black_box(MOODS[0]), &[]))
+                .unwrap()
+        })
+    });
+
+    let moods: Vec<String> = MOODS
+        .iter()
+        .cycle()
+        .take(32)
+        .map(|m| m.to_string())
+        .collect();
+    group.throughput(Throughput::Elements(moods.len() as u64));
+    group.bench_function("batch_32", |b| {
+        b.iter(|| {
+            runtime
+                .block_on(generator.generate_palettes(black_box(&moods), 4))
+                .unwrap()
+        })
+    });
+    group.finish();
+}
+
+criterion_group!(benches, scoring, generate_palette);
+criterion_main!(benches);
@@ -0,0 +1,82 @@
+use crate::matcher::{palette_confidence, ColorMatch, MoodPaletteGenerator};
+use anyhow::{Context, Result};
+use serde::Serialize;
+use std::fs::File;
+use std::io::{self, BufRead, BufReader, BufWriter, Write};
+
+#[derive(Serialize)]
+struct BatchRecord<'a> {
+    input: &'a str,
+    palette: &'a [ColorMatch],
+    confidence: Option<f32>,
+}
+
+/// Reads one mood per line from `input` (a file path, or stdin when `None`
+/// or `-`) and writes one JSON object per line to stdout.
+pub async fn run_batch(
+    generator: &mut MoodPaletteGenerator,
+    input: Option<&str>,
+    jobs: usize,
+) -> Result<()> {
+    let reader: Box<dyn BufRead> = match input {
+        None | Some("-") => Box::new(BufReader::new(io::stdin())),
+        Some(path) => Box::new(BufReader::new(
+            File::open(path).with_context(|| format!("failed to open {}", path))?,
+        )),
+    };
+
+    let batch_size = generator.config().batch_size;
+    let stdout = io::stdout();
+    let mut out = BufWriter::new(stdout.lock());
+    let mut moods = Vec::with_capacity(batch_size);
+
+    for line in reader.lines() {
+        let line = line?;
+        let mood = line.trim();
+        if mood.is_empty() {
+            continue;
+        }
+
+        moods.push(mood.to_string());
+        if moods.len() == batch_size {
+            write_chunk(generator, &moods, jobs, &mut out).await?;
+            moods.clear();
+        }
+    }
+
+    if !moods.is_empty() {
+        write_chunk(generator, &moods, jobs, &mut out).await?;
+    }
+
+    out.flush()?;
+    Ok(())
+}
+
+async fn write_chunk(
+    generator: &mut MoodPaletteGenerator,
+    moods: &[String],
+    jobs: usize,
+    out: &mut impl Write,
+) -> Result<()> {
+    let palettes = generator.generate_palettes(moods, jobs).await?;
+    let config = generator.config();
+
+    for (mood, (palette, report)) in moods.iter().zip(palettes.iter()) {
+        // Stdout is reserved for JSON lines
+        for warning in config.constraints.warnings(config.top_k, *report) {
+            eprintln!("⚠️  \"{}\": {}", mood, warning);
+        }
+
+        let record = BatchRecord {
+            input: mood,
+            palette,
+            confidence: palette_confidence(palette),
+        };
+        serde_json::to_writer(&mut *out, &record)?;
+        writeln!(out)?;
+    }
+
+    // Flush per chunk so downstream consumers see results as they are ready
+    out.flush()?;
+    Ok(())
+}
@@ -0,0 +1,126 @@
+use crate::cache::EmbeddingCache;
+use crate::index::{BruteForceIndex, EmbeddingMatrix, HnswIndex, HnswParams, VectorIndex};
+use crate::matcher::MoodPaletteGenerator;
+use anyhow::{bail, Context, Result};
+use colored::*;
+use std::fs;
+use std::time::{Duration, Instant};
+
+/// Moods used as queries when no file is given.
+const BENCH_MOODS: &[&str] = &[
+    "cozy cabin in winter",
+    "tropical beach sunset",
+    "cyberpunk city at night",
+    "peaceful forest morning",
+    "vintage romance",
+    "energetic summer festival",
+    "calm ocean breeze",
+    "autumn harvest market",
+    "minimalist scandinavian kitchen",
+    "haunted victorian mansion",
+    "spring cherry blossoms",
+    "desert canyon at noon",
+    "retro 80s arcade",
+    "misty mountain lake",
+    "luxurious velvet lounge",
+    "children's playroom",
+];
+
+/// Times each query this many times so short searches average out.
+const REPEATS: usize = 20;
+
+/// Compares HNSW against brute force over the loaded catalog: build time,
+/// recall@k of the approximate results, and average query latency.
+pub async fn run_index_bench(
+    generator: &mut MoodPaletteGenerator,
+    queries: Option<&str>,
+    k: usize,
+) -> Result<()> {
+    let moods: Vec<String> = match queries {
+        Some(path) => fs::read_to_string(path)
+            .with_context(|| format!("failed to read {}", path))?
+            .lines()
+            .map(str::trim)
+            .filter(|line| !line.is_empty())
+            .map(str::to_string)
+            .collect(),
+        None => BENCH_MOODS.iter().map(|mood| mood.to_string()).collect(),
+    };
+    if moods.is_empty() {
+        bail!("no benchmark queries");
+    }
+
+    let Some(vectors) = EmbeddingCache::new().load_embeddings(generator.catalog_key()) else {
+        bail!("catalog embeddings are not cached; run a palette first");
+    };
+    let query_vecs = generator.embed_texts(&moods).await?;
+
+    let started = Instant::now();
+    let brute = BruteForceIndex::new(EmbeddingMatrix::from_rows(&vectors)?);
+    let brute_build = started.elapsed();
+
+    let started = Instant::now();
+    let hnsw = HnswIndex::build(EmbeddingMatrix::from_rows(&vectors)?, HnswParams::default());
+    let hnsw_build = started.elapsed();
+
+    println!(
+        "\n{} {} colors, {} queries, k = {}",
+        "📏 Index benchmark:".bold().bright_blue(),
+        brute.len(),
+        moods.len(),
+        k
+    );
+
+    let (truth, brute_latency) = time_queries(&brute, &query_vecs, k)?;
+    let (found, hnsw_latency) = time_queries(&hnsw, &query_vecs, k)?;
+
+    let recall = truth
+        .iter()
+        .zip(&found)
+        .map(|(expected, got)| {
+            let hits = got.iter().filter(|row| expected.contains(row)).count();
+            hits as f32 / expected.len().max(1) as f32
+        })
+        .sum::<f32>()
+        / truth.len() as f32;
+
+    println!(
+        "   {:<12} build {:>10.2?}   query {:>10.2?}   recall@{} 1.000",
+        brute.name(),
+        brute_build,
+        brute_latency,
+        k
+    );
+    println!(
+        "   {:<12} build {:>10.2?}   query {:>10.2?}   recall@{} {:.3}",
+        hnsw.name(),
+        hnsw_build,
+        hnsw_latency,
+        k,
+        recall
+    );
+
+    Ok(())
+}
+
+/// Runs every query `REPEATS` times, returning the result rows of the last
+/// run and the average latency per query.
+fn time_queries(
+    index: &dyn VectorIndex,
+    queries: &[Vec<f32>],
+    k: usize,
+) -> Result<(Vec<Vec<usize>>, Duration)> {
+    let mut results = Vec::new();
+    let started = Instant::now();
+    for _ in 0..REPEATS {
+        results = queries
+            .iter()
+            .map(|query| {
+                let hits = index.search(query, k)?;
+                Ok(hits.into_iter().map(|(row, _)| row).collect())
+            })
+            .collect::<Result<_>>()?;
+    }
+    let latency = started.elapsed() / (REPEATS * queries.len()) as u32;
+    Ok((results, latency))
+}
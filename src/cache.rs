@@ -0,0 +1,81 @@
+use anyhow::{bail, Context, Result};
+use std::fs;
+use std::path::PathBuf;
+
+const EMBEDDINGS_MAGIC: &[u8; 8] = b"CFYEMB01";
+
+/// On-disk cache for catalog embeddings and the search indexes built from
+/// them, kept in a `colorify` folder inside fastembed's model cache.
+pub struct EmbeddingCache {
+    dir: PathBuf,
+}
+
+impl EmbeddingCache {
+    pub fn new() -> Self {
+        Self {
+            dir: PathBuf::from(fastembed::get_cache_dir()).join("colorify"),
+        }
+    }
+
+    /// Path for a cached artifact, e.g. `path(key, "hnsw")`.
+    pub fn path(&self, key: &str, extension: &str) -> PathBuf {
+        self.dir.join(format!("{}.{}", key, extension))
+    }
+
+    pub fn load_embeddings(&self, key: &str) -> Option<Vec<Vec<f32>>> {
+        let bytes = fs::read(self.path(key, "embeddings")).ok()?;
+        if bytes.len() < 16 || &bytes[..8] != EMBEDDINGS_MAGIC {
+            return None;
+        }
+
+        let count = u32::from_le_bytes(bytes[8..12].try_into().ok()?) as usize;
+        let dims = u32::from_le_bytes(bytes[12..16].try_into().ok()?) as usize;
+        let data = &bytes[16..];
+        if dims == 0 || data.len() != count * dims * 4 {
+            return None;
+        }
+
+        Some(
+            data.chunks_exact(dims * 4)
+                .map(|row| {
+                    row.chunks_exact(4)
+                        .map(|b| f32::from_le_bytes([b[0], b[1], b[2], b[3]]))
+                        .collect()
+                })
+                .collect(),
+        )
+    }
+
+    pub fn save_embeddings(&self, key: &str, embeddings: &[Vec<f32>]) -> Result<()> {
+        let dims = embeddings.first().map(|row| row.len()).unwrap_or(0);
+        if embeddings.iter().any(|row| row.len() != dims) {
+            bail!("embeddings have mixed dimensions");
+        }
+
+        let mut bytes = Vec::with_capacity(16 + embeddings.len() * dims * 4);
+        bytes.extend_from_slice(EMBEDDINGS_MAGIC);
+        bytes.extend_from_slice(&(embeddings.len() as u32).to_le_bytes());
+        bytes.extend_from_slice(&(dims as u32).to_le_bytes());
+        for value in embeddings.iter().flatten() {
+            bytes.extend_from_slice(&value.to_le_bytes());
+        }
+
+        fs::create_dir_all(&self.dir)
+            .with_context(|| format!("failed to create {}", self.dir.display()))?;
+        let path = self.path(key, "embeddings");
+        fs::write(&path, bytes).with_context(|| format!("failed to write {}", path.display()))
+    }
+}
+
+/// Stable FNV-1a fingerprint of a sequence of strings, used to key cached
+/// artifacts to the exact model and catalog text they came from.
+pub fn fingerprint<'a>(parts: impl IntoIterator<Item = &'a str>) -> String {
+    let mut hash: u64 = 0xcbf2_9ce4_8422_2325;
+    for part in parts {
+        for byte in part.bytes().chain(std::iter::once(0xff)) {
+            hash ^= byte as u64;
+            hash = hash.wrapping_mul(0x0000_0100_0000_01b3);
+        }
+    }
+    format!("{:016x}", hash)
+}
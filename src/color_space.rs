@@ -0,0 +1,354 @@
+//! Conversions between sRGB and the perceptual spaces used for palette
+//! decisions (OKLab/OKLCH), plus WCAG contrast helpers.
+
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Oklab {
+    pub l: f32,
+    pub a: f32,
+    pub b: f32,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Oklch {
+    pub l: f32,
+    pub c: f32,
+    /// Hue angle in degrees, 0..360
+    pub h: f32,
+}
+
+impl Oklab {
+    pub fn from_rgb(r: u8, g: u8, b: u8) -> Self {
+        let r = srgb_to_linear(r);
+        let g = srgb_to_linear(g);
+        let b = srgb_to_linear(b);
+
+        let l = (0.412_221_46 * r + 0.536_332_55 * g + 0.051_445_995 * b).cbrt();
+        let m = (0.211_903_5 * r + 0.680_699_5 * g + 0.107_396_96 * b).cbrt();
+        let s = (0.088_302_46 * r + 0.281_718_85 * g + 0.629_978_7 * b).cbrt();
+
+        Self {
+            l: 0.210_454_26 * l + 0.793_617_8 * m - 0.004_072_047 * s,
+            a: 1.977_998_5 * l - 2.428_592_2 * m + 0.450_593_7 * s,
+            b: 0.025_904_037 * l + 0.782_771_77 * m - 0.808_675_77 * s,
+        }
+    }
+
+    /// Linear sRGB channels; outside 0.0..=1.0 when sRGB can't show the color.
+    fn to_linear_rgb(self) -> [f32; 3] {
+        let l = (self.l + 0.396_337_78 * self.a + 0.215_803_76 * self.b).powi(3);
+        let m = (self.l - 0.105_561_346 * self.a - 0.063_854_17 * self.b).powi(3);
+        let s = (self.l - 0.089_484_18 * self.a - 1.291_485_5 * self.b).powi(3);
+        [
+            4.076_741_7 * l - 3.307_711_6 * m + 0.230_969_94 * s,
+            -1.268_438 * l + 2.609_757_4 * m - 0.341_319_4 * s,
+            -0.004_196_086_3 * l - 0.703_418_6 * m + 1.707_614_7 * s,
+        ]
+    }
+
+    /// sRGB, with out-of-gamut channels clipped.
+    pub fn to_rgb(self) -> (u8, u8, u8) {
+        let [r, g, b] = self.to_linear_rgb();
+        (linear_to_srgb(r), linear_to_srgb(g), linear_to_srgb(b))
+    }
+
+    pub fn to_oklch(self) -> Oklch {
+        let c = (self.a * self.a + self.b * self.b).sqrt();
+        let h = self.b.atan2(self.a).to_degrees().rem_euclid(360.0);
+        Oklch { l: self.l, c, h }
+    }
+
+    /// Euclidean distance in OKLab (ΔEok); around 0.02 is a just-noticeable
+    /// difference.
+    pub fn delta_e(&self, other: &Oklab) -> f32 {
+        let dl = self.l - other.l;
+        let da = self.a - other.a;
+        let db = self.b - other.b;
+        (dl * dl + da * da + db * db).sqrt()
+    }
+}
+
+/// Broad hue groups for describing and filtering colors.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum HueFamily {
+    Red,
+    Orange,
+    Yellow,
+    Green,
+    Cyan,
+    Blue,
+    Purple,
+    Pink,
+    Neutral,
+}
+
+impl HueFamily {
+    pub const ALL: [HueFamily; 9] = [
+        HueFamily::Red,
+        HueFamily::Orange,
+        HueFamily::Yellow,
+        HueFamily::Green,
+        HueFamily::Cyan,
+        HueFamily::Blue,
+        HueFamily::Purple,
+        HueFamily::Pink,
+        HueFamily::Neutral,
+    ];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            HueFamily::Red => "red",
+            HueFamily::Orange => "orange",
+            HueFamily::Yellow => "yellow",
+            HueFamily::Green => "green",
+            HueFamily::Cyan => "cyan",
+            HueFamily::Blue => "blue",
+            HueFamily::Purple => "purple",
+            HueFamily::Pink => "pink",
+            HueFamily::Neutral => "neutral",
+        }
+    }
+
+    pub fn from_name(name: &str) -> Option<Self> {
+        Self::ALL
+            .into_iter()
+            .find(|family| family.name().eq_ignore_ascii_case(name.trim()))
+    }
+}
+
+impl Oklch {
+    /// Below this chroma a color reads as grey rather than as its hue.
+    pub const NEUTRAL_CHROMA: f32 = 0.03;
+
+    pub fn to_oklab(self) -> Oklab {
+        let h = self.h.to_radians();
+        Oklab {
+            l: self.l,
+            a: self.c * h.cos(),
+            b: self.c * h.sin(),
+        }
+    }
+
+    /// The nearest color sRGB can show: same lightness and hue, with chroma
+    /// reduced until every channel is in range.
+    pub fn to_rgb_in_gamut(self) -> (u8, u8, u8) {
+        const TOLERANCE: f32 = 0.000_1;
+        let in_gamut = |c: f32| {
+            Oklch { c, ..self }
+                .to_oklab()
+                .to_linear_rgb()
+                .iter()
+                .all(|channel| (-TOLERANCE..=1.0 + TOLERANCE).contains(channel))
+        };
+        if in_gamut(self.c) {
+            return self.to_oklab().to_rgb();
+        }
+        let (mut low, mut high) = (0.0, self.c);
+        for _ in 0..20 {
+            let mid = (low + high) / 2.0;
+            if in_gamut(mid) {
+                low = mid;
+            } else {
+                high = mid;
+            }
+        }
+        Oklch { c: low, ..self }.to_oklab().to_rgb()
+    }
+
+    /// Cosine of the hue's angle from orange: 1.0 warmest, -1.0 coolest
+    /// (around cyan-blue).
+    pub fn warmth(&self) -> f32 {
+        (self.h - 50.0).to_radians().cos()
+    }
+
+    pub fn hue_family(&self) -> HueFamily {
+        if self.c < Self::NEUTRAL_CHROMA {
+            return HueFamily::Neutral;
+        }
+        match self.h {
+            h if h < 10.0 => HueFamily::Pink,
+            h if h < 45.0 => HueFamily::Red,
+            h if h < 80.0 => HueFamily::Orange,
+            h if h < 120.0 => HueFamily::Yellow,
+            h if h < 170.0 => HueFamily::Green,
+            h if h < 225.0 => HueFamily::Cyan,
+            h if h < 285.0 => HueFamily::Blue,
+            h if h < 335.0 => HueFamily::Purple,
+            _ => HueFamily::Pink,
+        }
+    }
+}
+
+/// Parses `#RRGGBB` or the `#RGB` shorthand, with or without the leading `#`.
+pub fn parse_hex(input: &str) -> Option<(u8, u8, u8)> {
+    let hex = input.trim().trim_start_matches('#');
+    if !hex.chars().all(|c| c.is_ascii_hexdigit()) {
+        return None;
+    }
+
+    match hex.len() {
+        6 => Some((
+            u8::from_str_radix(&hex[0..2], 16).ok()?,
+            u8::from_str_radix(&hex[2..4], 16).ok()?,
+            u8::from_str_radix(&hex[4..6], 16).ok()?,
+        )),
+        3 => {
+            let digit = |i: usize| u8::from_str_radix(&hex[i..i + 1], 16).ok().map(|d| d * 17);
+            Some((digit(0)?, digit(1)?, digit(2)?))
+        }
+        _ => None,
+    }
+}
+
+pub fn to_hex(r: u8, g: u8, b: u8) -> String {
+    format!("#{:02X}{:02X}{:02X}", r, g, b)
+}
+
+/// Shortest angle between two hues, 0..180 degrees.
+pub fn hue_distance(a: f32, b: f32) -> f32 {
+    let d = (a - b).rem_euclid(360.0);
+    d.min(360.0 - d)
+}
+
+/// How well two colors sit together by hue, from 0.0 to 1.0. Analogous,
+/// triadic, split-complementary and complementary pairs score highest;
+/// near-neutral colors go with anything.
+pub fn hue_harmony(a: &Oklch, b: &Oklch) -> f32 {
+    const NEUTRAL_CHROMA: f32 = 0.04;
+    const HARMONIC_ANGLES: [f32; 5] = [0.0, 30.0, 120.0, 150.0, 180.0];
+
+    if a.c < NEUTRAL_CHROMA || b.c < NEUTRAL_CHROMA {
+        return 1.0;
+    }
+
+    let d = hue_distance(a.h, b.h);
+    HARMONIC_ANGLES
+        .iter()
+        .map(|angle| (-((d - angle) / 20.0).powi(2)).exp())
+        .fold(0.0, f32::max)
+}
+
+/// Hue, saturation and lightness with hue in degrees and the rest in 0..1.
+pub fn rgb_to_hsl(r: u8, g: u8, b: u8) -> (f32, f32, f32) {
+    let r = r as f32 / 255.0;
+    let g = g as f32 / 255.0;
+    let b = b as f32 / 255.0;
+
+    let max = r.max(g).max(b);
+    let min = r.min(g).min(b);
+    let l = (max + min) / 2.0;
+    let delta = max - min;
+
+    if delta == 0.0 {
+        return (0.0, 0.0, l);
+    }
+
+    let s = delta / (1.0 - (2.0 * l - 1.0).abs());
+    let h = if max == r {
+        60.0 * ((g - b) / delta).rem_euclid(6.0)
+    } else if max == g {
+        60.0 * ((b - r) / delta + 2.0)
+    } else {
+        60.0 * ((r - g) / delta + 4.0)
+    };
+
+    (h, s, l)
+}
+
+/// Inverse of `rgb_to_hsl`: hue in degrees, saturation and lightness in 0..1.
+pub fn hsl_to_rgb(h: f32, s: f32, l: f32) -> (u8, u8, u8) {
+    let s = s.clamp(0.0, 1.0);
+    let l = l.clamp(0.0, 1.0);
+    let c = (1.0 - (2.0 * l - 1.0).abs()) * s;
+    let h = h.rem_euclid(360.0) / 60.0;
+    let x = c * (1.0 - (h % 2.0 - 1.0).abs());
+
+    let (r, g, b) = match h as u32 {
+        0 => (c, x, 0.0),
+        1 => (x, c, 0.0),
+        2 => (0.0, c, x),
+        3 => (0.0, x, c),
+        4 => (x, 0.0, c),
+        _ => (c, 0.0, x),
+    };
+
+    let m = l - c / 2.0;
+    let channel = |v: f32| ((v + m) * 255.0).round().clamp(0.0, 255.0) as u8;
+    (channel(r), channel(g), channel(b))
+}
+
+/// Hue in degrees, saturation and value (brightness) in 0..1.
+pub fn hsv_to_rgb(h: f32, s: f32, v: f32) -> (u8, u8, u8) {
+    let s = s.clamp(0.0, 1.0);
+    let v = v.clamp(0.0, 1.0);
+    let l = v * (1.0 - s / 2.0);
+    let s_l = if l <= 0.0 || l >= 1.0 {
+        0.0
+    } else {
+        (v - l) / l.min(1.0 - l)
+    };
+    hsl_to_rgb(h, s_l, l)
+}
+
+/// Naive (unmanaged) CMYK with every ink in 0..1.
+pub fn cmyk_to_rgb(c: f32, m: f32, y: f32, k: f32) -> (u8, u8, u8) {
+    let channel =
+        |ink: f32| ((1.0 - ink.clamp(0.0, 1.0)) * (1.0 - k.clamp(0.0, 1.0)) * 255.0).round() as u8;
+    (channel(c), channel(m), channel(y))
+}
+
+/// CIE L*a*b* under D50, as swatch formats store it, to sRGB.
+pub fn lab_to_rgb(l: f32, a: f32, b: f32) -> (u8, u8, u8) {
+    const DELTA: f32 = 6.0 / 29.0;
+    let inverse = |t: f32| {
+        if t > DELTA {
+            t * t * t
+        } else {
+            3.0 * DELTA * DELTA * (t - 4.0 / 29.0)
+        }
+    };
+    let fy = (l + 16.0) / 116.0;
+    let x = 0.964_22 * inverse(fy + a / 500.0);
+    let y = inverse(fy);
+    let z = 0.825_21 * inverse(fy - b / 200.0);
+
+    // XYZ (D50) to linear sRGB, Bradford-adapted to D65
+    let r = 3.133_856 * x - 1.616_867 * y - 0.490_614_6 * z;
+    let g = -0.978_768_4 * x + 1.916_141_5 * y + 0.033_454 * z;
+    let b = 0.071_945_3 * x - 0.228_991_4 * y + 1.405_242_7 * z;
+    (linear_to_srgb(r), linear_to_srgb(g), linear_to_srgb(b))
+}
+
+/// WCAG 2 relative luminance.
+pub fn relative_luminance(r: u8, g: u8, b: u8) -> f32 {
+    0.2126 * srgb_to_linear(r) + 0.7152 * srgb_to_linear(g) + 0.0722 * srgb_to_linear(b)
+}
+
+/// WCAG 2 contrast ratio between two colors, from 1.0 to 21.0.
+pub fn contrast_ratio(a: (u8, u8, u8), b: (u8, u8, u8)) -> f32 {
+    let la = relative_luminance(a.0, a.1, a.2);
+    let lb = relative_luminance(b.0, b.1, b.2);
+    let (lighter, darker) = if la > lb { (la, lb) } else { (lb, la) };
+    (lighter + 0.05) / (darker + 0.05)
+}
+
+fn srgb_to_linear(channel: u8) -> f32 {
+    let c = channel as f32 / 255.0;
+    if c <= 0.04045 {
+        c / 12.92
+    } else {
+        ((c + 0.055) / 1.055).powf(2.4)
+    }
+}
+
+fn linear_to_srgb(channel: f32) -> u8 {
+    let c = channel.clamp(0.0, 1.0);
+    let c = if c <= 0.003_130_8 {
+        c * 12.92
+    } else {
+        1.055 * c.powf(1.0 / 2.4) - 0.055
+    };
+    (c * 255.0).round() as u8
+}
@@ -1,10 +1,27 @@
+use crate::color_space::{parse_hex, to_hex, HueFamily, Oklab, Oklch};
+use crate::describe::fill_missing;
+use crate::swatches::{load_swatches, SwatchFormat};
+use crate::tags::{parse_tags, tag_catalog, BUILT_IN_SOURCE};
+use anyhow::{bail, Context, Result};
 use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::Path;
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
 pub struct Color {
     pub name: String,
     pub hex: String,
+    #[serde(default)]
     pub description: String,
+    /// Derived from the hex value when a catalog doesn't give one
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub family: Option<HueFamily>,
+    /// Lowercase labels such as "pastel" or "earthy" for `--only`/`--exclude`
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub tags: Vec<String>,
+    /// Where the color came from: "built-in" or the catalog file's name
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub source: String,
 }
 
 #[allow(dead_code)]
@@ -14,6 +31,22 @@ impl Color {
             name: name.to_string(),
             hex: hex.to_string(),
             description: description.to_string(),
+            ..Self::default()
+        }
+    }
+
+    /// A color outside the catalog, named after its normalized hex value.
+    pub fn from_hex(hex: &str) -> Option<Self> {
+        let (r, g, b) = parse_hex(hex)?;
+        Some(Self::from_rgb(r, g, b))
+    }
+
+    pub fn from_rgb(r: u8, g: u8, b: u8) -> Self {
+        let hex = to_hex(r, g, b);
+        Self {
+            name: hex.clone(),
+            hex,
+            ..Self::default()
         }
     }
 
@@ -34,6 +67,15 @@ impl Color {
         Some((r, g, b))
     }
 
+    pub fn to_oklab(&self) -> Option<Oklab> {
+        let (r, g, b) = self.get_rgb()?;
+        Some(Oklab::from_rgb(r, g, b))
+    }
+
+    pub fn to_oklch(&self) -> Option<Oklch> {
+        self.to_oklab().map(Oklab::to_oklch)
+    }
+
     pub fn get_text_color(&self) -> &'static str {
         if let Some((r, g, b)) = self.get_rgb() {
             let luminance = 0.299 * r as f32 + 0.587 * g as f32 + 0.114 * b as f32;
@@ -48,8 +90,139 @@ impl Color {
     }
 }
 
+/// Loads a catalog from disk: a JSON array of colors, CSV/TSV/plain text
+/// lines of `name, hex[, description]` (the XKCD survey's `name<TAB>#hex`
+/// list works as-is), or a `.gpl`, `.ase`, `.aco`, `.kpl` or `.swatches`
+/// swatch library. Unnamed colors get the name of their nearest built-in
+/// color and undescribed ones a synthesized description; colors without
+/// tags are tagged from their values, and all record the file as source.
+pub fn load_catalog(path: &Path) -> Result<Vec<Color>> {
+    let mut colors = read_catalog(path, true)?;
+    // The description is what moods are matched against, so never leave it empty
+    fill_missing(&mut colors, &get_color_data());
+    let source = path
+        .file_name()
+        .map(|name| name.to_string_lossy().into_owned())
+        .unwrap_or_default();
+    tag_catalog(&mut colors, &source);
+    Ok(colors)
+}
+
+/// Reads a catalog file as written. With `strict`, JSON and CSV/TSV hex
+/// values must parse and are normalized; without it they are kept verbatim
+/// so that `catalog lint` can report them.
+pub fn read_catalog(path: &Path, strict: bool) -> Result<Vec<Color>> {
+    if let Some(format) = SwatchFormat::from_path(path) {
+        return load_swatches(path, format);
+    }
+
+    let contents =
+        fs::read_to_string(path).with_context(|| format!("failed to read {}", path.display()))?;
+
+    let extension = path
+        .extension()
+        .and_then(|ext| ext.to_str())
+        .map(|ext| ext.to_lowercase());
+    if extension.as_deref() == Some("json") {
+        let mut colors: Vec<Color> = serde_json::from_str(&contents)
+            .with_context(|| format!("{} is not a JSON color list", path.display()))?;
+        if strict {
+            for (i, color) in colors.iter_mut().enumerate() {
+                let Some((r, g, b)) = parse_hex(&color.hex) else {
+                    bail!(
+                        "{}: color {} ('{}') has invalid hex value '{}'",
+                        path.display(),
+                        i + 1,
+                        color.name,
+                        color.hex
+                    );
+                };
+                color.hex = to_hex(r, g, b);
+            }
+        }
+        return Ok(colors);
+    }
+
+    let mut colors = Vec::new();
+    for (line_number, line) in contents.lines().enumerate() {
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') && !line.contains([',', '\t']) {
+            continue;
+        }
+
+        // TSV has room for `tags` and `family` columns after the description;
+        // in CSV the description may itself contain commas
+        let (separator, columns) = if line.contains('\t') {
+            ('\t', 5)
+        } else {
+            (',', 3)
+        };
+        let fields: Vec<&str> = line.splitn(columns, separator).map(str::trim).collect();
+        if fields.len() < 2 {
+            bail!(
+                "{}:{}: expected name and hex",
+                path.display(),
+                line_number + 1
+            );
+        }
+
+        let hex = match parse_hex(fields[1]) {
+            Some((r, g, b)) if strict => to_hex(r, g, b),
+            Some(_) => fields[1].to_string(),
+            // Allow a header row such as `name,hex,description`, and
+            // comments that happen to contain a separator
+            None if line_number == 0 || line.starts_with('#') => continue,
+            None if !strict => fields[1].to_string(),
+            None => bail!(
+                "{}:{}: invalid hex value '{}'",
+                path.display(),
+                line_number + 1,
+                fields[1]
+            ),
+        };
+
+        let family = match fields.get(4).filter(|field| !field.is_empty()) {
+            Some(field) => match HueFamily::from_name(field) {
+                Some(family) => Some(family),
+                None => bail!(
+                    "{}:{}: unknown hue family '{}'",
+                    path.display(),
+                    line_number + 1,
+                    field
+                ),
+            },
+            None => None,
+        };
+        colors.push(Color {
+            name: fields[0].to_string(),
+            hex,
+            description: fields.get(2).unwrap_or(&"").to_string(),
+            family,
+            tags: fields
+                .get(3)
+                .map(|field| parse_tags(field))
+                .unwrap_or_default(),
+            source: String::new(),
+        });
+    }
+
+    Ok(colors)
+}
+
+/// The catalog at `path`, or the built-in one when no path is given.
+pub fn load_colors(path: Option<&Path>) -> Result<Vec<Color>> {
+    let Some(path) = path else {
+        return Ok(get_color_data());
+    };
+    let colors = load_catalog(path)?;
+    if colors.is_empty() {
+        bail!("catalog {} has no colors", path.display());
+    }
+    Ok(colors)
+}
+
 pub fn get_color_data() -> Vec<Color> {
-    vec![
+    let mut colors = vec![
         // --- Core Colors ---
         Color::new(
             "Crimson",
@@ -1607,5 +1780,54 @@ pub fn get_color_data() -> Vec<Color> {
             "#EBC2AF",
             "A pale, brownish-pink, mineral color.",
         ),
-    ]
+    ];
+    tag_catalog(&mut colors, BUILT_IN_SOURCE);
+    colors
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Reads `contents` as a strict catalog file with the given extension.
+    fn read(extension: &str, contents: &str) -> Result<Vec<Color>> {
+        let path = std::env::temp_dir().join(format!(
+            "colorify-catalog-{}-{}.{}",
+            std::process::id(),
+            extension,
+            extension
+        ));
+        fs::write(&path, contents).unwrap();
+        let colors = read_catalog(&path, true);
+        fs::remove_file(&path).unwrap();
+        colors
+    }
+
+    #[test]
+    fn normalizes_json_hex_values() {
+        let colors = read(
+            "json",
+            r##"[{"name": "Mint", "hex": "abc"}, {"name": "Ink", "hex": "#1a2b3c"}]"##,
+        )
+        .unwrap();
+        let hexes: Vec<&str> = colors.iter().map(|c| c.hex.as_str()).collect();
+        assert_eq!(hexes, ["#AABBCC", "#1A2B3C"]);
+
+        let error = read("json", r##"[{"name": "Bad", "hex": "#12345"}]"##).unwrap_err();
+        assert!(error.to_string().contains("invalid hex value '#12345'"));
+    }
+
+    #[test]
+    fn skips_comment_lines_with_separators() {
+        let colors = read(
+            "csv",
+            "# name, hex, description\nMint,#3EB489,Fresh\n# ignored, still a comment\n",
+        )
+        .unwrap();
+        assert_eq!(colors.len(), 1);
+        assert_eq!(colors[0].name, "Mint");
+
+        let colors = read("tsv", "# exported\tby hand\n#1 Red\t#FF0000\n").unwrap();
+        assert_eq!(colors[0].name, "#1 Red");
+    }
 }
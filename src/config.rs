@@ -1,11 +1,142 @@
-#[derive(Debug, Clone)]
+use crate::constraints::Constraints;
+use serde::{Deserialize, Serialize};
+use std::path::PathBuf;
+
+/// How embedding similarity and lexical (BM25) matches are combined into a
+/// color's score.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Fusion {
+    /// Embedding similarity only
+    Semantic,
+    /// `(1 - weight) * semantic + weight * lexical`, with BM25 scaled to 0..1
+    /// by the best lexical hit for the query
+    Linear { weight: f32 },
+    /// Reciprocal rank fusion of both rankings, rescaled so the best possible
+    /// score is 1.0
+    Rrf { k: f32 },
+}
+
+/// How a color's scores under several description templates are combined.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Pooling {
+    /// The template that matches the mood best speaks for the color
+    Max,
+    /// Every template counts equally
+    Mean,
+}
+
+impl Pooling {
+    pub fn name(self) -> &'static str {
+        match self {
+            Pooling::Max => "max",
+            Pooling::Mean => "mean",
+        }
+    }
+}
+
+/// Which embedder turns moods and colors into vectors.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum EmbedderKind {
+    /// The MiniLM sentence model
+    Model,
+    /// Deterministic word hashing, for repeatable evaluation without the model
+    Hashing,
+}
+
+/// How a color is described to the embedder unless configured otherwise.
+pub const DEFAULT_TEMPLATE: &str = "{name}, {description}";
+
+/// Which nearest-neighbour index backs the semantic search.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum IndexKind {
+    /// Brute force for small catalogs, HNSW once they get large
+    Auto,
+    BruteForce,
+    Hnsw,
+}
+
+/// Everything that shapes a palette; saved with each history entry so it
+/// can be rerun.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
 pub struct Config {
     pub top_k: usize,
+    pub batch_size: usize,
+    /// Trade-off between raw mood score (0.0) and spreading the palette out
+    /// across color space (1.0).
+    pub diversity: f32,
+    /// Sample palettes from the softmaxed scores instead of taking the top
+    /// matches; `None` keeps generation deterministic.
+    pub temperature: Option<f32>,
+    /// Seed for sampling so runs can be reproduced.
+    pub seed: Option<u64>,
+    pub fusion: Fusion,
+    /// Pin hex codes, rgb()/hsl() literals and catalog names written in the
+    /// mood straight into the palette.
+    pub pin_mentions: bool,
+    /// Swap pinned colors that aren't in the catalog for their nearest
+    /// catalog color.
+    pub snap_mentions: bool,
+    pub index: IndexKind,
+    /// How many nearest colors the index hands to scoring, fusion and
+    /// palette selection; 0 scores the whole catalog.
+    pub candidate_pool: usize,
+    /// Colors whose embedding similarity falls below this are dropped,
+    /// unless the mood names them literally.
+    pub min_score: f32,
+    /// Drop colors below this fraction of the best similarity, 0.0 to 1.0.
+    pub relative_floor: f32,
+    /// Attribute each color to the mood words that drove it
+    pub explain: bool,
+    /// Expand abstract mood words with the thesaurus before embedding
+    pub expand: bool,
+    /// Extra thesaurus file layered over the bundled and user ones
+    pub thesaurus: Option<PathBuf>,
+    /// Texts each color is embedded under, with `{name}` and `{description}`
+    /// filled in
+    pub templates: Vec<String>,
+    pub pooling: Pooling,
+    pub embedder: EmbedderKind,
+    /// Re-weight scores with saved thumbs up/down feedback
+    pub use_feedback: bool,
+    /// Only draw from colors with one of these tags (or hue families)
+    pub only: Vec<String>,
+    /// Never draw from colors with any of these tags (or hue families)
+    pub exclude: Vec<String>,
+    /// Lightness, chroma, hue and temperature limits and minimum spacing
+    pub constraints: Constraints,
 }
 
 impl Default for Config {
     fn default() -> Self {
-        Self { top_k: 5 }
+        Self {
+            top_k: 5,
+            batch_size: 32,
+            diversity: 0.0,
+            temperature: None,
+            seed: None,
+            fusion: Fusion::Linear { weight: 0.25 },
+            pin_mentions: true,
+            snap_mentions: false,
+            index: IndexKind::Auto,
+            candidate_pool: 500,
+            min_score: 0.15,
+            relative_floor: 0.5,
+            explain: false,
+            expand: false,
+            thesaurus: None,
+            templates: vec![DEFAULT_TEMPLATE.to_string()],
+            pooling: Pooling::Max,
+            embedder: EmbedderKind::Model,
+            use_feedback: true,
+            only: Vec::new(),
+            exclude: Vec::new(),
+            constraints: Constraints::default(),
+        }
     }
 }
 
@@ -18,4 +149,98 @@ impl Config {
         self.top_k = top_k;
         self
     }
+
+    pub fn with_batch_size(mut self, batch_size: usize) -> Self {
+        self.batch_size = batch_size.max(1);
+        self
+    }
+
+    pub fn with_diversity(mut self, diversity: f32) -> Self {
+        self.diversity = diversity.clamp(0.0, 1.0);
+        self
+    }
+
+    pub fn with_temperature(mut self, temperature: f32) -> Self {
+        self.temperature = Some(temperature.max(0.0));
+        self
+    }
+
+    pub fn with_seed(mut self, seed: u64) -> Self {
+        self.seed = Some(seed);
+        self
+    }
+
+    pub fn with_fusion(mut self, fusion: Fusion) -> Self {
+        self.fusion = fusion;
+        self
+    }
+
+    pub fn with_pin_mentions(mut self, pin_mentions: bool) -> Self {
+        self.pin_mentions = pin_mentions;
+        self
+    }
+
+    pub fn with_snap_mentions(mut self, snap_mentions: bool) -> Self {
+        self.snap_mentions = snap_mentions;
+        self
+    }
+
+    pub fn with_index(mut self, index: IndexKind) -> Self {
+        self.index = index;
+        self
+    }
+
+    pub fn with_min_score(mut self, min_score: f32) -> Self {
+        self.min_score = min_score;
+        self
+    }
+
+    pub fn with_relative_floor(mut self, relative_floor: f32) -> Self {
+        self.relative_floor = relative_floor.clamp(0.0, 1.0);
+        self
+    }
+
+    pub fn with_explain(mut self, explain: bool) -> Self {
+        self.explain = explain;
+        self
+    }
+
+    pub fn with_expansion(mut self, expand: bool, thesaurus: Option<PathBuf>) -> Self {
+        self.expand = expand;
+        self.thesaurus = thesaurus;
+        self
+    }
+
+    pub fn with_templates(mut self, templates: Vec<String>) -> Self {
+        if !templates.is_empty() {
+            self.templates = templates;
+        }
+        self
+    }
+
+    pub fn with_pooling(mut self, pooling: Pooling) -> Self {
+        self.pooling = pooling;
+        self
+    }
+
+    pub fn with_embedder(mut self, embedder: EmbedderKind) -> Self {
+        self.embedder = embedder;
+        self
+    }
+
+    pub fn with_feedback(mut self, use_feedback: bool) -> Self {
+        self.use_feedback = use_feedback;
+        self
+    }
+
+    pub fn with_tag_filter(mut self, only: Vec<String>, exclude: Vec<String>) -> Self {
+        self.only = only;
+        self.exclude = exclude;
+        self
+    }
+
+    pub fn with_constraints(mut self, constraints: Constraints) -> Self {
+        self.constraints = constraints;
+        self
+    }
 }
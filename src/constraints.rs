@@ -0,0 +1,337 @@
+//! Palette-wide limits on lightness, chroma, hue and temperature, applied
+//! to a mood's ranking either as hard filters or as score penalties.
+
+use crate::color_space::{hue_distance, Oklch};
+use crate::matcher::ColorMatch;
+use anyhow::{bail, Context, Result};
+use serde::{Deserialize, Serialize};
+
+/// Score taken off per unit of violation in soft mode.
+const SOFT_PENALTY: f32 = 0.05;
+/// Violations beyond this count the same, so one far-off color isn't buried
+/// under colors that don't match the mood at all.
+const MAX_VIOLATION: f32 = 4.0;
+/// How far outside a range counts as one unit of violation.
+const LIGHTNESS_STEP: f32 = 0.1;
+const CHROMA_STEP: f32 = 0.04;
+const HUE_STEP: f32 = 30.0;
+/// Reported when hard constraints keep fewer than this share of candidates.
+const SPARSE_SHARE: f32 = 0.1;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Tone {
+    Warm,
+    Cool,
+}
+
+impl Tone {
+    pub fn parse(text: &str) -> Result<Self> {
+        match text.trim().to_lowercase().as_str() {
+            "warm" => Ok(Tone::Warm),
+            "cool" => Ok(Tone::Cool),
+            other => bail!("tone must be warm or cool, not '{}'", other),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Constraints {
+    /// OKLCH lightness range, 0.0 to 1.0
+    pub lightness: Option<(f32, f32)>,
+    /// OKLCH chroma range
+    pub chroma: Option<(f32, f32)>,
+    /// Hue arc in degrees, running upwards from the first to the second, so
+    /// `(330, 30)` wraps through red
+    pub hue: Option<(f32, f32)>,
+    pub tone: Option<Tone>,
+    /// Palette colors must be at least this far apart (ΔEok); 0.0 is off
+    pub min_delta_e: f32,
+    /// Penalize colors outside the ranges instead of dropping them
+    pub soft: bool,
+}
+
+/// What the constraints did to one ranking and the palettes built from it.
+#[derive(Debug, Clone, Copy)]
+pub struct ConstraintReport {
+    pub candidates: usize,
+    /// Candidates inside every range
+    pub satisfied: usize,
+    pub soft: bool,
+    /// `min_delta_e` turned away candidates a palette needed to fill up
+    pub spaced_out: bool,
+}
+
+impl ConstraintReport {
+    /// Hard constraints left too few colors to choose a palette from. When
+    /// they dropped nothing, a short ranking is the score floor's doing.
+    pub fn too_strict(&self, top_k: usize) -> bool {
+        !self.soft
+            && self.satisfied < self.candidates
+            && (self.satisfied < top_k
+                || (self.satisfied as f32) < self.candidates as f32 * SPARSE_SHARE)
+    }
+}
+
+impl Constraints {
+    /// `dark`, `mid`, `light`, or a range such as `0.2..0.5` (or `20..50`).
+    pub fn parse_lightness(text: &str) -> Result<(f32, f32)> {
+        match text.trim().to_lowercase().as_str() {
+            "dark" => Ok((0.0, 0.5)),
+            "mid" => Ok((0.4, 0.75)),
+            "light" => Ok((0.7, 1.0)),
+            range => {
+                let (low, high) = ordered(parse_range(range)?);
+                // Allow percentages
+                let scale = if high > 1.0 { 100.0 } else { 1.0 };
+                Ok((low / scale, high / scale))
+            }
+        }
+    }
+
+    /// `muted`, `vivid`, or an OKLCH chroma range such as `0.05..0.12`.
+    pub fn parse_chroma(text: &str) -> Result<(f32, f32)> {
+        match text.trim().to_lowercase().as_str() {
+            "muted" => Ok((0.0, 0.1)),
+            "vivid" => Ok((0.15, 0.5)),
+            range => parse_range(range).map(ordered),
+        }
+    }
+
+    /// A hue range in degrees such as `180..260`; `330..30` wraps past 0,
+    /// and a range spanning 360 degrees or more (`0..360`) allows any hue.
+    pub fn parse_hue(text: &str) -> Result<(f32, f32)> {
+        let (from, to) = parse_range(text)?;
+        if to - from >= 360.0 {
+            return Ok((0.0, 360.0));
+        }
+        Ok((from.rem_euclid(360.0), to.rem_euclid(360.0)))
+    }
+
+    /// Whether any per-color range or the spacing is set.
+    pub fn is_active(&self) -> bool {
+        self.limits_colors() || self.min_delta_e > 0.0
+    }
+
+    /// Whether any per-color range is set.
+    pub fn limits_colors(&self) -> bool {
+        self.lightness.is_some()
+            || self.chroma.is_some()
+            || self.hue.is_some()
+            || self.tone.is_some()
+    }
+
+    /// How far outside the ranges a color sits, 0.0 when it fits them all.
+    pub fn violation(&self, lch: &Oklch) -> f32 {
+        let mut total = 0.0;
+        if let Some(range) = self.lightness {
+            total += outside(lch.l, range) / LIGHTNESS_STEP;
+        }
+        if let Some(range) = self.chroma {
+            total += outside(lch.c, range) / CHROMA_STEP;
+        }
+        if let Some((from, to)) = self.hue {
+            total += if lch.c < Oklch::NEUTRAL_CHROMA {
+                // Greys have no hue to fall inside the arc
+                1.0
+            } else if to - from >= 360.0
+                || (lch.h - from).rem_euclid(360.0) <= (to - from).rem_euclid(360.0)
+            {
+                0.0
+            } else {
+                hue_distance(lch.h, from).min(hue_distance(lch.h, to)) / HUE_STEP
+            };
+        }
+        if let Some(tone) = self.tone {
+            // Greys sit with either temperature
+            if lch.c >= Oklch::NEUTRAL_CHROMA {
+                let warmth = lch.warmth();
+                total += match tone {
+                    Tone::Warm => (-warmth).max(0.0),
+                    Tone::Cool => warmth.max(0.0),
+                } * 2.0;
+            }
+        }
+        total
+    }
+
+    /// Why a mood's palettes may disappoint: hard constraints that left too
+    /// little to choose from, or spacing that cut a palette short.
+    pub fn warnings(&self, top_k: usize, report: Option<ConstraintReport>) -> Vec<String> {
+        let mut warnings = Vec::new();
+        let Some(report) = report else {
+            return warnings;
+        };
+        if report.too_strict(top_k) {
+            warnings.push(format!(
+                "Constraints kept {} of {} candidate colors; loosen them or add --soft-constraints.",
+                report.satisfied, report.candidates
+            ));
+        }
+        if report.spaced_out {
+            warnings.push(format!(
+                "Not enough candidates are ΔE {} apart to fill {} colors; try a lower --min-delta-e.",
+                self.min_delta_e, top_k
+            ));
+        }
+        warnings
+    }
+
+    /// Drops (or, when soft, marks down and re-sorts) candidates outside the
+    /// ranges.
+    pub fn apply(&self, ranking: &mut Vec<ColorMatch>) -> Option<ConstraintReport> {
+        if !self.is_active() {
+            return None;
+        }
+        let candidates = ranking.len();
+        let violations: Vec<f32> = ranking
+            .iter()
+            .map(|candidate| {
+                candidate
+                    .color
+                    .to_oklch()
+                    .map(|lch| self.violation(&lch))
+                    .unwrap_or(0.0)
+            })
+            .collect();
+        let satisfied = violations.iter().filter(|v| **v == 0.0).count();
+
+        if self.soft {
+            for (candidate, violation) in ranking.iter_mut().zip(&violations) {
+                candidate.score -= SOFT_PENALTY * violation.min(MAX_VIOLATION);
+            }
+            ranking.sort_by(|a, b| b.score.total_cmp(&a.score));
+        } else {
+            let mut violations = violations.into_iter();
+            ranking.retain(|_| violations.next() == Some(0.0));
+        }
+        Some(ConstraintReport {
+            candidates,
+            satisfied,
+            soft: self.soft,
+            spaced_out: false,
+        })
+    }
+}
+
+/// Distance from `value` to the nearest end of `(low, high)`, 0.0 inside.
+fn outside(value: f32, (low, high): (f32, f32)) -> f32 {
+    (low - value).max(value - high).max(0.0)
+}
+
+fn ordered((a, b): (f32, f32)) -> (f32, f32) {
+    (a.min(b), a.max(b))
+}
+
+/// `A..B`, `A:B` or `A-B`, where a `-` straight after the separator or at
+/// the start is a sign: `-30-30` runs from -30 to 30.
+fn parse_range(text: &str) -> Result<(f32, f32)> {
+    let text = text.trim();
+    let (low, high) = text
+        .split_once("..")
+        .or_else(|| text.split_once(':'))
+        .or_else(|| {
+            let dash = text
+                .char_indices()
+                .skip(1)
+                .find(|(i, c)| *c == '-' && !text[..*i].ends_with(['-', 'e', 'E']))?
+                .0;
+            Some((&text[..dash], &text[dash + 1..]))
+        })
+        .with_context(|| format!("expected a range like 0.2..0.5, got '{}'", text))?;
+    let parse = |value: &str| -> Result<f32> {
+        let value = value.trim();
+        let number: f32 = value
+            .parse()
+            .with_context(|| format!("'{}' is not a number", value))?;
+        // A NaN bound would make every comparison false
+        if !number.is_finite() {
+            bail!("'{}' is not a finite number", value);
+        }
+        Ok(number)
+    };
+    Ok((parse(low)?, parse(high)?))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn hard(satisfied: usize, candidates: usize) -> Option<ConstraintReport> {
+        Some(ConstraintReport {
+            candidates,
+            satisfied,
+            soft: false,
+            spaced_out: false,
+        })
+    }
+
+    #[test]
+    fn parses_ranges() {
+        assert_eq!(Constraints::parse_lightness("20..50").unwrap(), (0.2, 0.5));
+        assert_eq!(
+            Constraints::parse_chroma("0.12:0.05").unwrap(),
+            (0.05, 0.12)
+        );
+        assert_eq!(Constraints::parse_hue("330..390").unwrap(), (330.0, 30.0));
+        assert_eq!(Constraints::parse_hue("-30-30").unwrap(), (330.0, 30.0));
+        assert_eq!(Constraints::parse_hue("-90--30").unwrap(), (270.0, 330.0));
+        assert_eq!(
+            Constraints::parse_chroma("0.05 - 0.1").unwrap(),
+            (0.05, 0.1)
+        );
+        assert_eq!(Constraints::parse_chroma("1e-2-0.1").unwrap(), (0.01, 0.1));
+        assert!(Constraints::parse_chroma("0.1").is_err());
+    }
+
+    #[test]
+    fn a_full_turn_allows_any_hue() {
+        for range in ["0..360", "-180..180", "0..720"] {
+            let hue = Constraints {
+                hue: Some(Constraints::parse_hue(range).unwrap()),
+                ..Constraints::default()
+            };
+            for h in [0.0, 90.0, 359.0] {
+                let lch = Oklch { l: 0.6, c: 0.1, h };
+                assert_eq!(hue.violation(&lch), 0.0, "{} at {}", range, h);
+            }
+        }
+    }
+
+    #[test]
+    fn rejects_non_finite_bounds() {
+        for range in ["nan..1", "inf..2", "0..NaN", "-inf..0.5"] {
+            assert!(Constraints::parse_chroma(range).is_err(), "{}", range);
+        }
+        assert!(Constraints::parse_lightness("0.2..inf").is_err());
+        assert!(Constraints::parse_hue("nan..30").is_err());
+    }
+
+    #[test]
+    fn warns_when_too_few_candidates_survive() {
+        let constraints = Constraints::default();
+        assert_eq!(constraints.warnings(5, hard(3, 500)).len(), 1);
+        assert!(constraints.warnings(5, hard(200, 500)).is_empty());
+        assert!(constraints.warnings(5, None).is_empty());
+        // Nothing was filtered out, so the score floor kept the ranking short
+        assert!(constraints.warnings(5, hard(3, 3)).is_empty());
+    }
+
+    #[test]
+    fn blames_spacing_only_when_it_rejected_a_candidate() {
+        let spaced = Constraints {
+            min_delta_e: 0.1,
+            ..Constraints::default()
+        };
+        let mut ranking = vec![];
+        let report = spaced.apply(&mut ranking);
+        assert!(spaced.warnings(5, report).is_empty());
+
+        let report = report.map(|report| ConstraintReport {
+            spaced_out: true,
+            ..report
+        });
+        assert_eq!(spaced.warnings(5, report).len(), 1);
+    }
+}
@@ -0,0 +1,289 @@
+//! Words for colors that arrive without any: names borrowed from the nearest
+//! catalog color and descriptions derived from OKLCH attributes.
+
+use crate::color_space::{hue_distance, HueFamily, Oklab, Oklch};
+use crate::colors::{load_catalog, Color};
+use crate::display::PaletteDisplay;
+use crate::matcher::ColorMatch;
+use crate::swatches::{load_swatches, SwatchFormat};
+use crate::tags::tag_catalog;
+use anyhow::Result;
+use colored::*;
+use std::collections::HashMap;
+use std::path::Path;
+
+pub fn lightness_word(l: f32) -> &'static str {
+    match l {
+        l if l < 0.25 => "very dark",
+        l if l < 0.45 => "dark",
+        l if l < 0.65 => "mid-toned",
+        l if l < 0.85 => "light",
+        _ => "pale",
+    }
+}
+
+pub fn chroma_word(c: f32) -> &'static str {
+    match c {
+        c if c < 0.06 => "greyish",
+        c if c < 0.1 => "muted",
+        c if c < 0.16 => "soft",
+        _ => "vivid",
+    }
+}
+
+/// OKLCH hue of each named hue, with the adjective used when a color leans
+/// towards it.
+const HUE_ANCHORS: [(f32, HueFamily, &str); 8] = [
+    (25.0, HueFamily::Red, "reddish"),
+    (60.0, HueFamily::Orange, "orangey"),
+    (105.0, HueFamily::Yellow, "yellowish"),
+    (142.0, HueFamily::Green, "greenish"),
+    (195.0, HueFamily::Cyan, "teal"),
+    (255.0, HueFamily::Blue, "bluish"),
+    (305.0, HueFamily::Purple, "purplish"),
+    (350.0, HueFamily::Pink, "pinkish"),
+];
+
+/// The nearest named hue, qualified by the next nearest when the color sits
+/// well between them: "bluish green" rather than just "green".
+pub fn hue_name(h: f32) -> String {
+    let mut anchors = HUE_ANCHORS.to_vec();
+    anchors.sort_by(|a, b| hue_distance(h, a.0).total_cmp(&hue_distance(h, b.0)));
+    let (nearest, second) = (anchors[0], anchors[1]);
+    let gap = hue_distance(nearest.0, second.0);
+    if hue_distance(h, nearest.0) < gap / 3.0 {
+        nearest.1.name().to_string()
+    } else {
+        format!("{} {}", second.2, nearest.1.name())
+    }
+}
+
+/// Dark, not too saturated oranges read as brown rather than orange.
+fn brown_name(lch: &Oklch) -> Option<String> {
+    if lch.l >= 0.55 || lch.c >= 0.17 || !(20.0..100.0).contains(&lch.h) {
+        return None;
+    }
+    Some(match lch.h {
+        h if h < 45.0 => "reddish brown".to_string(),
+        h if h > 80.0 => "olive brown".to_string(),
+        _ => "brown".to_string(),
+    })
+}
+
+/// "warm" or "cool" for colors with enough chroma to have a temperature.
+pub fn temperature_word(lch: &Oklch) -> Option<&'static str> {
+    if lch.c < Oklch::NEUTRAL_CHROMA {
+        return None;
+    }
+    match lch.warmth() {
+        w if w > 0.3 => Some("warm"),
+        w if w < -0.3 => Some("cool"),
+        _ => None,
+    }
+}
+
+/// A description from lightness, chroma, temperature and hue, plus the
+/// closest named catalog colors, e.g. "A muted, dark, cool bluish green,
+/// similar to Teal." This is what the embedding model matches moods
+/// against, so it favors plain color words.
+pub fn synthesize_description(color: &Color, catalog: &[Color]) -> String {
+    let Some(lab) = color.to_oklab() else {
+        return String::new();
+    };
+    let lch = lab.to_oklch();
+
+    let mut words = Vec::new();
+    let noun = if lch.hue_family() == HueFamily::Neutral {
+        match lch.l {
+            l if l < 0.2 => "near-black".to_string(),
+            l if l > 0.95 => "near-white".to_string(),
+            l => {
+                words.push(lightness_word(l));
+                "grey".to_string()
+            }
+        }
+    } else {
+        words.push(chroma_word(lch.c));
+        words.push(lightness_word(lch.l));
+        words.extend(temperature_word(&lch));
+        brown_name(&lch).unwrap_or_else(|| hue_name(lch.h))
+    };
+    let mut description = if words.is_empty() {
+        format!("A {}", noun)
+    } else {
+        format!("A {} {}", words.join(", "), noun)
+    };
+
+    let neighbours: Vec<&str> = neighbours(&lab, catalog, &color.name, NEIGHBOURS)
+        .into_iter()
+        .map(|known| known.name.as_str())
+        .collect();
+    if !neighbours.is_empty() {
+        description.push_str(", similar to ");
+        description.push_str(&neighbours.join(" and "));
+    }
+    description.push('.');
+    description
+}
+
+/// Named catalog colors mentioned in a synthesized description.
+const NEIGHBOURS: usize = 2;
+/// Catalog colors further than this (ΔEok) aren't "similar".
+const NEIGHBOUR_DISTANCE: f32 = 0.08;
+
+/// Up to `n` catalog colors within `NEIGHBOUR_DISTANCE`, closest first,
+/// skipping `exclude` and hex-named entries.
+fn neighbours<'a>(lab: &Oklab, catalog: &'a [Color], exclude: &str, n: usize) -> Vec<&'a Color> {
+    let mut close: Vec<(&Color, f32)> = catalog
+        .iter()
+        .filter(|known| !known.name.eq_ignore_ascii_case(exclude) && !known.name.starts_with('#'))
+        .filter_map(|known| Some((known, known.to_oklab()?.delta_e(lab))))
+        .filter(|(_, distance)| *distance < NEIGHBOUR_DISTANCE)
+        .collect();
+    close.sort_by(|a, b| a.1.total_cmp(&b.1));
+    close.into_iter().take(n).map(|(known, _)| known).collect()
+}
+
+/// The catalog color perceptually closest to `lab`, with its ΔEok.
+pub fn nearest<'a>(lab: &Oklab, catalog: &'a [Color]) -> Option<(&'a Color, f32)> {
+    catalog
+        .iter()
+        .filter_map(|color| Some((color, color.to_oklab()?.delta_e(lab))))
+        .min_by(|a, b| a.1.total_cmp(&b.1))
+}
+
+/// Names unnamed colors after their nearest catalog color (numbered when
+/// several land on the same one) and gives undescribed colors a synthesized
+/// description.
+pub fn fill_missing(colors: &mut [Color], catalog: &[Color]) {
+    let mut used: HashMap<String, usize> = HashMap::new();
+    for color in colors.iter() {
+        if !color.name.is_empty() {
+            used.insert(color.name.to_lowercase(), 1);
+        }
+    }
+
+    for color in colors.iter_mut() {
+        let Some(lab) = color.to_oklab() else {
+            continue;
+        };
+        if color.name.is_empty() {
+            let base = nearest(&lab, catalog)
+                .map(|(known, _)| known.name.clone())
+                .unwrap_or_else(|| color.hex.clone());
+            let seen = used.entry(base.to_lowercase()).or_default();
+            *seen += 1;
+            color.name = if *seen == 1 {
+                base
+            } else {
+                format!("{} {}", base, seen)
+            };
+        }
+        if color.description.trim().is_empty() {
+            color.description = synthesize_description(color, catalog);
+        }
+    }
+}
+
+/// Prints names and descriptions for swatch files, catalogs or hex values,
+/// alongside the closest color in `catalog`.
+pub fn run_describe(inputs: &[String], catalog: &[Color]) -> Result<()> {
+    let mut colors = Vec::new();
+    for input in inputs {
+        let path = Path::new(input);
+        if let Some(color) = Color::from_hex(input).filter(|_| !path.exists()) {
+            colors.push(Color {
+                name: String::new(),
+                ..color
+            });
+        } else if let Some(format) = SwatchFormat::from_path(path) {
+            colors.extend(load_swatches(path, format)?);
+        } else {
+            colors.extend(load_catalog(path)?);
+        }
+    }
+    fill_missing(&mut colors, catalog);
+    tag_catalog(&mut colors, "");
+
+    for color in &colors {
+        println!(
+            "{} {} {}",
+            PaletteDisplay::swatches(&[ColorMatch::unscored(color.clone())]),
+            color.name.bold(),
+            color.hex.bright_black()
+        );
+        println!("   {}", color.description.italic().bright_black());
+        if !color.tags.is_empty() {
+            println!("   {} {}", "tags:".cyan(), color.tags.join(", "));
+        }
+        if let Some((known, distance)) = color.to_oklab().and_then(|lab| nearest(&lab, catalog)) {
+            println!(
+                "   {} {} {} (ΔE {:.3})",
+                "≈".cyan(),
+                known.name,
+                known.hex.bright_black(),
+                distance
+            );
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn catalog() -> Vec<Color> {
+        vec![
+            Color::new("Teal", "#008080", "A calm blue-green."),
+            Color::new("Dark Cyan", "#008B8B", "A deep cyan."),
+            Color::new("Brick", "#8B3A2B", "A weathered red-brown."),
+            Color::new("#FF0000", "#FF0000", ""),
+        ]
+    }
+
+    fn describe(hex: &str) -> String {
+        synthesize_description(&Color::from_hex(hex).unwrap(), &catalog())
+    }
+
+    #[test]
+    fn synthesizes_descriptions() {
+        assert_eq!(
+            describe("#007F7F"),
+            "A muted, mid-toned, cool cyan, similar to Teal and Dark Cyan."
+        );
+        assert_eq!(
+            describe("#8B4513"),
+            "A soft, mid-toned, warm brown, similar to Brick."
+        );
+        assert_eq!(describe("#6B8E23"), "A soft, mid-toned yellowish green.");
+        assert_eq!(describe("#808080"), "A mid-toned grey.");
+        assert_eq!(describe("#050505"), "A near-black.");
+        // Hex-named catalog entries are never offered as "similar"
+        assert_eq!(describe("#FF1010"), "A vivid, mid-toned, warm red.");
+    }
+
+    #[test]
+    fn names_unnamed_colors_after_the_nearest_catalog_color() {
+        let unnamed = |hex: &str| Color {
+            name: String::new(),
+            ..Color::from_hex(hex).unwrap()
+        };
+        let mut colors = vec![
+            unnamed("#007F7F"),
+            Color::new("Lagoon", "#017E7E", "Kept as written."),
+            unnamed("#018080"),
+            unnamed("#8B3A2C"),
+        ];
+        fill_missing(&mut colors, &catalog());
+
+        let names: Vec<&str> = colors.iter().map(|c| c.name.as_str()).collect();
+        assert_eq!(names, ["Teal", "Lagoon", "Teal 2", "Brick"]);
+        assert_eq!(colors[1].description, "Kept as written.");
+        assert!(colors[0].description.starts_with("A muted, mid-toned"));
+
+        let mut alone = vec![unnamed("#123456")];
+        fill_missing(&mut alone, &[]);
+        assert_eq!(alone[0].name, "#123456");
+    }
+}
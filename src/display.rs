@@ -3,8 +3,43 @@ use colored::*;
 
 pub struct PaletteDisplay;
 
+/// How palettes are printed, shared by one-shot, variation and interactive
+/// output.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct DisplayOptions {
+    pub show_bar: bool,
+    pub no_names: bool,
+    /// Show the semantic and lexical parts of each score
+    pub verbose: bool,
+    /// Print a share code under each palette
+    pub share: bool,
+}
+
+pub const EXAMPLE_MOODS: &[&str] = &[
+    "cozy cabin in winter",
+    "tropical beach sunset",
+    "cyberpunk city at night",
+    "peaceful forest morning",
+    "vintage romance",
+    "energetic summer festival",
+    "calm ocean breeze",
+    "autumn harvest market",
+    "rainy tokyo night",
+    "spring cherry blossoms",
+    "desert canyon at noon",
+    "minimalist scandinavian kitchen",
+];
+
 impl PaletteDisplay {
-    pub fn display_palette(matches: &[ColorMatch]) {
+    pub fn show(matches: &[ColorMatch], options: &DisplayOptions) {
+        if options.show_bar {
+            Self::display_color_bar(matches, options.no_names);
+        } else {
+            Self::display_palette(matches, options.verbose);
+        }
+    }
+
+    pub fn display_palette(matches: &[ColorMatch], verbose: bool) {
         if matches.is_empty() {
             println!("{}", "No colors found for the given mood.".red());
             return;
@@ -20,21 +55,52 @@ impl PaletteDisplay {
             // Create a color block using the actual hex color
             let color_block = Self::create_color_block(&color.hex);
 
+            let lock = if color_match.locked { " 🔒" } else { "" };
+
             println!(
-                "{} {} {} | {} | Score: {:.3}",
+                "{} {} {} | {} | Score: {:.3}{}",
                 format!("{}.", i + 1).bright_black(),
                 color_block,
                 color.name.bold(),
                 color.hex.bright_black(),
-                format!("{:.3}", score).bright_green()
+                format!("{:.3}", score).bright_green(),
+                lock
             );
 
+            if verbose {
+                let mut parts = format!(
+                    "semantic {:.3} · lexical {:.3} · z {:.1}",
+                    color_match.semantic, color_match.lexical, color_match.confidence
+                );
+                if color_match.feedback != 0.0 {
+                    parts.push_str(&format!(" · feedback {:+.3}", color_match.feedback));
+                }
+                println!("   {}", parts.bright_black());
+            }
+
             // Display description with word wrapping
             let wrapped_description = Self::wrap_text(&color.description, 60);
             for line in wrapped_description {
                 println!("   {}", line.italic().bright_black());
             }
 
+            if let Some(explanation) = &color_match.explanation {
+                let drivers: Vec<String> = explanation
+                    .top_drivers(3)
+                    .map(|driver| format!("{} (+{:.3})", driver.word, driver.influence))
+                    .collect();
+                if !drivers.is_empty() {
+                    println!("   {} {}", "💬 driven by".cyan(), drivers.join(", "));
+                }
+                if !explanation.overlap.is_empty() {
+                    println!(
+                        "   {} {}",
+                        "🔗 shares".cyan(),
+                        explanation.overlap.join(", ")
+                    );
+                }
+            }
+
             if i < matches.len() - 1 {
                 println!();
             }
@@ -62,11 +128,12 @@ impl PaletteDisplay {
                 print!(
                     "{}",
                     format!(
-                        "{} {} ({}) - {:.3}\n",
+                        "{} {} ({}) - {:.3}{}\n",
                         color_block,
                         color_match.color.name,
                         color_match.color.hex,
-                        color_match.score
+                        color_match.score,
+                        if color_match.locked { " 🔒" } else { "" }
                     )
                     .bright_green()
                 );
@@ -75,6 +142,14 @@ impl PaletteDisplay {
         println!();
     }
 
+    /// One block per color, for compact one-line listings.
+    pub fn swatches(matches: &[ColorMatch]) -> String {
+        matches
+            .iter()
+            .map(|color_match| Self::create_color_block(&color_match.color.hex).to_string())
+            .collect()
+    }
+
     fn create_color_block(hex: &str) -> ColoredString {
         // Convert hex to RGB for terminal colors
         if let Some((r, g, b)) = Self::hex_to_rgb(hex) {
@@ -105,7 +180,7 @@ impl PaletteDisplay {
         for word in words {
             if current_line.is_empty() {
                 current_line = word.to_string();
-            } else if current_line.len() + word.len() + 1 <= max_width {
+            } else if current_line.len() + word.len() < max_width {
                 current_line.push(' ');
                 current_line.push_str(word);
             } else {
@@ -142,16 +217,7 @@ impl PaletteDisplay {
 
     pub fn display_examples() {
         println!("\n{}", "💡 Try these example moods:".bold().yellow());
-        let examples = [
-            "cozy cabin in winter",
-            "tropical beach sunset",
-            "cyberpunk city at night",
-            "peaceful forest morning",
-            "vintage romance",
-            "energetic summer festival",
-        ];
-
-        for example in &examples {
+        for example in EXAMPLE_MOODS {
             println!("   • {}", example.italic());
         }
     }
@@ -1,49 +1,92 @@
+use crate::lexical::tokenize;
 use anyhow::Result;
 use fastembed::{EmbeddingModel, InitOptions, TextEmbedding};
 
+/// Width of the hashing embedder's vectors, matching the MiniLM models.
+const HASHING_DIMS: usize = 384;
+
+enum Backend {
+    Model(Box<TextEmbedding>),
+    /// Deterministic feature hashing of words and character trigrams: no
+    /// download and identical output everywhere, for evaluation gates.
+    Hashing,
+}
+
 pub struct EmbeddingGenerator {
-    embedder: TextEmbedding,
+    backend: Backend,
+    model_name: &'static str,
 }
 
 impl EmbeddingGenerator {
     pub async fn new() -> Result<Self> {
         // Try AllMiniLML12V2 first, fallback to L6V2
-        let embedder = match TextEmbedding::try_new(
+        let (embedder, model_name) = match TextEmbedding::try_new(
             InitOptions::new(EmbeddingModel::AllMiniLML12V2).with_show_download_progress(true),
         ) {
-            Ok(model) => model,
+            Ok(model) => (model, "AllMiniLML12V2"),
             Err(e) => {
-                println!("⚠️  Failed to load AllMiniLML12V2: {}", e);
-                println!("🔄 Falling back to AllMiniLML6V2...");
-                TextEmbedding::try_new(
+                eprintln!("⚠️  Failed to load AllMiniLML12V2: {}", e);
+                eprintln!("🔄 Falling back to AllMiniLML6V2...");
+                let model = TextEmbedding::try_new(
                     InitOptions::new(EmbeddingModel::AllMiniLML6V2)
                         .with_show_download_progress(true),
-                )?
+                )?;
+                (model, "AllMiniLML6V2")
             }
         };
 
-        Ok(Self { embedder })
+        Ok(Self {
+            backend: Backend::Model(Box::new(embedder)),
+            model_name,
+        })
     }
 
-    pub async fn generate_embeddings(&mut self, texts: &[String]) -> Result<Vec<Vec<f32>>> {
-        let text_refs: Vec<&str> = texts.iter().map(|s| s.as_str()).collect();
-        let embeddings = self.embedder.embed(text_refs, None)?;
-        Ok(embeddings)
+    pub fn hashing() -> Self {
+        Self {
+            backend: Backend::Hashing,
+            model_name: "hashing-384",
+        }
+    }
+
+    /// Name of the model actually loaded, which may be the fallback.
+    pub fn model_name(&self) -> &'static str {
+        self.model_name
     }
 
-    pub fn cosine_similarity(a: &[f32], b: &[f32]) -> f32 {
-        if a.len() != b.len() {
-            return 0.0;
+    pub async fn generate_embeddings(&mut self, texts: &[String]) -> Result<Vec<Vec<f32>>> {
+        match &mut self.backend {
+            Backend::Model(embedder) => {
+                let text_refs: Vec<&str> = texts.iter().map(|s| s.as_str()).collect();
+                let embeddings = embedder.embed(text_refs, None)?;
+                Ok(embeddings)
+            }
+            Backend::Hashing => Ok(texts.iter().map(|text| hash_embedding(text)).collect()),
         }
+    }
+}
 
-        let dot_product: f32 = a.iter().zip(b.iter()).map(|(x, y)| x * y).sum();
-        let norm_a: f32 = a.iter().map(|x| x * x).sum::<f32>().sqrt();
-        let norm_b: f32 = b.iter().map(|x| x * x).sum::<f32>().sqrt();
+fn hash_embedding(text: &str) -> Vec<f32> {
+    let mut vector = vec![0.0; HASHING_DIMS];
+    for token in tokenize(text) {
+        add_feature(&mut vector, &token, 1.0);
 
-        if norm_a == 0.0 || norm_b == 0.0 {
-            return 0.0;
+        let padded: Vec<char> = format!(" {} ", token).chars().collect();
+        for trigram in padded.windows(3) {
+            add_feature(&mut vector, &trigram.iter().collect::<String>(), 0.5);
         }
+    }
+    vector
+}
 
-        dot_product / (norm_a * norm_b)
+/// Adds `weight` to the feature's bucket, with a hashed sign so unrelated
+/// features sharing a bucket tend to cancel rather than pile up.
+fn add_feature(vector: &mut [f32], feature: &str, weight: f32) {
+    let mut hash: u64 = 0xcbf2_9ce4_8422_2325;
+    for byte in feature.bytes() {
+        hash ^= byte as u64;
+        hash = hash.wrapping_mul(0x0000_0100_0000_01b3);
     }
+    let bucket = (hash % vector.len() as u64) as usize;
+    let sign = if hash >> 63 == 0 { 1.0 } else { -1.0 };
+    vector[bucket] += sign * weight;
 }
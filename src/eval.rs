@@ -0,0 +1,384 @@
+use crate::config::{Config, EmbedderKind, Fusion, IndexKind, Pooling};
+use crate::matcher::MoodPaletteGenerator;
+use anyhow::{bail, Context, Result};
+use colored::*;
+use serde::Deserialize;
+use std::fs;
+use std::path::PathBuf;
+
+/// One line of a golden set: a mood, the catalog colors a good palette for
+/// it would draw from, and others that would be fine too.
+#[derive(Debug, Deserialize)]
+pub struct GoldenCase {
+    pub mood: String,
+    pub expected: Vec<String>,
+    #[serde(default)]
+    pub acceptable: Vec<String>,
+}
+
+impl GoldenCase {
+    /// Graded relevance for nDCG: 2 for expected, 1 for acceptable.
+    fn relevance(&self, name: &str) -> u32 {
+        let listed = |names: &[String]| names.iter().any(|n| n.eq_ignore_ascii_case(name));
+        if listed(&self.expected) {
+            2
+        } else if listed(&self.acceptable) {
+            1
+        } else {
+            0
+        }
+    }
+
+    fn relevant_count(&self) -> usize {
+        self.expected.len() + self.acceptable.len()
+    }
+}
+
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Metrics {
+    pub precision: f32,
+    pub recall: f32,
+    pub ndcg: f32,
+    pub mrr: f32,
+}
+
+impl Metrics {
+    const NAMES: [&'static str; 4] = ["precision", "recall", "ndcg", "mrr"];
+
+    fn get(&self, name: &str) -> Option<f32> {
+        match name {
+            "precision" => Some(self.precision),
+            "recall" => Some(self.recall),
+            "ndcg" => Some(self.ndcg),
+            "mrr" => Some(self.mrr),
+            _ => None,
+        }
+    }
+
+    fn mean(all: &[Metrics]) -> Metrics {
+        let n = all.len().max(1) as f32;
+        Metrics {
+            precision: all.iter().map(|m| m.precision).sum::<f32>() / n,
+            recall: all.iter().map(|m| m.recall).sum::<f32>() / n,
+            ndcg: all.iter().map(|m| m.ndcg).sum::<f32>() / n,
+            mrr: all.iter().map(|m| m.mrr).sum::<f32>() / n,
+        }
+    }
+}
+
+pub struct EvalOptions {
+    pub k: usize,
+    pub verbose: bool,
+    /// Minimum mean value per metric, e.g. `("ndcg", 0.3)`
+    pub gates: Vec<(String, f32)>,
+}
+
+pub fn load_golden(path: &str) -> Result<Vec<GoldenCase>> {
+    let contents = fs::read_to_string(path).with_context(|| format!("failed to read {}", path))?;
+    let mut cases = Vec::new();
+    for (line_number, line) in contents.lines().enumerate() {
+        if line.trim().is_empty() {
+            continue;
+        }
+        let case: GoldenCase = serde_json::from_str(line)
+            .with_context(|| format!("{}:{}: invalid golden case", path, line_number + 1))?;
+        cases.push(case);
+    }
+    if cases.is_empty() {
+        bail!("{} has no golden cases", path);
+    }
+    Ok(cases)
+}
+
+/// Parses a `--fail-below metric=value` gate.
+pub fn parse_gate(spec: &str) -> Result<(String, f32)> {
+    let Some((metric, value)) = spec.split_once('=') else {
+        bail!("expected metric=value, got '{}'", spec);
+    };
+    let metric = metric.trim().to_lowercase();
+    if !Metrics::NAMES.contains(&metric.as_str()) {
+        bail!(
+            "unknown metric '{}' (use {})",
+            metric,
+            Metrics::NAMES.join(", ")
+        );
+    }
+    let value = value
+        .trim()
+        .parse()
+        .with_context(|| format!("invalid threshold in '{}'", spec))?;
+    Ok((metric, value))
+}
+
+/// Applies a `--against key=value` override to a copy of the configuration
+/// under evaluation.
+pub fn apply_override(config: Config, spec: &str) -> Result<Config> {
+    let Some((key, value)) = spec.split_once('=') else {
+        bail!("expected key=value, got '{}'", spec);
+    };
+    let value = value.trim();
+    let number = || -> Result<f32> {
+        value
+            .parse()
+            .with_context(|| format!("invalid number in '{}'", spec))
+    };
+
+    Ok(match key.trim() {
+        "fusion" => config.with_fusion(match value {
+            "rrf" => Fusion::Rrf { k: 60.0 },
+            "semantic" => Fusion::Semantic,
+            "linear" => Fusion::Linear { weight: 0.25 },
+            _ => bail!("unknown fusion '{}'", value),
+        }),
+        "lexical-weight" => config.with_fusion(Fusion::Linear {
+            weight: number()?.clamp(0.0, 1.0),
+        }),
+        "pooling" => config.with_pooling(match value {
+            "max" => Pooling::Max,
+            "mean" => Pooling::Mean,
+            _ => bail!("unknown pooling '{}'", value),
+        }),
+        "template" => {
+            let mut templates = config.templates.clone();
+            templates.push(value.to_string());
+            config.with_templates(templates)
+        }
+        "templates" => config.with_templates(value.split('|').map(str::to_string).collect()),
+        "index" => config.with_index(match value {
+            "auto" => IndexKind::Auto,
+            "brute" => IndexKind::BruteForce,
+            "hnsw" => IndexKind::Hnsw,
+            _ => bail!("unknown index '{}'", value),
+        }),
+        "expand" => {
+            let thesaurus = config.thesaurus.clone();
+            config.with_expansion(value == "true", thesaurus)
+        }
+        "thesaurus" => config.with_expansion(true, Some(PathBuf::from(value))),
+        "min-score" => config.with_min_score(number()?),
+        "relative-floor" => config.with_relative_floor(number()?),
+        "embedder" => config.with_embedder(match value {
+            "model" => EmbedderKind::Model,
+            "hashing" => EmbedderKind::Hashing,
+            _ => bail!("unknown embedder '{}'", value),
+        }),
+        other => bail!("unknown setting '{}'", other),
+    })
+}
+
+/// Ranks every golden mood and scores the top `k` against the expected
+/// colors.
+pub async fn evaluate(
+    generator: &mut MoodPaletteGenerator,
+    cases: &[GoldenCase],
+    k: usize,
+) -> Result<Vec<Metrics>> {
+    let mut results = Vec::with_capacity(cases.len());
+    for case in cases {
+        let ranking = generator.rank_all(&case.mood).await?;
+        let names: Vec<&str> = ranking.iter().map(|m| m.color.name.as_str()).collect();
+        results.push(score_case(case, &names, k));
+    }
+    Ok(results)
+}
+
+fn score_case(case: &GoldenCase, ranking: &[&str], k: usize) -> Metrics {
+    let relevance: Vec<u32> = ranking.iter().map(|name| case.relevance(name)).collect();
+    let top = &relevance[..k.min(relevance.len())];
+    let hits = top.iter().filter(|r| **r > 0).count();
+
+    let dcg: f32 = top
+        .iter()
+        .enumerate()
+        .map(|(i, rel)| gain(*rel) / (i as f32 + 2.0).log2())
+        .sum();
+    let ideal: f32 = std::iter::repeat_n(2, case.expected.len())
+        .chain(std::iter::repeat_n(1, case.acceptable.len()))
+        .take(k)
+        .enumerate()
+        .map(|(i, rel)| gain(rel) / (i as f32 + 2.0).log2())
+        .sum();
+
+    Metrics {
+        precision: hits as f32 / k.max(1) as f32,
+        recall: hits as f32 / case.relevant_count().max(1) as f32,
+        ndcg: if ideal > 0.0 { dcg / ideal } else { 0.0 },
+        mrr: relevance
+            .iter()
+            .position(|rel| *rel > 0)
+            .map(|rank| 1.0 / (rank + 1) as f32)
+            .unwrap_or(0.0),
+    }
+}
+
+fn gain(relevance: u32) -> f32 {
+    (2u32.pow(relevance) - 1) as f32
+}
+
+/// Evaluates `generator` (and `other`, side by side, when given) on the
+/// golden set. Returns false when a gate fails.
+pub async fn run_eval(
+    generator: &mut MoodPaletteGenerator,
+    other: Option<&mut MoodPaletteGenerator>,
+    path: &str,
+    options: &EvalOptions,
+) -> Result<bool> {
+    let cases = load_golden(path)?;
+    let k = options.k;
+    println!(
+        "\n{} {} cases from {}, k = {}",
+        "🧪 Evaluating:".bold().bright_blue(),
+        cases.len(),
+        path,
+        k
+    );
+
+    let results = evaluate(generator, &cases, k).await?;
+    let other_results = match other {
+        Some(other) => Some(evaluate(other, &cases, k).await?),
+        None => None,
+    };
+
+    if options.verbose {
+        for (i, case) in cases.iter().enumerate() {
+            match &other_results {
+                Some(other) => println!(
+                    "   {:<36} nDCG {:.3} → {:.3}",
+                    case.mood, results[i].ndcg, other[i].ndcg
+                ),
+                None => println!(
+                    "   {:<36} nDCG {:.3}  P {:.2}  R {:.2}  RR {:.2}",
+                    case.mood,
+                    results[i].ndcg,
+                    results[i].precision,
+                    results[i].recall,
+                    results[i].mrr
+                ),
+            }
+        }
+    }
+
+    let mean = Metrics::mean(&results);
+    let other_mean = other_results.as_deref().map(Metrics::mean);
+    println!("{}", "─".repeat(50).bright_black());
+    match other_mean {
+        Some(_) => println!("{:<14}{:>10}{:>10}{:>10}", "", "current", "against", "Δ"),
+        None => println!("{:<14}{:>10}", "", "current"),
+    }
+    for name in Metrics::NAMES {
+        let label = match name {
+            "precision" | "recall" | "ndcg" => format!("{}@{}", name, k),
+            _ => name.to_string(),
+        };
+        let value = mean.get(name).unwrap_or(0.0);
+        match other_mean.and_then(|m| m.get(name)) {
+            Some(against) => {
+                let delta = format!("{:+.3}", against - value);
+                let delta = if against > value {
+                    delta.green()
+                } else if against < value {
+                    delta.red()
+                } else {
+                    delta.normal()
+                };
+                println!("{:<14}{:>10.3}{:>10.3}{:>10}", label, value, against, delta);
+            }
+            None => println!("{:<14}{:>10.3}", label, value),
+        }
+    }
+
+    let mut passed = true;
+    for (metric, threshold) in &options.gates {
+        let value = mean.get(metric).unwrap_or(0.0);
+        if value < *threshold {
+            println!(
+                "{} {} {:.3} is below {:.3}",
+                "❌ Gate failed:".red().bold(),
+                metric,
+                value,
+                threshold
+            );
+            passed = false;
+        }
+    }
+    Ok(passed)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::colors::get_color_data;
+
+    fn case() -> GoldenCase {
+        GoldenCase {
+            mood: "test".to_string(),
+            expected: vec!["Teal".to_string(), "Navy".to_string()],
+            acceptable: vec!["Sand".to_string()],
+        }
+    }
+
+    #[test]
+    fn scores_a_hand_built_ranking() {
+        let metrics = score_case(&case(), &["Coral", "teal", "Sand", "Gold", "Navy"], 3);
+
+        assert!((metrics.precision - 2.0 / 3.0).abs() < 1e-6);
+        assert!((metrics.recall - 2.0 / 3.0).abs() < 1e-6);
+        assert!((metrics.mrr - 0.5).abs() < 1e-6);
+        // DCG 3/log2(3) + 1/log2(4) over the ideal 3 + 3/log2(3) + 1/log2(4)
+        let dcg = 3.0 / 3f32.log2() + 0.5;
+        assert!((metrics.ndcg - dcg / (3.0 + dcg)).abs() < 1e-6);
+    }
+
+    #[test]
+    fn scores_a_ranking_without_hits() {
+        let metrics = score_case(&case(), &["Coral", "Gold"], 10);
+        assert_eq!(metrics.precision, 0.0);
+        assert_eq!(metrics.recall, 0.0);
+        assert_eq!(metrics.ndcg, 0.0);
+        assert_eq!(metrics.mrr, 0.0);
+    }
+
+    #[test]
+    fn parses_gates() {
+        assert_eq!(parse_gate("NDCG = 0.3").unwrap(), ("ndcg".to_string(), 0.3));
+        assert_eq!(parse_gate("mrr=0.25").unwrap(), ("mrr".to_string(), 0.25));
+        assert!(parse_gate("ndcg").is_err());
+        assert!(parse_gate("f1=0.5").is_err());
+        assert!(parse_gate("recall=high").is_err());
+    }
+
+    #[test]
+    fn applies_overrides() {
+        let config = apply_override(Config::new(), "fusion=rrf").unwrap();
+        assert_eq!(config.fusion, Fusion::Rrf { k: 60.0 });
+        let config = apply_override(config, "pooling=mean").unwrap();
+        assert_eq!(config.pooling, Pooling::Mean);
+        assert!(apply_override(Config::new(), "fusion=loud").is_err());
+        assert!(apply_override(Config::new(), "colour=red").is_err());
+    }
+
+    /// Regression gate: the deterministic hashing embedder must keep ranking
+    /// the golden set at least this well.
+    #[tokio::test]
+    async fn golden_set_passes_gates() {
+        let config = Config::new()
+            .with_embedder(EmbedderKind::Hashing)
+            .with_feedback(false);
+        let mut generator = MoodPaletteGenerator::new(config, get_color_data())
+            .await
+            .unwrap();
+        let options = EvalOptions {
+            k: 10,
+            verbose: false,
+            gates: vec![
+                parse_gate("ndcg=0.15").unwrap(),
+                parse_gate("mrr=0.3").unwrap(),
+            ],
+        };
+        let golden = concat!(env!("CARGO_MANIFEST_DIR"), "/data/golden.jsonl");
+
+        assert!(run_eval(&mut generator, None, golden, &options)
+            .await
+            .unwrap());
+    }
+}
@@ -0,0 +1,125 @@
+use crate::lexical::tokenize;
+use anyhow::{bail, Context, Result};
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+const BUNDLED: &str = include_str!("../data/thesaurus.txt");
+
+/// Weight of each expanded query relative to the mood itself.
+pub const EXPANSION_WEIGHT: f32 = 0.5;
+
+/// A mood term and the concrete descriptors it expands to.
+#[derive(Debug, Clone)]
+pub struct Expansion {
+    pub term: String,
+    pub descriptors: Vec<String>,
+}
+
+impl Expansion {
+    /// The text embedded in place of the term.
+    pub fn query(&self) -> String {
+        self.descriptors.join(", ")
+    }
+}
+
+/// Maps abstract mood words ("hygge", "melancholy") to things with a color,
+/// from the bundled list plus the user's own file.
+pub struct Thesaurus {
+    /// Keyed by the term's tokens, so "Cozy" and "cozies" meet "cozy"
+    entries: BTreeMap<Vec<String>, Expansion>,
+    longest: usize,
+}
+
+impl Thesaurus {
+    /// The bundled thesaurus, overlaid with `~/.config/colorify/thesaurus.txt`
+    /// and then `extra` when given.
+    pub fn load(extra: Option<&Path>) -> Result<Self> {
+        let mut thesaurus = Self {
+            entries: BTreeMap::new(),
+            longest: 1,
+        };
+        thesaurus.add(BUNDLED, "bundled thesaurus")?;
+
+        if let Some(path) = user_path().filter(|path| path.exists()) {
+            thesaurus.add_file(&path)?;
+        }
+        if let Some(path) = extra {
+            thesaurus.add_file(path)?;
+        }
+
+        Ok(thesaurus)
+    }
+
+    fn add_file(&mut self, path: &Path) -> Result<()> {
+        let contents = fs::read_to_string(path)
+            .with_context(|| format!("failed to read {}", path.display()))?;
+        self.add(&contents, &path.display().to_string())
+    }
+
+    /// Parses `term: descriptor, descriptor` lines; `#` starts a comment.
+    fn add(&mut self, contents: &str, source: &str) -> Result<()> {
+        for (line_number, line) in contents.lines().enumerate() {
+            let line = line.split('#').next().unwrap_or("").trim();
+            if line.is_empty() {
+                continue;
+            }
+
+            let Some((term, descriptors)) = line.split_once(':') else {
+                bail!(
+                    "{}:{}: expected 'term: descriptors'",
+                    source,
+                    line_number + 1
+                );
+            };
+            let key = tokenize(term);
+            let descriptors: Vec<String> = descriptors
+                .split(',')
+                .map(str::trim)
+                .filter(|d| !d.is_empty())
+                .map(str::to_string)
+                .collect();
+            if key.is_empty() || descriptors.is_empty() {
+                continue;
+            }
+
+            self.longest = self.longest.max(key.len());
+            self.entries.insert(
+                key,
+                Expansion {
+                    term: term.trim().to_string(),
+                    descriptors,
+                },
+            );
+        }
+        Ok(())
+    }
+
+    /// Expansions for every thesaurus term in the mood, longest match first,
+    /// in the order they appear.
+    pub fn expand(&self, mood: &str) -> Vec<Expansion> {
+        let tokens = tokenize(mood);
+        let mut found: Vec<Expansion> = Vec::new();
+        let mut i = 0;
+        while i < tokens.len() {
+            let matched = (1..=self.longest.min(tokens.len() - i))
+                .rev()
+                .find_map(|len| self.entries.get(&tokens[i..i + len]).map(|e| (len, e)));
+
+            match matched {
+                Some((len, expansion)) => {
+                    if !found.iter().any(|e| e.term == expansion.term) {
+                        found.push(expansion.clone());
+                    }
+                    i += len;
+                }
+                None => i += 1,
+            }
+        }
+        found
+    }
+}
+
+fn user_path() -> Option<PathBuf> {
+    dirs::config_dir().map(|dir| dir.join("colorify").join("thesaurus.txt"))
+}
@@ -0,0 +1,113 @@
+use crate::colors::Color;
+use crate::lexical::tokenize;
+use serde::{Deserialize, Serialize};
+
+/// Why a color landed in the palette, for designers rather than the scorer.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Explanation {
+    /// Mood words by how much the match weakens without them, strongest first
+    pub drivers: Vec<WordInfluence>,
+    /// Mood words that also appear in the color's name or description
+    pub overlap: Vec<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct WordInfluence {
+    pub word: String,
+    /// Drop in similarity when the word is left out of the mood
+    pub influence: f32,
+}
+
+/// A mood's embedding alongside one embedding per word left out, for
+/// leave-one-word-out attribution.
+pub struct Ablation {
+    pub words: Vec<String>,
+    pub full: Vec<f32>,
+    /// `without[i]` embeds the mood minus `words[i]`; empty for a single word
+    pub without: Vec<Vec<f32>>,
+}
+
+impl Ablation {
+    /// The mood with each word removed in turn; a one-word mood has no
+    /// meaningful ablation, so it yields nothing.
+    pub fn variants(words: &[String]) -> Vec<String> {
+        if words.len() < 2 {
+            return vec![];
+        }
+        (0..words.len())
+            .map(|skip| {
+                words
+                    .iter()
+                    .enumerate()
+                    .filter(|(i, _)| *i != skip)
+                    .map(|(_, word)| word.as_str())
+                    .collect::<Vec<_>>()
+                    .join(" ")
+            })
+            .collect()
+    }
+
+    pub fn words(mood: &str) -> Vec<String> {
+        mood.split_whitespace()
+            .map(|word| word.trim_matches(|c: char| !c.is_alphanumeric()))
+            .filter(|word| !word.is_empty())
+            .map(str::to_string)
+            .collect()
+    }
+}
+
+impl Explanation {
+    /// Builds an explanation from the full similarity and the similarity with
+    /// each word left out (`None` for a single-word mood, which gets full
+    /// credit).
+    pub fn new(
+        words: &[String],
+        full: f32,
+        without: Option<Vec<f32>>,
+        mood: &str,
+        color: &Color,
+    ) -> Self {
+        let mut drivers: Vec<WordInfluence> = match without {
+            Some(without) => words
+                .iter()
+                .zip(without)
+                .map(|(word, score)| WordInfluence {
+                    word: word.clone(),
+                    influence: full - score,
+                })
+                .collect(),
+            None => words
+                .iter()
+                .map(|word| WordInfluence {
+                    word: word.clone(),
+                    influence: full,
+                })
+                .collect(),
+        };
+        drivers.sort_by(|a, b| b.influence.total_cmp(&a.influence));
+
+        Self {
+            drivers,
+            overlap: description_overlap(mood, color),
+        }
+    }
+
+    /// Words that pulled the color in, for compact display.
+    pub fn top_drivers(&self, n: usize) -> impl Iterator<Item = &WordInfluence> {
+        self.drivers
+            .iter()
+            .filter(|driver| driver.influence > 0.0)
+            .take(n)
+    }
+}
+
+fn description_overlap(mood: &str, color: &Color) -> Vec<String> {
+    let color_terms = tokenize(&format!("{} {}", color.name, color.description));
+    let mut overlap: Vec<String> = Vec::new();
+    for term in tokenize(mood) {
+        if color_terms.contains(&term) && !overlap.contains(&term) {
+            overlap.push(term);
+        }
+    }
+    overlap
+}
@@ -0,0 +1,75 @@
+use crate::colors::Color;
+use crate::matcher::ColorMatch;
+use crate::swatches::{write_ase, write_gpl, write_kpl};
+use anyhow::{Context, Result};
+use std::fs;
+use std::path::Path;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ExportFormat {
+    Json,
+    Css,
+    Text,
+    Gpl,
+    Ase,
+    Kpl,
+}
+
+impl ExportFormat {
+    /// Picks a format from the file extension, defaulting to plain text.
+    pub fn from_path(path: &Path) -> Self {
+        match path
+            .extension()
+            .and_then(|ext| ext.to_str())
+            .map(|ext| ext.to_lowercase())
+            .as_deref()
+        {
+            Some("json") => ExportFormat::Json,
+            Some("css") => ExportFormat::Css,
+            Some("gpl") => ExportFormat::Gpl,
+            Some("ase") => ExportFormat::Ase,
+            Some("kpl") => ExportFormat::Kpl,
+            _ => ExportFormat::Text,
+        }
+    }
+}
+
+pub fn export_palette(path: &Path, palette: &[ColorMatch]) -> Result<()> {
+    // Swatch formats carry a palette name; the file name is the best we have
+    let name = path
+        .file_stem()
+        .and_then(|stem| stem.to_str())
+        .unwrap_or("colorify");
+    let colors: Vec<Color> = palette.iter().map(|m| m.color.clone()).collect();
+    let contents = match ExportFormat::from_path(path) {
+        ExportFormat::Json => (serde_json::to_string_pretty(palette)? + "\n").into_bytes(),
+        ExportFormat::Css => to_css(palette).into_bytes(),
+        ExportFormat::Text => to_text(palette).into_bytes(),
+        ExportFormat::Gpl => write_gpl(name, &colors)?.into_bytes(),
+        ExportFormat::Ase => write_ase(&colors)?,
+        ExportFormat::Kpl => write_kpl(name, &colors)?,
+    };
+
+    fs::write(path, contents).with_context(|| format!("failed to write {}", path.display()))
+}
+
+fn to_css(palette: &[ColorMatch]) -> String {
+    let mut css = String::from(":root {\n");
+    for (i, color_match) in palette.iter().enumerate() {
+        css.push_str(&format!(
+            "  --color-{}: {}; /* {} */\n",
+            i + 1,
+            color_match.color.hex,
+            color_match.color.name
+        ));
+    }
+    css.push_str("}\n");
+    css
+}
+
+fn to_text(palette: &[ColorMatch]) -> String {
+    palette
+        .iter()
+        .map(|color_match| format!("{} {}\n", color_match.color.hex, color_match.color.name))
+        .collect()
+}
@@ -0,0 +1,152 @@
+use crate::colors::Color;
+use crate::index::{dot, normalize};
+use crate::matcher::ColorMatch;
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// Largest amount feedback can move a color's score either way.
+const FEEDBACK_STRENGTH: f32 = 0.1;
+/// Share of a verdict that applies to every mood; the rest only applies in
+/// proportion to how similar the mood is to the one it was given for.
+const GLOBAL_SHARE: f32 = 0.3;
+
+/// A thumbs up or down on one color for one mood.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FeedbackEntry {
+    pub mood: String,
+    pub embedding: Vec<f32>,
+    pub color: String,
+    pub hex: String,
+    /// +1 for a thumbs up, -1 for a thumbs down
+    pub verdict: i8,
+    /// Seconds since the Unix epoch
+    pub timestamp: u64,
+}
+
+/// Local store of color feedback, learned into a per-color bias that is
+/// stronger for moods like the one the feedback was given for.
+#[derive(Debug, Default)]
+pub struct FeedbackStore {
+    path: Option<PathBuf>,
+    entries: Vec<FeedbackEntry>,
+}
+
+#[derive(Serialize)]
+struct FeedbackExport<'a> {
+    /// Net verdict per color, ignoring mood
+    color_bias: BTreeMap<&'a str, i32>,
+    entries: &'a [FeedbackEntry],
+}
+
+impl FeedbackStore {
+    /// Loads `feedback.json` from the data directory; a missing file is an
+    /// empty store.
+    pub fn load() -> Result<Self> {
+        let path = default_path();
+        let entries = match &path {
+            Some(path) if path.exists() => {
+                let contents = fs::read_to_string(path)
+                    .with_context(|| format!("failed to read {}", path.display()))?;
+                serde_json::from_str(&contents)
+                    .with_context(|| format!("{} is not a feedback file", path.display()))?
+            }
+            _ => vec![],
+        };
+        Ok(Self { path, entries })
+    }
+
+    pub fn entries(&self) -> &[FeedbackEntry] {
+        &self.entries
+    }
+
+    pub fn record(
+        &mut self,
+        mood: &str,
+        embedding: Vec<f32>,
+        color: &Color,
+        thumbs_up: bool,
+    ) -> Result<()> {
+        self.entries.push(FeedbackEntry {
+            mood: mood.to_string(),
+            embedding: normalize(&embedding),
+            color: color.name.clone(),
+            hex: color.hex.clone(),
+            verdict: if thumbs_up { 1 } else { -1 },
+            timestamp: SystemTime::now()
+                .duration_since(UNIX_EPOCH)
+                .map(|d| d.as_secs())
+                .unwrap_or(0),
+        });
+        self.save()
+    }
+
+    pub fn reset(&mut self) -> Result<()> {
+        self.entries.clear();
+        match &self.path {
+            Some(path) if path.exists() => fs::remove_file(path)
+                .with_context(|| format!("failed to remove {}", path.display())),
+            _ => Ok(()),
+        }
+    }
+
+    /// Writes the raw feedback and each color's net verdict as JSON.
+    pub fn export(&self, path: &Path) -> Result<()> {
+        let mut color_bias: BTreeMap<&str, i32> = BTreeMap::new();
+        for entry in &self.entries {
+            *color_bias.entry(entry.color.as_str()).or_default() += entry.verdict as i32;
+        }
+        let export = FeedbackExport {
+            color_bias,
+            entries: &self.entries,
+        };
+        fs::write(path, serde_json::to_string_pretty(&export)?)
+            .with_context(|| format!("failed to write {}", path.display()))
+    }
+
+    /// Shifts each color's score by its learned bias for this mood and
+    /// re-sorts the ranking.
+    pub fn rerank(&self, ranking: &mut [ColorMatch], mood_vec: &[f32]) {
+        if self.entries.is_empty() {
+            return;
+        }
+
+        let mood_vec = normalize(mood_vec);
+        let mut bias: BTreeMap<&str, f32> = BTreeMap::new();
+        for entry in &self.entries {
+            let similarity = if entry.embedding.len() == mood_vec.len() {
+                dot(&entry.embedding, &mood_vec).max(0.0)
+            } else {
+                0.0
+            };
+            let weight = GLOBAL_SHARE + (1.0 - GLOBAL_SHARE) * similarity;
+            *bias.entry(entry.color.as_str()).or_default() += entry.verdict as f32 * weight;
+        }
+
+        for color_match in ranking.iter_mut() {
+            if let Some(bias) = bias.get(color_match.color.name.as_str()) {
+                color_match.feedback = FEEDBACK_STRENGTH * bias.tanh();
+                color_match.score += color_match.feedback;
+            }
+        }
+        ranking.sort_by(|a, b| b.score.total_cmp(&a.score));
+    }
+
+    fn save(&self) -> Result<()> {
+        let Some(path) = &self.path else {
+            return Ok(());
+        };
+        if let Some(dir) = path.parent() {
+            fs::create_dir_all(dir)?;
+        }
+        fs::write(path, serde_json::to_string(&self.entries)?)
+            .with_context(|| format!("failed to write {}", path.display()))
+    }
+}
+
+fn default_path() -> Option<PathBuf> {
+    dirs::data_dir().map(|dir| dir.join("colorify").join("feedback.json"))
+}
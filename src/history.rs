@@ -0,0 +1,317 @@
+use crate::config::Config;
+use crate::matcher::ColorMatch;
+use anyhow::{Context, Result};
+use rusqlite::{params, Connection, OptionalExtension, Row};
+use serde::{Deserialize, Serialize};
+use std::path::PathBuf;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+const SCHEMA: &str = "
+    CREATE TABLE IF NOT EXISTS palettes (
+        id INTEGER PRIMARY KEY,
+        created_at INTEGER NOT NULL,
+        mood TEXT NOT NULL,
+        options TEXT NOT NULL,
+        palette TEXT NOT NULL,
+        model TEXT NOT NULL,
+        catalog_hash TEXT NOT NULL,
+        seed INTEGER,
+        favorite INTEGER NOT NULL DEFAULT 0
+    );
+    CREATE TABLE IF NOT EXISTS tags (
+        palette_id INTEGER NOT NULL REFERENCES palettes(id) ON DELETE CASCADE,
+        tag TEXT NOT NULL,
+        PRIMARY KEY (palette_id, tag)
+    );
+";
+
+const COLUMNS: &str = "id, strftime('%Y-%m-%d %H:%M', created_at, 'unixepoch', 'localtime'), \
+                       mood, options, palette, model, catalog_hash, seed, favorite";
+
+/// Everything needed to generate the same palette again.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RunOptions {
+    pub config: Config,
+    /// Catalog file the run used, `None` for the built-in catalog
+    pub catalog: Option<PathBuf>,
+    /// `--keep` values as given
+    pub keep: Vec<String>,
+    pub variations: usize,
+    /// Which of the variations this entry is
+    pub variation: usize,
+    /// Thumbs up/down on record when the palette was made
+    pub feedback_count: usize,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct Entry {
+    pub id: i64,
+    pub created: String,
+    pub mood: String,
+    pub options: RunOptions,
+    pub palette: Vec<ColorMatch>,
+    pub model: String,
+    pub catalog_hash: String,
+    pub seed: Option<u64>,
+    pub favorite: bool,
+    pub tags: Vec<String>,
+}
+
+/// A palette about to be saved.
+pub struct NewEntry<'a> {
+    pub mood: &'a str,
+    pub options: &'a RunOptions,
+    pub palette: &'a [ColorMatch],
+    pub model: &'a str,
+    pub catalog_hash: &'a str,
+}
+
+/// SQLite-backed record of generated palettes, with favorites and tags.
+pub struct History {
+    conn: Connection,
+}
+
+impl History {
+    /// Opens (creating if needed) `history.sqlite3` in the data directory.
+    pub fn open() -> Result<Self> {
+        let path = dirs::data_dir()
+            .context("no data directory on this system")?
+            .join("colorify")
+            .join("history.sqlite3");
+        if let Some(dir) = path.parent() {
+            std::fs::create_dir_all(dir)
+                .with_context(|| format!("failed to create {}", dir.display()))?;
+        }
+
+        let conn = Connection::open(&path)
+            .with_context(|| format!("failed to open {}", path.display()))?;
+        Self::with_connection(conn)
+    }
+
+    fn with_connection(conn: Connection) -> Result<Self> {
+        conn.execute_batch("PRAGMA foreign_keys = ON;")?;
+        conn.execute_batch(SCHEMA)?;
+        Ok(Self { conn })
+    }
+
+    pub fn record(&self, entry: &NewEntry) -> Result<i64> {
+        let created_at = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|d| d.as_secs() as i64)
+            .unwrap_or(0);
+        self.conn.execute(
+            "INSERT INTO palettes (created_at, mood, options, palette, model, catalog_hash, seed)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7)",
+            params![
+                created_at,
+                entry.mood,
+                serde_json::to_string(entry.options)?,
+                serde_json::to_string(entry.palette)?,
+                entry.model,
+                entry.catalog_hash,
+                entry.options.config.seed.map(|seed| seed as i64),
+            ],
+        )?;
+        Ok(self.conn.last_insert_rowid())
+    }
+
+    /// The latest `limit` entries, newest first.
+    pub fn list(&self, limit: usize, favorites_only: bool) -> Result<Vec<Entry>> {
+        self.select(Some(limit), favorites_only)
+    }
+
+    /// Every entry, newest first.
+    pub fn all(&self, favorites_only: bool) -> Result<Vec<Entry>> {
+        self.select(None, favorites_only)
+    }
+
+    fn select(&self, limit: Option<usize>, favorites_only: bool) -> Result<Vec<Entry>> {
+        let sql = format!(
+            "SELECT {} FROM palettes {} ORDER BY id DESC LIMIT ?1",
+            COLUMNS,
+            if favorites_only {
+                "WHERE favorite = 1"
+            } else {
+                ""
+            }
+        );
+        // SQLite reads a negative LIMIT as no limit
+        let limit = limit.map_or(-1, |limit| limit.min(i64::MAX as usize) as i64);
+        self.query(&sql, params![limit])
+    }
+
+    pub fn get(&self, id: i64) -> Result<Option<Entry>> {
+        let sql = format!("SELECT {} FROM palettes WHERE id = ?1", COLUMNS);
+        let entry = self
+            .conn
+            .query_row(&sql, params![id], read_entry)
+            .optional()?;
+        entry.map(|entry| self.with_tags(entry?)).transpose()
+    }
+
+    /// Entries whose mood, colors or tags contain `text`, newest first.
+    /// `%` and `_` in `text` match themselves rather than acting as LIKE
+    /// wildcards.
+    pub fn search(&self, text: &str, limit: usize) -> Result<Vec<Entry>> {
+        let sql = format!(
+            "SELECT {} FROM palettes
+             WHERE mood LIKE ?1 ESCAPE '\\' OR palette LIKE ?1 ESCAPE '\\'
+                OR id IN (SELECT palette_id FROM tags WHERE tag LIKE ?1 ESCAPE '\\')
+             ORDER BY id DESC LIMIT ?2",
+            COLUMNS
+        );
+        let pattern = format!("%{}%", escape_like(text));
+        self.query(&sql, params![pattern, limit as i64])
+    }
+
+    /// Returns false when there is no entry with that id.
+    pub fn set_favorite(&self, id: i64, favorite: bool) -> Result<bool> {
+        let changed = self.conn.execute(
+            "UPDATE palettes SET favorite = ?1 WHERE id = ?2",
+            params![favorite, id],
+        )?;
+        Ok(changed > 0)
+    }
+
+    /// Returns false when there is no entry with that id.
+    pub fn add_tags(&self, id: i64, tags: &[String]) -> Result<bool> {
+        if self.get(id)?.is_none() {
+            return Ok(false);
+        }
+        for tag in tags {
+            self.conn.execute(
+                "INSERT OR IGNORE INTO tags (palette_id, tag) VALUES (?1, ?2)",
+                params![id, tag.trim().to_lowercase()],
+            )?;
+        }
+        Ok(true)
+    }
+
+    fn query(&self, sql: &str, params: impl rusqlite::Params) -> Result<Vec<Entry>> {
+        let mut statement = self.conn.prepare(sql)?;
+        let entries = statement
+            .query_map(params, read_entry)?
+            .collect::<rusqlite::Result<Vec<Result<Entry>>>>()?;
+        entries
+            .into_iter()
+            .map(|entry| self.with_tags(entry?))
+            .collect()
+    }
+
+    fn with_tags(&self, mut entry: Entry) -> Result<Entry> {
+        let mut statement = self
+            .conn
+            .prepare("SELECT tag FROM tags WHERE palette_id = ?1 ORDER BY tag")?;
+        entry.tags = statement
+            .query_map(params![entry.id], |row| row.get(0))?
+            .collect::<rusqlite::Result<Vec<String>>>()?;
+        Ok(entry)
+    }
+}
+
+/// Escapes LIKE's wildcards (and the escape character itself) with `\`.
+fn escape_like(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for c in text.chars() {
+        if matches!(c, '%' | '_' | '\\') {
+            escaped.push('\\');
+        }
+        escaped.push(c);
+    }
+    escaped
+}
+
+/// Reads a row selected with `COLUMNS`. JSON errors are kept separate from
+/// SQLite ones so a corrupt entry reports what went wrong.
+fn read_entry(row: &Row) -> rusqlite::Result<Result<Entry>> {
+    let id: i64 = row.get(0)?;
+    let options: String = row.get(3)?;
+    let palette: String = row.get(4)?;
+    let seed: Option<i64> = row.get(7)?;
+
+    let created: String = row.get(1)?;
+    let mood: String = row.get(2)?;
+    let model: String = row.get(5)?;
+    let catalog_hash: String = row.get(6)?;
+    let favorite: bool = row.get(8)?;
+
+    Ok((|| {
+        Ok(Entry {
+            id,
+            created,
+            mood,
+            options: serde_json::from_str(&options)
+                .with_context(|| format!("history entry {} has unreadable options", id))?,
+            palette: serde_json::from_str(&palette)
+                .with_context(|| format!("history entry {} has an unreadable palette", id))?,
+            model,
+            catalog_hash,
+            seed: seed.map(|seed| seed as u64),
+            favorite,
+            tags: vec![],
+        })
+    })())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn history(moods: &[&str]) -> History {
+        let history = History::with_connection(Connection::open_in_memory().unwrap()).unwrap();
+        let options = RunOptions {
+            config: Config::new(),
+            catalog: None,
+            keep: vec![],
+            variations: 1,
+            variation: 0,
+            feedback_count: 0,
+        };
+        for mood in moods {
+            history
+                .record(&NewEntry {
+                    mood,
+                    options: &options,
+                    palette: &[],
+                    model: "test",
+                    catalog_hash: "test",
+                })
+                .unwrap();
+        }
+        history
+    }
+
+    fn moods(entries: Vec<Entry>) -> Vec<String> {
+        entries.into_iter().map(|entry| entry.mood).collect()
+    }
+
+    #[test]
+    fn search_treats_wildcards_literally() {
+        let history = history(&[
+            "100% cotton",
+            "1000 lakes",
+            "dark_blue night",
+            "darkXblue",
+            r"c:\dark",
+        ]);
+
+        assert_eq!(moods(history.search("100%", 10).unwrap()), ["100% cotton"]);
+        assert_eq!(
+            moods(history.search("dark_blue", 10).unwrap()),
+            ["dark_blue night"]
+        );
+        assert_eq!(moods(history.search(r"\d", 10).unwrap()), [r"c:\dark"]);
+        assert_eq!(moods(history.search("DARK", 10).unwrap()).len(), 3);
+    }
+
+    #[test]
+    fn lists_newest_first_with_or_without_a_limit() {
+        let history = history(&["dawn", "noon", "dusk"]);
+        assert_eq!(moods(history.list(2, false).unwrap()), ["dusk", "noon"]);
+        assert_eq!(moods(history.all(false).unwrap()), ["dusk", "noon", "dawn"]);
+
+        history.set_favorite(1, true).unwrap();
+        assert_eq!(moods(history.all(true).unwrap()), ["dawn"]);
+        assert_eq!(history.list(0, true).unwrap().len(), 0);
+    }
+}
@@ -0,0 +1,519 @@
+use anyhow::{bail, Context, Result};
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+use std::cmp::{Ordering, Reverse};
+use std::collections::{BinaryHeap, HashSet};
+use std::fs;
+use std::path::Path;
+
+const HNSW_MAGIC: &[u8; 8] = b"CFYHNSW1";
+
+/// Nearest-neighbour search over the catalog's color embeddings. Vectors are
+/// normalized once up front so similarity is a plain dot product.
+pub trait VectorIndex: Send + Sync {
+    fn name(&self) -> &'static str;
+
+    fn vectors(&self) -> &EmbeddingMatrix;
+
+    fn len(&self) -> usize {
+        self.vectors().len()
+    }
+
+    /// Up to `k` rows most similar to `query` as `(row, cosine similarity)`,
+    /// best first.
+    fn search(&self, query: &[f32], k: usize) -> Result<Vec<(usize, f32)>>;
+
+    /// Cosine similarity between `query` and a single row.
+    fn similarity(&self, row: usize, query: &[f32]) -> Result<f32> {
+        let query = self.vectors().prepare_query(query)?;
+        Ok(dot(&query, self.vectors().row(row)))
+    }
+}
+
+/// Unit-length vectors stored row-major in one contiguous buffer, so scoring
+/// the catalog is a single linear pass over memory.
+#[derive(Debug, Clone)]
+pub struct EmbeddingMatrix {
+    dims: usize,
+    data: Vec<f32>,
+}
+
+impl EmbeddingMatrix {
+    /// Normalizes and packs `rows`, which must all have the same length.
+    pub fn from_rows(rows: &[Vec<f32>]) -> Result<Self> {
+        let dims = rows.first().map(Vec::len).unwrap_or(0);
+        let mut data = Vec::with_capacity(rows.len() * dims);
+        for (i, row) in rows.iter().enumerate() {
+            if row.len() != dims {
+                bail!(
+                    "embedding {} has {} dimensions, expected {}",
+                    i,
+                    row.len(),
+                    dims
+                );
+            }
+            data.extend(normalize(row));
+        }
+        Ok(Self { dims, data })
+    }
+
+    pub fn len(&self) -> usize {
+        self.data.len().checked_div(self.dims).unwrap_or(0)
+    }
+
+    pub fn row(&self, row: usize) -> &[f32] {
+        &self.data[row * self.dims..(row + 1) * self.dims]
+    }
+
+    pub fn rows(&self) -> impl Iterator<Item = &[f32]> {
+        self.data.chunks_exact(self.dims.max(1))
+    }
+
+    /// Normalizes a query for scoring against this matrix, refusing one from
+    /// a model with a different embedding size.
+    pub fn prepare_query(&self, query: &[f32]) -> Result<Vec<f32>> {
+        if query.len() != self.dims {
+            bail!(
+                "query embedding has {} dimensions but the catalog has {}; \
+                 was the catalog embedded with a different model?",
+                query.len(),
+                self.dims
+            );
+        }
+        Ok(normalize(query))
+    }
+}
+
+pub fn normalize(vector: &[f32]) -> Vec<f32> {
+    let norm = dot(vector, vector).sqrt();
+    if norm == 0.0 {
+        return vector.to_vec();
+    }
+    vector.iter().map(|x| x / norm).collect()
+}
+
+/// Lanes summed independently in `dot`; eight f32s fill an AVX register.
+const LANES: usize = 8;
+
+/// Dot product over fixed-size chunks with separate accumulators, which lets
+/// the compiler vectorize it without reassociating a single running sum.
+pub fn dot(a: &[f32], b: &[f32]) -> f32 {
+    let a_chunks = a.chunks_exact(LANES);
+    let b_chunks = b.chunks_exact(LANES);
+    let tail: f32 = a_chunks
+        .remainder()
+        .iter()
+        .zip(b_chunks.remainder())
+        .map(|(x, y)| x * y)
+        .sum();
+
+    let mut sums = [0.0f32; LANES];
+    for (x, y) in a_chunks.zip(b_chunks) {
+        for ((sum, x), y) in sums.iter_mut().zip(x).zip(y) {
+            *sum += x * y;
+        }
+    }
+    sums.iter().sum::<f32>() + tail
+}
+
+/// Scores every row, then keeps the best `k` with a partial selection rather
+/// than sorting the whole catalog.
+pub struct BruteForceIndex {
+    vectors: EmbeddingMatrix,
+}
+
+impl BruteForceIndex {
+    pub fn new(vectors: EmbeddingMatrix) -> Self {
+        Self { vectors }
+    }
+}
+
+impl VectorIndex for BruteForceIndex {
+    fn name(&self) -> &'static str {
+        "brute-force"
+    }
+
+    fn vectors(&self) -> &EmbeddingMatrix {
+        &self.vectors
+    }
+
+    fn search(&self, query: &[f32], k: usize) -> Result<Vec<(usize, f32)>> {
+        let query = self.vectors.prepare_query(query)?;
+        let mut scored: Vec<(usize, f32)> = self
+            .vectors
+            .rows()
+            .enumerate()
+            .map(|(row, vector)| (row, dot(&query, vector)))
+            .collect();
+
+        let by_score = |a: &(usize, f32), b: &(usize, f32)| b.1.total_cmp(&a.1);
+        if k < scored.len() {
+            scored.select_nth_unstable_by(k, by_score);
+            scored.truncate(k);
+        }
+        scored.sort_by(by_score);
+        Ok(scored)
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct HnswParams {
+    /// Neighbours kept per node on upper layers (twice this on layer 0)
+    pub m: usize,
+    pub ef_construction: usize,
+    pub ef_search: usize,
+    pub seed: u64,
+}
+
+impl Default for HnswParams {
+    fn default() -> Self {
+        Self {
+            m: 16,
+            ef_construction: 100,
+            ef_search: 64,
+            seed: 42,
+        }
+    }
+}
+
+/// Cosine distance wrapper with a total order for the search heaps.
+#[derive(Debug, Clone, Copy, PartialEq)]
+struct Distance(f32);
+
+impl Eq for Distance {}
+
+impl PartialOrd for Distance {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for Distance {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.0.total_cmp(&other.0)
+    }
+}
+
+/// Hierarchical navigable small world graph (Malkov & Yashunin) for
+/// approximate search over large catalogs.
+pub struct HnswIndex {
+    vectors: EmbeddingMatrix,
+    /// `links[node][layer]` holds the node's neighbours on that layer
+    links: Vec<Vec<Vec<u32>>>,
+    entry: u32,
+    max_level: usize,
+    params: HnswParams,
+}
+
+impl HnswIndex {
+    pub fn build(vectors: EmbeddingMatrix, params: HnswParams) -> Self {
+        let count = vectors.len();
+        let mut index = Self {
+            vectors,
+            links: Vec::with_capacity(count),
+            entry: 0,
+            max_level: 0,
+            params,
+        };
+
+        let mut rng = StdRng::seed_from_u64(params.seed);
+        let level_scale = 1.0 / (params.m.max(2) as f64).ln();
+        for node in 0..count {
+            let level = (-(1.0 - rng.random::<f64>()).ln() * level_scale).floor() as usize;
+            index.insert(node as u32, level);
+        }
+
+        index
+    }
+
+    /// Loads a graph saved by `save`, checking it was built for `vectors`.
+    pub fn load(path: &Path, vectors: EmbeddingMatrix, params: HnswParams) -> Result<Self> {
+        let bytes = fs::read(path).with_context(|| format!("failed to read {}", path.display()))?;
+        let mut reader = ByteReader { bytes: &bytes };
+
+        if reader.take(HNSW_MAGIC.len())? != HNSW_MAGIC {
+            bail!("{} is not a colorify HNSW index", path.display());
+        }
+        let count = reader.u32()? as usize;
+        if count != vectors.len() {
+            bail!(
+                "index holds {} vectors but the catalog has {}",
+                count,
+                vectors.len()
+            );
+        }
+        let m = reader.u32()? as usize;
+        let entry = reader.u32()?;
+        let max_level = reader.u32()? as usize;
+
+        let mut links = Vec::with_capacity(count);
+        for _ in 0..count {
+            let levels = reader.u32()? as usize;
+            let mut node_links = Vec::with_capacity(levels);
+            for _ in 0..levels {
+                let len = reader.u32()? as usize;
+                let neighbours = (0..len)
+                    .map(|_| reader.u32())
+                    .collect::<Result<Vec<u32>>>()?;
+                if neighbours.iter().any(|n| *n as usize >= count) {
+                    bail!("{} is corrupt", path.display());
+                }
+                node_links.push(neighbours);
+            }
+            links.push(node_links);
+        }
+
+        // A stale or hand-edited file could still point search at a node or
+        // layer that isn't there
+        let has_layer = |node: u32, layer: usize| links[node as usize].len() > layer;
+        if count > 0 && (entry as usize >= count || !has_layer(entry, max_level)) {
+            bail!("{} is corrupt", path.display());
+        }
+        for node_links in &links {
+            for (layer, neighbours) in node_links.iter().enumerate() {
+                if !neighbours.iter().all(|n| has_layer(*n, layer)) {
+                    bail!("{} is corrupt", path.display());
+                }
+            }
+        }
+
+        Ok(Self {
+            vectors,
+            links,
+            entry,
+            max_level,
+            params: HnswParams { m, ..params },
+        })
+    }
+
+    pub fn save(&self, path: &Path) -> Result<()> {
+        let mut bytes = Vec::new();
+        bytes.extend_from_slice(HNSW_MAGIC);
+        for value in [
+            self.links.len() as u32,
+            self.params.m as u32,
+            self.entry,
+            self.max_level as u32,
+        ] {
+            bytes.extend_from_slice(&value.to_le_bytes());
+        }
+
+        for node_links in &self.links {
+            bytes.extend_from_slice(&(node_links.len() as u32).to_le_bytes());
+            for neighbours in node_links {
+                bytes.extend_from_slice(&(neighbours.len() as u32).to_le_bytes());
+                for neighbour in neighbours {
+                    bytes.extend_from_slice(&neighbour.to_le_bytes());
+                }
+            }
+        }
+
+        if let Some(dir) = path.parent() {
+            fs::create_dir_all(dir)?;
+        }
+        fs::write(path, bytes).with_context(|| format!("failed to write {}", path.display()))
+    }
+
+    fn distance(&self, query: &[f32], node: u32) -> Distance {
+        Distance(1.0 - dot(query, self.vectors.row(node as usize)))
+    }
+
+    fn insert(&mut self, node: u32, level: usize) {
+        self.links.push(vec![Vec::new(); level + 1]);
+        if node == 0 {
+            self.entry = 0;
+            self.max_level = level;
+            return;
+        }
+
+        let query = self.vectors.row(node as usize).to_vec();
+        let mut entry = self.entry;
+        for layer in (level + 1..=self.max_level).rev() {
+            entry = self.search_layer(&query, entry, 1, layer)[0].1;
+        }
+
+        for layer in (0..=level.min(self.max_level)).rev() {
+            let found = self.search_layer(&query, entry, self.params.ef_construction, layer);
+            let max_links = self.max_links(layer);
+            let neighbours: Vec<u32> = found.iter().take(self.params.m).map(|(_, n)| *n).collect();
+
+            for &neighbour in &neighbours {
+                let links = &mut self.links[neighbour as usize][layer];
+                links.push(node);
+                if links.len() > max_links {
+                    self.prune(neighbour, layer, max_links);
+                }
+            }
+            self.links[node as usize][layer] = neighbours;
+            entry = found[0].1;
+        }
+
+        if level > self.max_level {
+            self.max_level = level;
+            self.entry = node;
+        }
+    }
+
+    fn max_links(&self, layer: usize) -> usize {
+        if layer == 0 {
+            self.params.m * 2
+        } else {
+            self.params.m
+        }
+    }
+
+    /// Keeps only the closest `keep` neighbours of `node` on `layer`.
+    fn prune(&mut self, node: u32, layer: usize, keep: usize) {
+        let base = self.vectors.row(node as usize);
+        let mut scored: Vec<(Distance, u32)> = self.links[node as usize][layer]
+            .iter()
+            .map(|n| (Distance(1.0 - dot(base, self.vectors.row(*n as usize))), *n))
+            .collect();
+        scored.sort();
+        scored.truncate(keep);
+        self.links[node as usize][layer] = scored.into_iter().map(|(_, n)| n).collect();
+    }
+
+    /// Beam search on one layer, returning up to `ef` nodes closest first.
+    fn search_layer(
+        &self,
+        query: &[f32],
+        entry: u32,
+        ef: usize,
+        layer: usize,
+    ) -> Vec<(Distance, u32)> {
+        let mut visited = HashSet::from([entry]);
+        let start = self.distance(query, entry);
+        let mut candidates = BinaryHeap::from([Reverse((start, entry))]);
+        let mut results = BinaryHeap::from([(start, entry)]);
+
+        while let Some(Reverse((distance, node))) = candidates.pop() {
+            let worst = results.peek().map(|(d, _)| *d).unwrap_or(distance);
+            if distance > worst && results.len() >= ef {
+                break;
+            }
+
+            for &neighbour in &self.links[node as usize][layer] {
+                if !visited.insert(neighbour) {
+                    continue;
+                }
+                let d = self.distance(query, neighbour);
+                let worst = results.peek().map(|(d, _)| *d).unwrap_or(d);
+                if results.len() < ef || d < worst {
+                    candidates.push(Reverse((d, neighbour)));
+                    results.push((d, neighbour));
+                    if results.len() > ef {
+                        results.pop();
+                    }
+                }
+            }
+        }
+
+        results.into_sorted_vec()
+    }
+}
+
+impl VectorIndex for HnswIndex {
+    fn name(&self) -> &'static str {
+        "hnsw"
+    }
+
+    fn vectors(&self) -> &EmbeddingMatrix {
+        &self.vectors
+    }
+
+    fn search(&self, query: &[f32], k: usize) -> Result<Vec<(usize, f32)>> {
+        let query = self.vectors.prepare_query(query)?;
+        if self.links.is_empty() || k == 0 {
+            return Ok(vec![]);
+        }
+
+        let mut entry = self.entry;
+        for layer in (1..=self.max_level).rev() {
+            entry = self.search_layer(&query, entry, 1, layer)[0].1;
+        }
+
+        Ok(self
+            .search_layer(&query, entry, self.params.ef_search.max(k), 0)
+            .into_iter()
+            .take(k)
+            .map(|(distance, node)| (node as usize, 1.0 - distance.0))
+            .collect())
+    }
+}
+
+struct ByteReader<'a> {
+    bytes: &'a [u8],
+}
+
+impl<'a> ByteReader<'a> {
+    fn take(&mut self, len: usize) -> Result<&'a [u8]> {
+        if self.bytes.len() < len {
+            bail!("unexpected end of index file");
+        }
+        let (head, tail) = self.bytes.split_at(len);
+        self.bytes = tail;
+        Ok(head)
+    }
+
+    fn u32(&mut self) -> Result<u32> {
+        Ok(u32::from_le_bytes(self.take(4)?.try_into().unwrap()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn vectors() -> EmbeddingMatrix {
+        let rows: Vec<Vec<f32>> = (0..200)
+            .map(|i| {
+                let angle = i as f32 * 0.37;
+                vec![angle.cos(), angle.sin(), (i % 7) as f32 * 0.1]
+            })
+            .collect();
+        EmbeddingMatrix::from_rows(&rows).unwrap()
+    }
+
+    fn saved(name: &str) -> (std::path::PathBuf, Vec<u8>) {
+        let path =
+            std::env::temp_dir().join(format!("colorify-{}-{}.hnsw", name, std::process::id()));
+        HnswIndex::build(vectors(), HnswParams::default())
+            .save(&path)
+            .unwrap();
+        let bytes = fs::read(&path).unwrap();
+        (path, bytes)
+    }
+
+    #[test]
+    fn reloads_a_saved_graph() {
+        let (path, _) = saved("reload");
+        let index = HnswIndex::load(&path, vectors(), HnswParams::default()).unwrap();
+        let query = index.vectors().row(17).to_vec();
+        assert_eq!(index.search(&query, 1).unwrap()[0].0, 17);
+        fs::remove_file(path).unwrap();
+    }
+
+    #[test]
+    fn rejects_truncated_and_stale_files() {
+        let (path, bytes) = saved("corrupt");
+        let load = |bytes: &[u8]| {
+            fs::write(&path, bytes).unwrap();
+            HnswIndex::load(&path, vectors(), HnswParams::default())
+        };
+
+        assert!(load(&bytes[..bytes.len() / 2]).is_err());
+
+        // Entry point past the last node
+        let mut bad_entry = bytes.clone();
+        bad_entry[16..20].copy_from_slice(&500u32.to_le_bytes());
+        assert!(load(&bad_entry).is_err());
+
+        // Claims more layers than the entry point has links for
+        let mut bad_level = bytes.clone();
+        bad_level[20..24].copy_from_slice(&40u32.to_le_bytes());
+        assert!(load(&bad_level).is_err());
+
+        fs::remove_file(path).unwrap();
+    }
+}
@@ -0,0 +1,113 @@
+use crate::colors::Color;
+use std::collections::HashMap;
+
+/// BM25 term-frequency saturation
+const K1: f32 = 1.2;
+/// BM25 document-length normalization
+const B: f32 = 0.75;
+/// Name tokens count this many times over description tokens, so "sage"
+/// favours the color called Sage over one merely described as sage-like.
+const NAME_BOOST: usize = 3;
+
+const STOPWORDS: &[&str] = &[
+    "a", "an", "and", "as", "at", "by", "color", "colour", "for", "from", "in", "into", "is", "it",
+    "like", "of", "on", "or", "the", "to", "with",
+];
+
+/// A BM25 index over color names and descriptions, used to reward literal
+/// color words in a mood ("sage and cream") that the embedding can blur.
+pub struct LexicalIndex {
+    documents: Vec<HashMap<String, usize>>,
+    lengths: Vec<usize>,
+    average_length: f32,
+    document_frequency: HashMap<String, usize>,
+}
+
+impl LexicalIndex {
+    pub fn new(colors: &[Color]) -> Self {
+        let mut documents = Vec::with_capacity(colors.len());
+        let mut lengths = Vec::with_capacity(colors.len());
+        let mut document_frequency: HashMap<String, usize> = HashMap::new();
+
+        for color in colors {
+            let mut terms: HashMap<String, usize> = HashMap::new();
+            for token in tokenize(&color.name) {
+                *terms.entry(token).or_default() += NAME_BOOST;
+            }
+            for token in tokenize(&color.description) {
+                *terms.entry(token).or_default() += 1;
+            }
+
+            for term in terms.keys() {
+                *document_frequency.entry(term.clone()).or_default() += 1;
+            }
+            lengths.push(terms.values().sum());
+            documents.push(terms);
+        }
+
+        let average_length = if lengths.is_empty() {
+            0.0
+        } else {
+            lengths.iter().sum::<usize>() as f32 / lengths.len() as f32
+        };
+
+        Self {
+            documents,
+            lengths,
+            average_length,
+            document_frequency,
+        }
+    }
+
+    /// Raw BM25 score of every color for the query, in catalog order.
+    pub fn scores(&self, query: &str) -> Vec<f32> {
+        let mut terms = tokenize(query);
+        terms.sort();
+        terms.dedup();
+
+        let total = self.documents.len() as f32;
+        let weighted: Vec<(&String, f32)> = terms
+            .iter()
+            .filter_map(|term| {
+                let df = *self.document_frequency.get(term)? as f32;
+                let idf = ((total - df + 0.5) / (df + 0.5) + 1.0).ln();
+                Some((term, idf))
+            })
+            .collect();
+
+        self.documents
+            .iter()
+            .zip(self.lengths.iter())
+            .map(|(document, length)| {
+                let norm = K1 * (1.0 - B + B * *length as f32 / self.average_length.max(1.0));
+                weighted
+                    .iter()
+                    .filter_map(|(term, idf)| {
+                        let tf = *document.get(*term)? as f32;
+                        Some(idf * tf * (K1 + 1.0) / (tf + norm))
+                    })
+                    .sum()
+            })
+            .collect()
+    }
+}
+
+/// Lowercased word tokens with stopwords removed and plurals folded, so
+/// "greens" and "berries" meet "green" and "berry".
+pub fn tokenize(text: &str) -> Vec<String> {
+    text.split(|c: char| !c.is_alphanumeric())
+        .filter(|word| !word.is_empty())
+        .map(|word| stem(&word.to_lowercase()))
+        .filter(|word| !STOPWORDS.contains(&word.as_str()))
+        .collect()
+}
+
+fn stem(word: &str) -> String {
+    if word.len() > 4 && word.ends_with("ies") {
+        format!("{}y", &word[..word.len() - 3])
+    } else if word.len() > 3 && word.ends_with('s') && !word.ends_with("ss") {
+        word[..word.len() - 1].to_string()
+    } else {
+        word.to_string()
+    }
+}
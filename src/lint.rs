@@ -0,0 +1,474 @@
+use crate::color_space::{parse_hex, to_hex, HueFamily, Oklab};
+use crate::colors::Color;
+use crate::describe::synthesize_description;
+use anyhow::{Context, Result};
+use colored::*;
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::fs;
+use std::path::Path;
+
+pub struct LintOptions {
+    /// Entries closer than this (ΔEok) count as the same color
+    pub delta_e: f32,
+    /// Descriptions shorter than this many characters are flagged
+    pub min_description: usize,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum IssueKind {
+    InvalidHex,
+    NonCanonicalHex,
+    DuplicateName,
+    NearDuplicate,
+    MissingDescription,
+    ShortDescription,
+    InconsistentDescription,
+}
+
+impl IssueKind {
+    fn label(self) -> &'static str {
+        match self {
+            IssueKind::InvalidHex => "invalid hex",
+            IssueKind::NonCanonicalHex => "non-canonical hex",
+            IssueKind::DuplicateName => "duplicate name",
+            IssueKind::NearDuplicate => "near-identical color",
+            IssueKind::MissingDescription => "empty description",
+            IssueKind::ShortDescription => "short description",
+            IssueKind::InconsistentDescription => "description contradicts color",
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct Issue {
+    pub kind: IssueKind,
+    /// Position of the entry in the catalog
+    pub index: usize,
+    pub message: String,
+}
+
+pub struct LintReport {
+    pub issues: Vec<Issue>,
+    /// Entries per hue family
+    pub coverage: BTreeMap<HueFamily, usize>,
+    /// Families holding well under an even share of the catalog
+    pub sparse: Vec<HueFamily>,
+}
+
+/// Families with fewer than this share of an even split are reported.
+const SPARSE_SHARE: f32 = 0.5;
+
+/// Hue words a description may use, by the family they belong to.
+const HUE_WORDS: [(HueFamily, &[&str]); 8] = [
+    (
+        HueFamily::Red,
+        &["red", "reddish", "crimson", "scarlet", "maroon"],
+    ),
+    (
+        HueFamily::Orange,
+        &["orange", "orangey", "brown", "tan", "rust"],
+    ),
+    (
+        HueFamily::Yellow,
+        &["yellow", "yellowish", "golden", "gold", "mustard"],
+    ),
+    (HueFamily::Green, &["green", "greenish", "olive", "lime"]),
+    (HueFamily::Cyan, &["cyan", "teal", "turquoise", "aqua"]),
+    (HueFamily::Blue, &["blue", "bluish", "navy", "azure"]),
+    (
+        HueFamily::Purple,
+        &["purple", "purplish", "violet", "lavender", "indigo"],
+    ),
+    (HueFamily::Pink, &["pink", "pinkish", "magenta", "rose"]),
+];
+
+/// Checks every entry; `colors` is taken as written, so hex values may be
+/// malformed.
+pub fn lint(colors: &[Color], options: &LintOptions) -> LintReport {
+    let mut issues = Vec::new();
+    let mut issue = |kind, index, message: String| {
+        issues.push(Issue {
+            kind,
+            index,
+            message,
+        })
+    };
+
+    let mut first_by_name: HashMap<String, usize> = HashMap::new();
+    let mut labs: Vec<(usize, Oklab)> = Vec::new();
+    let mut coverage: BTreeMap<HueFamily, usize> = BTreeMap::new();
+
+    for (i, color) in colors.iter().enumerate() {
+        match canonical_hex(&color.hex) {
+            None => issue(
+                IssueKind::InvalidHex,
+                i,
+                format!("{}: '{}' is not a hex color", color.name, color.hex),
+            ),
+            Some(hex) if hex.len() != color.hex.trim().len() => issue(
+                IssueKind::NonCanonicalHex,
+                i,
+                format!("{}: '{}' should be written {}", color.name, color.hex, hex),
+            ),
+            Some(_) => {}
+        }
+
+        match first_by_name.get(&color.name.to_lowercase()) {
+            Some(&first) => issue(
+                IssueKind::DuplicateName,
+                i,
+                format!(
+                    "{} ({}) repeats entry {} ({})",
+                    color.name,
+                    color.hex,
+                    first + 1,
+                    colors[first].hex
+                ),
+            ),
+            None => {
+                first_by_name.insert(color.name.to_lowercase(), i);
+            }
+        }
+
+        let description = color.description.trim();
+        if description.is_empty() {
+            issue(
+                IssueKind::MissingDescription,
+                i,
+                format!("{} has no description", color.name),
+            );
+        } else if description.chars().count() < options.min_description {
+            issue(
+                IssueKind::ShortDescription,
+                i,
+                format!("{}: \"{}\"", color.name, description),
+            );
+        }
+
+        let Some(lab) = canonical_hex(&color.hex).and_then(|hex| Color::from_hex(&hex)?.to_oklab())
+        else {
+            continue;
+        };
+        let family = lab.to_oklch().hue_family();
+        *coverage.entry(family).or_default() += 1;
+
+        if let Some(word) = contradicting_word(description, family) {
+            issue(
+                IssueKind::InconsistentDescription,
+                i,
+                format!(
+                    "{} is {} but its description says \"{}\"",
+                    color.name,
+                    family.name(),
+                    word
+                ),
+            );
+        }
+
+        for (j, other) in &labs {
+            let distance = lab.delta_e(other);
+            // A repeated name is already reported
+            if distance < options.delta_e && !colors[*j].name.eq_ignore_ascii_case(&color.name) {
+                issue(
+                    IssueKind::NearDuplicate,
+                    i,
+                    format!(
+                        "{} ({}) is ΔE {:.3} from {} ({})",
+                        color.name, color.hex, distance, colors[*j].name, colors[*j].hex
+                    ),
+                );
+                break;
+            }
+        }
+        labs.push((i, lab));
+    }
+
+    let even_share = labs.len() as f32 / HueFamily::ALL.len() as f32;
+    let sparse = HueFamily::ALL
+        .iter()
+        .copied()
+        .filter(|family| (*coverage.get(family).unwrap_or(&0) as f32) < even_share * SPARSE_SHARE)
+        .collect();
+
+    issues.sort_by_key(|issue| (issue.kind, issue.index));
+    LintReport {
+        issues,
+        coverage,
+        sparse,
+    }
+}
+
+/// `#RRGGBB` for 3-, 6- and 8-digit (alpha dropped) hex values.
+fn canonical_hex(hex: &str) -> Option<String> {
+    let digits = hex.trim().trim_start_matches('#');
+    let digits = match digits.len() {
+        // Checked first so slicing can't split a multi-byte character
+        8 if digits.bytes().all(|b| b.is_ascii_hexdigit()) => &digits[..6],
+        _ => digits,
+    };
+    let (r, g, b) = parse_hex(digits)?;
+    Some(to_hex(r, g, b))
+}
+
+/// A hue word in the description that belongs to neither the color's family
+/// nor its neighbours, when no word matches them.
+fn contradicting_word(description: &str, family: HueFamily) -> Option<&'static str> {
+    if family == HueFamily::Neutral {
+        return None;
+    }
+    let position = |family: HueFamily| HUE_WORDS.iter().position(|(f, _)| *f == family);
+    let own = position(family)?;
+    let n = HUE_WORDS.len();
+    let compatible = |f: HueFamily| {
+        position(f).is_some_and(|p| p == own || p == (own + 1) % n || p == (own + n - 1) % n)
+    };
+
+    let lowered = description.to_lowercase();
+    let words: Vec<&str> = lowered
+        .split(|c: char| !c.is_alphabetic())
+        .filter(|word| !word.is_empty())
+        .collect();
+    let mut contradiction = None;
+    for (f, hue_words) in HUE_WORDS {
+        for hue_word in hue_words {
+            if words.contains(hue_word) {
+                if compatible(f) {
+                    return None;
+                }
+                contradiction.get_or_insert(*hue_word);
+            }
+        }
+    }
+    contradiction
+}
+
+/// A cleaned copy of the catalog: hex values normalized (unparseable ones
+/// dropped), exact repeats and near-identical colors dropped, clashing names
+/// numbered (skipping numbers another entry already uses), and empty or
+/// short descriptions filled in.
+pub fn fix(colors: &[Color], options: &LintOptions) -> (Vec<Color>, Vec<String>) {
+    let mut cleaned: Vec<Color> = Vec::new();
+    let mut labs: Vec<Oklab> = Vec::new();
+    let mut changes = Vec::new();
+    // Every name in the file, so "Sage" doesn't become an existing "Sage 2"
+    let mut taken: HashSet<String> = colors.iter().map(|c| c.name.to_lowercase()).collect();
+    let mut kept: HashSet<String> = HashSet::new();
+
+    for color in colors {
+        let Some(hex) = canonical_hex(&color.hex) else {
+            changes.push(format!(
+                "dropped {}: invalid hex '{}'",
+                color.name, color.hex
+            ));
+            continue;
+        };
+        let Some(lab) = Color::from_hex(&hex).and_then(|c| c.to_oklab()) else {
+            continue;
+        };
+        if let Some(k) = labs
+            .iter()
+            .position(|other| lab.delta_e(other) < options.delta_e)
+        {
+            changes.push(format!(
+                "dropped {} ({}): same as {} ({})",
+                color.name, color.hex, cleaned[k].name, cleaned[k].hex
+            ));
+            continue;
+        }
+
+        let mut fixed = Color {
+            hex,
+            ..color.clone()
+        };
+        if !kept.insert(color.name.to_lowercase()) {
+            fixed.name = (2..)
+                .map(|n| format!("{} {}", color.name, n))
+                .find(|name| !taken.contains(&name.to_lowercase()))
+                .unwrap();
+            taken.insert(fixed.name.to_lowercase());
+            kept.insert(fixed.name.to_lowercase());
+            changes.push(format!(
+                "renamed {} ({}) to {}",
+                color.name, fixed.hex, fixed.name
+            ));
+        }
+        labs.push(lab);
+        cleaned.push(fixed);
+    }
+
+    let reference = cleaned.clone();
+    for color in &mut cleaned {
+        let description = color.description.trim();
+        if description.chars().count() >= options.min_description {
+            continue;
+        }
+        let synthesized = synthesize_description(color, &reference);
+        color.description = if description.is_empty() {
+            synthesized
+        } else {
+            format!("{} {}", description, synthesized)
+        };
+        changes.push(format!("described {}", color.name));
+    }
+    (cleaned, changes)
+}
+
+/// Writes a catalog that `--catalog` reads back: JSON for `.json`, TSV (with
+/// tags and hue family) for `.tsv`, otherwise `name, hex, description` lines.
+pub fn write_catalog(path: &Path, colors: &[Color]) -> Result<()> {
+    let extension = path
+        .extension()
+        .and_then(|ext| ext.to_str())
+        .map(|ext| ext.to_lowercase());
+    let contents = match extension.as_deref() {
+        Some("json") => serde_json::to_string_pretty(colors)? + "\n",
+        Some("tsv") => colors
+            .iter()
+            .map(|c| {
+                format!(
+                    "{}\t{}\t{}\t{}\t{}\n",
+                    c.name,
+                    c.hex,
+                    c.description,
+                    c.tags.join(","),
+                    c.family.map(HueFamily::name).unwrap_or_default()
+                )
+            })
+            .collect(),
+        _ => colors
+            .iter()
+            .map(|c| {
+                format!(
+                    "{}, {}, {}\n",
+                    c.name.replace(',', " "),
+                    c.hex,
+                    c.description
+                )
+            })
+            .collect(),
+    };
+    fs::write(path, contents).with_context(|| format!("failed to write {}", path.display()))
+}
+
+/// Prints the lint report, writing a cleaned catalog when `fix` is given.
+/// Returns false when there were issues and nothing was fixed.
+pub fn run_lint(
+    colors: &[Color],
+    source: &str,
+    options: &LintOptions,
+    fix_path: Option<&Path>,
+) -> Result<bool> {
+    let report = lint(colors, options);
+    println!(
+        "\n{} {} ({} colors)",
+        "🧹 Linting catalog:".bold().bright_blue(),
+        source,
+        colors.len()
+    );
+
+    let mut by_kind: BTreeMap<IssueKind, Vec<&Issue>> = BTreeMap::new();
+    for issue in &report.issues {
+        by_kind.entry(issue.kind).or_default().push(issue);
+    }
+    for (kind, issues) in &by_kind {
+        println!(
+            "\n{} {}",
+            format!("⚠️  {}", kind.label()).yellow().bold(),
+            format!("({})", issues.len()).bright_black()
+        );
+        for issue in issues {
+            println!(
+                "   {} {}",
+                format!("#{}", issue.index + 1).bright_black(),
+                issue.message
+            );
+        }
+    }
+    if report.issues.is_empty() {
+        println!("{}", "✅ No issues found.".green());
+    }
+
+    println!("\n{}", "🎨 Hue coverage:".bright_blue());
+    for family in HueFamily::ALL {
+        let count = report.coverage.get(&family).copied().unwrap_or(0);
+        let line = format!("   {:<8} {:>4}", family.name(), count);
+        if report.sparse.contains(&family) {
+            println!("{} {}", line.yellow(), "← under-covered".yellow());
+        } else {
+            println!("{}", line);
+        }
+    }
+
+    let Some(path) = fix_path else {
+        return Ok(report.issues.is_empty());
+    };
+    let (cleaned, changes) = fix(colors, options);
+    write_catalog(path, &cleaned)?;
+    println!(
+        "\n{} {} colors to {} ({} changes)",
+        "💾 Wrote".green(),
+        cleaned.len(),
+        path.display(),
+        changes.len()
+    );
+    for change in &changes {
+        println!("   {}", change.bright_black());
+    }
+    Ok(true)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const OPTIONS: LintOptions = LintOptions {
+        delta_e: 0.02,
+        min_description: 10,
+    };
+
+    fn kinds(colors: &[Color]) -> Vec<IssueKind> {
+        lint(colors, &OPTIONS)
+            .issues
+            .into_iter()
+            .map(|issue| issue.kind)
+            .collect()
+    }
+
+    #[test]
+    fn canonicalizes_hex() {
+        assert_eq!(canonical_hex("#abc").as_deref(), Some("#AABBCC"));
+        assert_eq!(canonical_hex(" 1a2b3cff ").as_deref(), Some("#1A2B3C"));
+        assert_eq!(canonical_hex("#1a2b3czz"), None);
+        assert_eq!(canonical_hex("#abcde€"), None);
+    }
+
+    #[test]
+    fn reports_non_ascii_hex_as_invalid() {
+        let path = std::env::temp_dir().join(format!("colorify-lint-{}.csv", std::process::id()));
+        fs::write(
+            &path,
+            "name,hex,description\nFoo,#abcde€,A plain test color.\n",
+        )
+        .unwrap();
+        let colors = crate::colors::read_catalog(&path, false).unwrap();
+        fs::remove_file(&path).unwrap();
+
+        assert_eq!(colors[0].hex, "#abcde€");
+        assert_eq!(kinds(&colors), [IssueKind::InvalidHex]);
+    }
+
+    #[test]
+    fn numbers_duplicate_names_past_existing_ones() {
+        let colors = vec![
+            Color::new("Sage", "#9CAF88", "A dusty grey-green herb."),
+            Color::new("sage", "#4A7A5B", "A deeper green herb leaf."),
+            Color::new("Sage 2", "#B8C4A0", "A pale, chalky sage tint."),
+            Color::new("Sage", "#2F4F3F", "A shadowed forest green."),
+        ];
+        let (cleaned, _) = fix(&colors, &OPTIONS);
+
+        let names: Vec<&str> = cleaned.iter().map(|c| c.name.as_str()).collect();
+        assert_eq!(names, ["Sage", "sage 3", "Sage 2", "Sage 4"]);
+        assert!(!kinds(&cleaned).contains(&IssueKind::DuplicateName));
+    }
+}
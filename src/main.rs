@@ -1,16 +1,47 @@
+mod batch;
+mod bench;
+mod cache;
+mod color_space;
 mod colors;
 mod config;
+mod constraints;
+mod describe;
 mod display;
 mod embedding;
+mod eval;
+mod expansion;
+mod explain;
+mod export;
+mod feedback;
+mod history;
+mod index;
+mod lexical;
+mod lint;
+mod map;
 mod matcher;
+mod query;
+mod repl;
+mod share;
+mod swatches;
+mod tags;
+mod tui;
+mod variant;
 
+use anyhow::Context;
 use clap::{Arg, Command};
 use colored::*;
-use colors::get_color_data;
-use config::Config;
-use display::PaletteDisplay;
-use matcher::MoodPaletteGenerator;
-use std::io::{self, Write};
+use colors::{load_colors, Color};
+use config::{Config, EmbedderKind, Fusion, IndexKind, Pooling};
+use constraints::{Constraints, Tone};
+use display::{DisplayOptions, PaletteDisplay, EXAMPLE_MOODS};
+use history::{History, NewEntry, RunOptions};
+use matcher::{
+    confidence_label, palette_confidence, ColorMatch, MoodPaletteGenerator, VAGUE_CONFIDENCE,
+};
+use share::SharedPalette;
+use std::path::{Path, PathBuf};
+use tags::TagFilter;
+use variant::{Mode, ThemedPalette, Variant};
 
 #[tokio::main]
 async fn main() {
@@ -32,7 +63,152 @@ async fn main() {
                 .long("count")
                 .help("Number of colors to generate")
                 .value_name("COUNT")
-                .default_value("5"),
+                .default_value("5")
+                .global(true),
+        )
+        .arg(
+            Arg::new("diversity")
+                .long("diversity")
+                .help("How much to spread colors apart, from 0.0 (best matches) to 1.0")
+                .value_name("AMOUNT")
+                .default_value("0.0")
+                .global(true),
+        )
+        .arg(
+            Arg::new("lightness")
+                .long("lightness")
+                .help("Keep colors in this lightness band: dark, mid, light or a range like 0.2..0.5")
+                .value_name("BAND")
+                .global(true),
+        )
+        .arg(
+            Arg::new("chroma")
+                .long("chroma")
+                .help("Keep colors this saturated: muted, vivid or an OKLCH chroma range like 0.05..0.12")
+                .value_name("BAND")
+                .global(true),
+        )
+        .arg(
+            Arg::new("hue")
+                .long("hue")
+                .help("Keep colors in this OKLCH hue range in degrees, e.g. 180..260 (330..30 wraps through red)")
+                .value_name("RANGE")
+                .global(true),
+        )
+        .arg(
+            Arg::new("tone")
+                .long("tone")
+                .help("Keep warm or cool colors (greys fit either)")
+                .value_name("TONE")
+                .value_parser(["warm", "cool"])
+                .global(true),
+        )
+        .arg(
+            Arg::new("min_delta_e")
+                .long("min-delta-e")
+                .help("Keep every pair of palette colors at least this far apart (ΔEok, try 0.1)")
+                .value_name("DELTA")
+                .global(true),
+        )
+        .arg(
+            Arg::new("soft_constraints")
+                .long("soft-constraints")
+                .help("Mark down colors outside --lightness/--chroma/--hue/--tone instead of dropping them")
+                .action(clap::ArgAction::SetTrue)
+                .global(true),
+        )
+        .arg(
+            Arg::new("fusion")
+                .long("fusion")
+                .help("How word matches are blended with semantic similarity")
+                .value_name("MODE")
+                .value_parser(["linear", "rrf", "semantic"])
+                .default_value("linear")
+                .global(true),
+        )
+        .arg(
+            Arg::new("lexical_weight")
+                .long("lexical-weight")
+                .help("Weight of literal word matches in linear fusion, 0.0 to 1.0")
+                .value_name("WEIGHT")
+                .default_value("0.25")
+                .global(true),
+        )
+        .arg(
+            Arg::new("catalog")
+                .long("catalog")
+                .help("Load colors from a JSON, CSV, TSV or swatch file (.gpl, .ase, .aco, .swatches) instead of the built-in catalog")
+                .value_name("FILE")
+                .global(true),
+        )
+        .arg(
+            Arg::new("index")
+                .long("index")
+                .help("Nearest-neighbour index for semantic search")
+                .value_name("KIND")
+                .value_parser(["auto", "brute", "hnsw"])
+                .default_value("auto")
+                .global(true),
+        )
+        .arg(
+            Arg::new("min_score")
+                .long("min-score")
+                .help("Drop colors whose similarity to the mood is below this")
+                .value_name("SCORE")
+                .default_value("0.15")
+                .global(true),
+        )
+        .arg(
+            Arg::new("relative_floor")
+                .long("relative-floor")
+                .help("Drop colors scoring below this fraction of the best match, 0.0 to 1.0")
+                .value_name("FRACTION")
+                .default_value("0.5")
+                .global(true),
+        )
+        .arg(
+            Arg::new("keep")
+                .short('k')
+                .long("keep")
+                .help("Keep a color (catalog name or hex) and fill the rest around it")
+                .value_name("COLOR")
+                .action(clap::ArgAction::Append),
+        )
+        .arg(
+            Arg::new("temperature")
+                .short('t')
+                .long("temperature")
+                .help("Sample colors instead of taking the top matches (try 0.02-0.1)")
+                .value_name("TEMP")
+                .global(true),
+        )
+        .arg(
+            Arg::new("seed")
+                .long("seed")
+                .help("Seed for sampling, for reproducible palettes")
+                .value_name("SEED")
+                .global(true),
+        )
+        .arg(
+            Arg::new("variations")
+                .long("variations")
+                .help("Show several distinct palettes for the same mood")
+                .value_name("N")
+                .default_value("1"),
+        )
+        .arg(
+            Arg::new("no_pin")
+                .long("no-pin")
+                .help("Treat hex codes and color names in the mood as plain words")
+                .action(clap::ArgAction::SetTrue)
+                .global(true),
+        )
+        .arg(
+            Arg::new("snap")
+                .long("snap")
+                .help("Swap colors written in the mood for their nearest catalog color")
+                .action(clap::ArgAction::SetTrue)
+                .global(true),
         )
         .arg(
             Arg::new("interactive")
@@ -54,12 +230,357 @@ async fn main() {
                 .help("Hide color names in bar mode")
                 .action(clap::ArgAction::SetTrue),
         )
+        .arg(
+            Arg::new("export")
+                .long("export")
+                .help("Save the palette; the extension picks the format (.json, .css, .gpl, .ase, .kpl or plain text)")
+                .value_name("FILE"),
+        )
+        .arg(
+            Arg::new("variant")
+                .long("variant")
+                .help("Also show the palette for the other color scheme: dark, light or both; .css exports get both under prefers-color-scheme")
+                .value_name("MODE")
+                .value_parser(["dark", "light", "both"]),
+        )
+        .arg(
+            Arg::new("snap_variant")
+                .long("snap-variant")
+                .help("Snap --variant colors to the nearest catalog color in their lightness band")
+                .action(clap::ArgAction::SetTrue),
+        )
+        .arg(
+            Arg::new("share")
+                .long("share")
+                .help("Print a short code for each palette that colorify decode can open")
+                .action(clap::ArgAction::SetTrue)
+                .global(true),
+        )
+        .arg(
+            Arg::new("verbose")
+                .short('v')
+                .long("verbose")
+                .help("Show how each score was put together")
+                .action(clap::ArgAction::SetTrue)
+                .global(true),
+        )
+        .arg(
+            Arg::new("expand")
+                .long("expand")
+                .help("Expand abstract mood words (hygge, melancholy) into concrete visual ones")
+                .action(clap::ArgAction::SetTrue)
+                .global(true),
+        )
+        .arg(
+            Arg::new("thesaurus")
+                .long("thesaurus")
+                .help("Extra thesaurus file of 'term: descriptor, descriptor' lines (implies --expand)")
+                .value_name("FILE")
+                .global(true),
+        )
+        .arg(
+            Arg::new("template")
+                .long("template")
+                .help("Describe colors to the model with this template, e.g. \"a {name} colored scene\" (repeatable)")
+                .value_name("TEMPLATE")
+                .action(clap::ArgAction::Append)
+                .global(true),
+        )
+        .arg(
+            Arg::new("pooling")
+                .long("pooling")
+                .help("How scores under several templates are combined")
+                .value_name("MODE")
+                .value_parser(["max", "mean"])
+                .default_value("max")
+                .global(true),
+        )
+        .arg(
+            Arg::new("embedder")
+                .long("embedder")
+                .help("Embedding backend; 'hashing' is deterministic and needs no model download")
+                .value_name("KIND")
+                .value_parser(["model", "hashing"])
+                .default_value("model")
+                .global(true),
+        )
+        .arg(
+            Arg::new("no_feedback")
+                .long("no-feedback")
+                .help("Ignore saved thumbs up/down feedback")
+                .action(clap::ArgAction::SetTrue)
+                .global(true),
+        )
+        .arg(
+            Arg::new("only")
+                .long("only")
+                .help("Only use colors with one of these tags or hue families (e.g. earthy,pastel,blue)")
+                .value_name("TAGS")
+                .value_delimiter(',')
+                .action(clap::ArgAction::Append)
+                .global(true),
+        )
+        .arg(
+            Arg::new("exclude")
+                .long("exclude")
+                .help("Never use colors with any of these tags or hue families (e.g. neon)")
+                .value_name("TAGS")
+                .value_delimiter(',')
+                .action(clap::ArgAction::Append)
+                .global(true),
+        )
+        .arg(
+            Arg::new("no_history")
+                .long("no-history")
+                .help("Don't save generated palettes to the history")
+                .action(clap::ArgAction::SetTrue)
+                .global(true),
+        )
+        .arg(
+            Arg::new("explain")
+                .long("explain")
+                .help("Show which mood words drove each color")
+                .action(clap::ArgAction::SetTrue)
+                .global(true),
+        )
         .arg(
             Arg::new("examples")
                 .long("examples")
                 .help("Show example mood descriptions")
                 .action(clap::ArgAction::SetTrue),
         )
+        .subcommand(
+            Command::new("tui")
+                .about("Explore palettes in a full-screen terminal UI")
+                .arg(
+                    Arg::new("mood")
+                        .help("Mood to start with")
+                        .value_name("MOOD")
+                        .index(1),
+                ),
+        )
+        .subcommand(
+            Command::new("batch")
+                .about("Generate palettes for many moods, one per line, as JSON lines")
+                .arg(
+                    Arg::new("input")
+                        .help("File with one mood per line (reads stdin if omitted or '-')")
+                        .value_name("FILE")
+                        .index(1),
+                )
+                .arg(
+                    Arg::new("jobs")
+                        .short('j')
+                        .long("jobs")
+                        .help("Number of parallel scoring workers")
+                        .value_name("JOBS")
+                        .default_value("1"),
+                )
+                .arg(
+                    Arg::new("batch_size")
+                        .long("batch-size")
+                        .help("Number of moods to embed per model call")
+                        .value_name("SIZE")
+                        .default_value("32"),
+                ),
+        )
+        .subcommand(
+            Command::new("eval")
+                .about("Measure retrieval quality against a golden set of moods and expected colors (saved feedback is ignored)")
+                .arg(
+                    Arg::new("golden")
+                        .help("JSON lines of {\"mood\": ..., \"expected\": [color names]}")
+                        .value_name("FILE")
+                        .default_value("data/golden.jsonl")
+                        .index(1),
+                )
+                .arg(
+                    Arg::new("k")
+                        .short('k')
+                        .help("Cutoff rank for precision, recall and nDCG")
+                        .value_name("K")
+                        .default_value("10"),
+                )
+                .arg(
+                    Arg::new("against")
+                        .long("against")
+                        .help("Compare with the same setup plus this change, e.g. pooling=mean (repeatable)")
+                        .value_name("KEY=VALUE")
+                        .action(clap::ArgAction::Append),
+                )
+                .arg(
+                    Arg::new("fail_below")
+                        .long("fail-below")
+                        .help("Exit with an error if a mean metric drops below this, e.g. ndcg=0.3")
+                        .value_name("METRIC=VALUE")
+                        .action(clap::ArgAction::Append),
+                ),
+        )
+        .subcommand(
+            Command::new("feedback")
+                .about("Inspect, export or reset learned color preferences")
+                .subcommand_required(true)
+                .subcommand(Command::new("show").about("List saved thumbs up/down"))
+                .subcommand(
+                    Command::new("export")
+                        .about("Write feedback and per-color bias as JSON")
+                        .arg(Arg::new("path").value_name("FILE").required(true)),
+                )
+                .subcommand(Command::new("reset").about("Forget all feedback")),
+        )
+        .subcommand(
+            Command::new("catalog")
+                .about("Check and clean the color catalog (--catalog, or the built-in one)")
+                .subcommand_required(true)
+                .subcommand(
+                    Command::new("lint")
+                        .about("Flag bad hex values, duplicates, near-identical colors, weak descriptions and sparse hues")
+                        .arg(
+                            Arg::new("delta_e")
+                                .long("delta-e")
+                                .help("Colors closer than this (ΔEok) count as duplicates")
+                                .value_name("DELTA")
+                                .default_value("0.01"),
+                        )
+                        .arg(
+                            Arg::new("min_description")
+                                .long("min-description")
+                                .help("Flag descriptions shorter than this many characters")
+                                .value_name("CHARS")
+                                .default_value("20"),
+                        )
+                        .arg(
+                            Arg::new("fix")
+                                .long("fix")
+                                .help("Write a cleaned catalog here (.json, .tsv or name, hex, description lines)")
+                                .value_name("FILE"),
+                        ),
+                )
+                .subcommand(
+                    Command::new("map")
+                        .about("Draw the catalog as an SVG: hue wheel, lightness/chroma plane and embedding space with mood labels")
+                        .arg(
+                            Arg::new("output")
+                                .short('o')
+                                .long("output")
+                                .help("SVG file to write")
+                                .value_name("FILE")
+                                .default_value("map.svg"),
+                        )
+                        .arg(
+                            Arg::new("moods")
+                                .long("moods")
+                                .help("Label these moods (one per line) instead of the examples")
+                                .value_name("FILE"),
+                        ),
+                ),
+        )
+        .subcommand(
+            Command::new("describe")
+                .about("Name and describe colors from swatch files (.gpl, .ase, .aco, .swatches), catalogs or hex values")
+                .arg(
+                    Arg::new("inputs")
+                        .value_name("FILE|HEX")
+                        .required(true)
+                        .num_args(1..),
+                ),
+        )
+        .subcommand(
+            Command::new("decode")
+                .about("Show a palette from a share code")
+                .arg(Arg::new("code").value_name("CODE").required(true))
+                .arg(
+                    Arg::new("output")
+                        .short('o')
+                        .long("output")
+                        .help("Also export it (.json, .css, .gpl, .ase, .kpl or plain text)")
+                        .value_name("FILE"),
+                ),
+        )
+        .subcommand(
+            Command::new("history")
+                .about("Browse, search, re-run and export past palettes")
+                .subcommand_required(true)
+                .subcommand(
+                    Command::new("list").about("Show recent palettes").arg(
+                        Arg::new("limit")
+                            .short('n')
+                            .help("Number of entries to show")
+                            .value_name("N")
+                            .default_value("20"),
+                    ),
+                )
+                .subcommand(
+                    Command::new("show")
+                        .about("Show one palette with the options that made it")
+                        .arg(Arg::new("id").value_name("ID").required(true)),
+                )
+                .subcommand(
+                    Command::new("search")
+                        .about("Find palettes by mood, color or tag")
+                        .arg(Arg::new("text").value_name("TEXT").required(true)),
+                )
+                .subcommand(
+                    Command::new("rerun")
+                        .about("Generate a saved palette again with its original options (--seed overrides)")
+                        .arg(Arg::new("id").value_name("ID").required(true)),
+                )
+                .subcommand(
+                    Command::new("export")
+                        .about("Write the history as JSON, or one palette in the format its extension implies")
+                        .arg(Arg::new("path").value_name("FILE").required(true))
+                        .arg(
+                            Arg::new("id")
+                                .long("id")
+                                .help("Export only this entry")
+                                .value_name("ID"),
+                        ),
+                )
+                .subcommand(
+                    Command::new("tag")
+                        .about("Tag a palette")
+                        .arg(Arg::new("id").value_name("ID").required(true))
+                        .arg(
+                            Arg::new("tags")
+                                .value_name("TAG")
+                                .required(true)
+                                .num_args(1..),
+                        ),
+                ),
+        )
+        .subcommand(
+            Command::new("fav")
+                .about("Mark, unmark or list favorite palettes")
+                .subcommand_required(true)
+                .subcommand(
+                    Command::new("add")
+                        .about("Mark a history entry as a favorite")
+                        .arg(Arg::new("id").value_name("ID").required(true)),
+                )
+                .subcommand(
+                    Command::new("rm")
+                        .about("Unmark a favorite")
+                        .arg(Arg::new("id").value_name("ID").required(true)),
+                )
+                .subcommand(Command::new("list").about("List favorite palettes")),
+        )
+        .subcommand(
+            Command::new("bench")
+                .about("Compare HNSW recall and latency against brute-force search (saved feedback is ignored)")
+                .arg(
+                    Arg::new("queries")
+                        .help("File with one mood per line (uses built-in moods if omitted)")
+                        .value_name("FILE")
+                        .index(1),
+                )
+                .arg(
+                    Arg::new("k")
+                        .short('k')
+                        .help("Neighbours to retrieve per query")
+                        .value_name("K")
+                        .default_value("10"),
+                ),
+        )
         .get_matches();
 
     if matches.get_flag("examples") {
@@ -67,14 +588,211 @@ async fn main() {
         return;
     }
 
+    if let Some(("feedback", feedback_matches)) = matches.subcommand() {
+        if let Err(e) = run_feedback_command(feedback_matches) {
+            eprintln!("❌ {:#}", e);
+            std::process::exit(1);
+        }
+        return;
+    }
+
+    let display = DisplayOptions {
+        show_bar: matches.get_flag("bar"),
+        no_names: matches.get_flag("no_names"),
+        verbose: matches.get_flag("verbose"),
+        share: matches.get_flag("share"),
+    };
+
+    match matches.subcommand() {
+        // Mapping needs the model, so it runs with the other commands below
+        Some(("catalog", catalog_matches)) if catalog_matches.subcommand_name() != Some("map") => {
+            let catalog = matches.get_one::<String>("catalog").map(PathBuf::from);
+            match run_catalog_command(catalog_matches, catalog.as_deref()) {
+                Ok(true) => {}
+                Ok(false) => std::process::exit(1),
+                Err(e) => {
+                    eprintln!("❌ {:#}", e);
+                    std::process::exit(1);
+                }
+            }
+            return;
+        }
+        Some(("describe", describe_matches)) => {
+            let catalog = matches.get_one::<String>("catalog").map(PathBuf::from);
+            let inputs: Vec<String> = describe_matches
+                .get_many::<String>("inputs")
+                .unwrap_or_default()
+                .cloned()
+                .collect();
+            if let Err(e) = load_colors(catalog.as_deref())
+                .and_then(|catalog| describe::run_describe(&inputs, &catalog))
+            {
+                eprintln!("❌ {:#}", e);
+                std::process::exit(1);
+            }
+            return;
+        }
+        Some(("decode", decode_matches)) => {
+            let catalog = matches.get_one::<String>("catalog").map(PathBuf::from);
+            if let Err(e) = run_decode(decode_matches, catalog.as_deref(), &display) {
+                eprintln!("❌ {:#}", e);
+                std::process::exit(1);
+            }
+            return;
+        }
+        Some(("history", history_matches)) => {
+            let result = match history_matches.subcommand() {
+                Some(("rerun", rerun_matches)) => rerun_history(rerun_matches, &display).await,
+                _ => run_history_command(history_matches, &display),
+            };
+            if let Err(e) = result {
+                eprintln!("❌ {:#}", e);
+                std::process::exit(1);
+            }
+            return;
+        }
+        Some(("fav", fav_matches)) => {
+            if let Err(e) = run_fav_command(fav_matches, &display) {
+                eprintln!("❌ {:#}", e);
+                std::process::exit(1);
+            }
+            return;
+        }
+        _ => {}
+    }
+
     let count: usize = matches
         .get_one::<String>("count")
         .unwrap()
         .parse()
         .unwrap_or(5);
 
-    let config = Config::new().with_top_k(count);
-    let colors = get_color_data();
+    let diversity: f32 = matches
+        .get_one::<String>("diversity")
+        .unwrap()
+        .parse()
+        .unwrap_or(0.0);
+
+    let mut config = Config::new().with_top_k(count).with_diversity(diversity);
+    let lexical_weight: f32 = matches
+        .get_one::<String>("lexical_weight")
+        .unwrap()
+        .parse()
+        .unwrap_or(0.25);
+    config = config.with_fusion(
+        match matches.get_one::<String>("fusion").unwrap().as_str() {
+            "rrf" => Fusion::Rrf { k: 60.0 },
+            "semantic" => Fusion::Semantic,
+            _ => Fusion::Linear {
+                weight: lexical_weight.clamp(0.0, 1.0),
+            },
+        },
+    );
+    config = config
+        .with_pin_mentions(!matches.get_flag("no_pin"))
+        .with_snap_mentions(matches.get_flag("snap"))
+        .with_explain(matches.get_flag("explain"));
+    config = config
+        .with_templates(
+            matches
+                .get_many::<String>("template")
+                .unwrap_or_default()
+                .cloned()
+                .collect(),
+        )
+        .with_pooling(
+            match matches.get_one::<String>("pooling").unwrap().as_str() {
+                "mean" => Pooling::Mean,
+                _ => Pooling::Max,
+            },
+        );
+    // Measurements shouldn't depend on what this machine's thumbs up/down
+    // taught it, so eval and bench always score without feedback
+    let measuring = matches!(matches.subcommand_name(), Some("eval" | "bench"));
+    config = config.with_feedback(!matches.get_flag("no_feedback") && !measuring);
+    let tag_list = |id: &str| -> Vec<String> {
+        matches
+            .get_many::<String>(id)
+            .unwrap_or_default()
+            .cloned()
+            .collect()
+    };
+    config = config.with_tag_filter(tag_list("only"), tag_list("exclude"));
+    match parse_constraints(&matches) {
+        Ok(constraints) => config = config.with_constraints(constraints),
+        Err(e) => {
+            eprintln!("❌ {:#}", e);
+            std::process::exit(1);
+        }
+    }
+    config = config.with_embedder(
+        match matches.get_one::<String>("embedder").unwrap().as_str() {
+            "hashing" => EmbedderKind::Hashing,
+            _ => EmbedderKind::Model,
+        },
+    );
+    let thesaurus = matches.get_one::<String>("thesaurus").map(PathBuf::from);
+    config = config.with_expansion(matches.get_flag("expand") || thesaurus.is_some(), thesaurus);
+    if let Some(temperature) = matches.get_one::<String>("temperature") {
+        match temperature.parse::<f32>() {
+            Ok(temperature) => config = config.with_temperature(temperature),
+            Err(_) => eprintln!("⚠️  Ignoring invalid temperature '{}'", temperature),
+        }
+    }
+    if let Some(seed) = matches.get_one::<String>("seed") {
+        match seed.parse::<u64>() {
+            Ok(seed) => config = config.with_seed(seed),
+            Err(_) => eprintln!("⚠️  Ignoring invalid seed '{}'", seed),
+        }
+    }
+    if config.seed.is_none() {
+        // Always seed so that a saved palette can be reproduced exactly.
+        config = config.with_seed(rand::random());
+    }
+    let min_score: f32 = matches
+        .get_one::<String>("min_score")
+        .unwrap()
+        .parse()
+        .unwrap_or(0.15);
+    let relative_floor: f32 = matches
+        .get_one::<String>("relative_floor")
+        .unwrap()
+        .parse()
+        .unwrap_or(0.5);
+    config = config
+        .with_min_score(min_score)
+        .with_relative_floor(relative_floor);
+    config = config.with_index(match matches.get_one::<String>("index").unwrap().as_str() {
+        "brute" => IndexKind::BruteForce,
+        "hnsw" => IndexKind::Hnsw,
+        _ => IndexKind::Auto,
+    });
+    if let Some(("batch", batch_matches)) = matches.subcommand() {
+        let batch_size = batch_matches
+            .get_one::<String>("batch_size")
+            .unwrap()
+            .parse()
+            .unwrap_or(32);
+        config = config.with_batch_size(batch_size);
+    }
+    let catalog = matches.get_one::<String>("catalog").map(PathBuf::from);
+    let colors = match load_colors(catalog.as_deref()) {
+        Ok(colors) => colors,
+        Err(e) => {
+            eprintln!("❌ Failed to load catalog: {:#}", e);
+            std::process::exit(1);
+        }
+    };
+    for term in config.only.iter().chain(&config.exclude) {
+        let filter = TagFilter::new(std::slice::from_ref(term), &[]);
+        if !colors.iter().any(|color| filter.allows(color)) {
+            eprintln!(
+                "⚠️  No color in the catalog is tagged '{}' (known tags: {})",
+                term,
+                tags::KNOWN_TAGS.join(", ")
+            );
+        }
+    }
 
     // Initialize the generator with advanced semantic matching
     let mut generator = match MoodPaletteGenerator::new(config, colors).await {
@@ -85,21 +803,186 @@ async fn main() {
         }
     };
 
-    if matches.get_flag("interactive") {
-        run_interactive_mode(
+    if let Some(("batch", batch_matches)) = matches.subcommand() {
+        let jobs: usize = batch_matches
+            .get_one::<String>("jobs")
+            .unwrap()
+            .parse()
+            .unwrap_or(1);
+        if let Err(e) = batch::run_batch(
             &mut generator,
-            matches.get_flag("bar"),
-            matches.get_flag("no_names"),
+            batch_matches.get_one::<String>("input").map(|s| s.as_str()),
+            jobs,
         )
-        .await;
-    } else if let Some(mood) = matches.get_one::<String>("mood") {
-        generate_and_display(
+        .await
+        {
+            eprintln!("❌ Batch processing failed: {}", e);
+            std::process::exit(1);
+        }
+    } else if let Some(("catalog", catalog_matches)) = matches.subcommand() {
+        if let Some(("map", map_matches)) = catalog_matches.subcommand() {
+            let output = PathBuf::from(map_matches.get_one::<String>("output").unwrap());
+            let moods = map_matches.get_one::<String>("moods").map(|s| s.as_str());
+            if let Err(e) = map::run_map(&mut generator, &output, moods).await {
+                eprintln!("❌ Failed to map catalog: {:#}", e);
+                std::process::exit(1);
+            }
+        }
+    } else if let Some(("eval", eval_matches)) = matches.subcommand() {
+        let mut against = None;
+        if let Some(specs) = eval_matches.get_many::<String>("against") {
+            let mut other_config = generator.config().clone();
+            for spec in specs {
+                other_config = match eval::apply_override(other_config, spec) {
+                    Ok(config) => config,
+                    Err(e) => {
+                        eprintln!("❌ Invalid --against: {}", e);
+                        std::process::exit(1);
+                    }
+                };
+            }
+            match MoodPaletteGenerator::new(other_config, generator.colors().to_vec()).await {
+                Ok(other) => against = Some(other),
+                Err(e) => {
+                    eprintln!("❌ Failed to initialize comparison setup: {}", e);
+                    std::process::exit(1);
+                }
+            }
+        }
+
+        let mut gates = Vec::new();
+        for spec in eval_matches
+            .get_many::<String>("fail_below")
+            .unwrap_or_default()
+        {
+            match eval::parse_gate(spec) {
+                Ok(gate) => gates.push(gate),
+                Err(e) => {
+                    eprintln!("❌ Invalid --fail-below: {}", e);
+                    std::process::exit(1);
+                }
+            }
+        }
+
+        let options = eval::EvalOptions {
+            k: eval_matches
+                .get_one::<String>("k")
+                .unwrap()
+                .parse()
+                .unwrap_or(10),
+            verbose: display.verbose,
+            gates,
+        };
+        let golden = eval_matches.get_one::<String>("golden").unwrap();
+        match eval::run_eval(&mut generator, against.as_mut(), golden, &options).await {
+            Ok(true) => {}
+            Ok(false) => std::process::exit(1),
+            Err(e) => {
+                eprintln!("❌ Evaluation failed: {}", e);
+                std::process::exit(1);
+            }
+        }
+    } else if let Some(("bench", bench_matches)) = matches.subcommand() {
+        let k: usize = bench_matches
+            .get_one::<String>("k")
+            .unwrap()
+            .parse()
+            .unwrap_or(10);
+        if let Err(e) = bench::run_index_bench(
             &mut generator,
-            mood,
-            matches.get_flag("bar"),
-            matches.get_flag("no_names"),
+            bench_matches
+                .get_one::<String>("queries")
+                .map(|s| s.as_str()),
+            k,
         )
-        .await;
+        .await
+        {
+            eprintln!("❌ Benchmark failed: {}", e);
+            std::process::exit(1);
+        }
+    } else if let Some(("tui", tui_matches)) = matches.subcommand() {
+        let mood = tui_matches.get_one::<String>("mood").map(|s| s.as_str());
+        if let Err(e) = tui::run_tui(&mut generator, mood).await {
+            eprintln!("❌ Terminal UI failed: {}", e);
+            std::process::exit(1);
+        }
+    } else if matches.get_flag("interactive") {
+        repl::run_interactive_mode(&mut generator, display).await;
+    } else if let Some(mood) = matches.get_one::<String>("mood") {
+        let mut locked = Vec::new();
+        for spec in matches.get_many::<String>("keep").unwrap_or_default() {
+            match generator.resolve_color(spec) {
+                Some(color) => locked.push(color),
+                None => {
+                    eprintln!(
+                        "❌ Unknown color '{}': use a catalog name or a hex value like #aa3300",
+                        spec
+                    );
+                    std::process::exit(1);
+                }
+            }
+        }
+
+        let variations: usize = matches
+            .get_one::<String>("variations")
+            .unwrap()
+            .parse()
+            .unwrap_or(1);
+
+        let palettes =
+            generate_and_display(&mut generator, mood, &locked, variations, &display).await;
+        let variant = matches
+            .get_one::<String>("variant")
+            .and_then(|variant| Variant::parse(variant).ok());
+        let themed: Vec<ThemedPalette> = match variant {
+            Some(variant) => {
+                let catalog = matches.get_flag("snap_variant").then(|| generator.colors());
+                palettes
+                    .iter()
+                    .map(|palette| ThemedPalette::new(palette, variant, catalog))
+                    .collect()
+            }
+            None => vec![],
+        };
+        let mut readable = true;
+        for pair in &themed {
+            readable &= variant::show_themed(pair, &display);
+        }
+        if !readable {
+            println!(
+                "{}",
+                format!(
+                    "⚠️  Some colors fall below {}:1 contrast with their mode's text.",
+                    variant::MIN_TEXT_CONTRAST
+                )
+                .yellow()
+            );
+        }
+        if let Some(path) = matches.get_one::<String>("export") {
+            if themed.is_empty() {
+                export_palettes(Path::new(path), &palettes);
+            } else {
+                export_themed_palettes(Path::new(path), &themed);
+            }
+        }
+        if !matches.get_flag("no_history") {
+            let options = RunOptions {
+                config: generator.config().clone(),
+                // Absolute, so the entry can be re-run from any directory
+                catalog: catalog.map(|path| path.canonicalize().unwrap_or(path)),
+                keep: matches
+                    .get_many::<String>("keep")
+                    .unwrap_or_default()
+                    .cloned()
+                    .collect(),
+                variations,
+                variation: 0,
+                feedback_count: generator.feedback_count(),
+            };
+            if let Err(e) = save_history(&generator, mood, options, &palettes) {
+                eprintln!("⚠️  Palette not saved to history: {:#}", e);
+            }
+        }
     } else {
         PaletteDisplay::display_header();
         println!(
@@ -111,65 +994,485 @@ async fn main() {
     }
 }
 
-async fn run_interactive_mode(
-    generator: &mut MoodPaletteGenerator,
-    show_bar: bool,
-    no_names: bool,
-) {
-    PaletteDisplay::display_header();
+fn run_feedback_command(matches: &clap::ArgMatches) -> anyhow::Result<()> {
+    let mut store = feedback::FeedbackStore::load()?;
+    match matches.subcommand() {
+        Some(("export", export_matches)) => {
+            let path = export_matches.get_one::<String>("path").unwrap();
+            store.export(Path::new(path))?;
+            println!(
+                "{} {} entries to {}",
+                "💾 Exported".green(),
+                store.entries().len(),
+                path
+            );
+        }
+        Some(("reset", _)) => {
+            store.reset()?;
+            println!("{}", "🧹 Forgot all feedback.".cyan());
+        }
+        _ => {
+            if store.entries().is_empty() {
+                println!(
+                    "{}",
+                    "No feedback yet. Use :up N or :down N in interactive mode.".yellow()
+                );
+            }
+            for entry in store.entries() {
+                let thumb = if entry.verdict > 0 { "👍" } else { "👎" };
+                println!(
+                    "{} {} {} for {}",
+                    thumb,
+                    entry.color.bold(),
+                    entry.hex.bright_black(),
+                    entry.mood.italic()
+                );
+            }
+        }
+    }
+    Ok(())
+}
+
+fn run_catalog_command(matches: &clap::ArgMatches, catalog: Option<&Path>) -> anyhow::Result<bool> {
+    let Some(("lint", lint_matches)) = matches.subcommand() else {
+        return Ok(true);
+    };
+    // Lint the catalog as written, before loading normalizes it
+    let (colors, source) = match catalog {
+        Some(path) => (
+            colors::read_catalog(path, false)?,
+            path.display().to_string(),
+        ),
+        None => (colors::get_color_data(), "built-in".to_string()),
+    };
+    let options = lint::LintOptions {
+        delta_e: lint_matches
+            .get_one::<String>("delta_e")
+            .unwrap()
+            .parse()
+            .unwrap_or(0.01),
+        min_description: lint_matches
+            .get_one::<String>("min_description")
+            .unwrap()
+            .parse()
+            .unwrap_or(20),
+    };
+    let fix = lint_matches.get_one::<String>("fix").map(Path::new);
+    lint::run_lint(&colors, &source, &options, fix)
+}
+
+fn run_decode(
+    matches: &clap::ArgMatches,
+    catalog: Option<&Path>,
+    display: &DisplayOptions,
+) -> anyhow::Result<()> {
+    let mut shared = SharedPalette::decode(matches.get_one::<String>("code").unwrap())?;
+    shared.describe_from(&load_colors(catalog)?);
+
+    if let Some(mood) = &shared.mood {
+        println!(
+            "\n{} {}",
+            "🔗 Shared palette for:".bright_blue(),
+            mood.italic().white()
+        );
+    }
+    let palette = shared.to_matches();
+    PaletteDisplay::show(&palette, display);
+
+    if let Some(path) = matches.get_one::<String>("output") {
+        export::export_palette(Path::new(path), &palette)?;
+        println!("{} {}", "💾 Saved palette to".green(), path);
+    }
+    Ok(())
+}
+
+fn print_share_code(mood: &str, palette: &[ColorMatch]) {
+    match SharedPalette::new(Some(mood), palette).encode() {
+        Ok(code) => println!("{} {}", "🔗 Share code:".bright_blue(), code),
+        Err(e) => println!("⚠️  Could not make a share code: {}", e),
+    }
+}
+
+/// Saves each palette; several variations go to numbered files next to
+/// `path`.
+fn export_palettes(path: &Path, palettes: &[Vec<ColorMatch>]) {
+    for (i, palette) in palettes.iter().enumerate() {
+        let target = if palettes.len() == 1 {
+            path.to_path_buf()
+        } else {
+            suffixed_path(path, &(i + 1).to_string())
+        };
+        match export::export_palette(&target, palette) {
+            Ok(()) => println!("{} {}", "💾 Saved palette to".green(), target.display()),
+            Err(e) => eprintln!("❌ Export failed: {:#}", e),
+        }
+    }
+}
+
+/// CSS gets both modes in one file under `prefers-color-scheme`; other
+/// formats get a `-light` and a `-dark` file.
+fn export_themed_palettes(path: &Path, themed: &[ThemedPalette]) {
+    for (i, pair) in themed.iter().enumerate() {
+        let target = if themed.len() == 1 {
+            path.to_path_buf()
+        } else {
+            suffixed_path(path, &(i + 1).to_string())
+        };
+        if export::ExportFormat::from_path(&target) == export::ExportFormat::Css {
+            match variant::export_themed(&target, pair) {
+                Ok(()) => println!("{} {}", "💾 Saved themed CSS to".green(), target.display()),
+                Err(e) => eprintln!("❌ Export failed: {:#}", e),
+            }
+            continue;
+        }
+        for mode in [Mode::Light, Mode::Dark] {
+            let target = suffixed_path(&target, mode.name());
+            match export::export_palette(&target, pair.palette(mode)) {
+                Ok(()) => println!("{} {}", "💾 Saved palette to".green(), target.display()),
+                Err(e) => eprintln!("❌ Export failed: {:#}", e),
+            }
+        }
+    }
+}
+
+/// `palette.css` with suffix `2` becomes `palette-2.css`.
+fn suffixed_path(path: &Path, suffix: &str) -> PathBuf {
+    let stem = path
+        .file_stem()
+        .and_then(|stem| stem.to_str())
+        .unwrap_or("palette");
+    let name = match path.extension().and_then(|ext| ext.to_str()) {
+        Some(ext) => format!("{}-{}.{}", stem, suffix, ext),
+        None => format!("{}-{}", stem, suffix),
+    };
+    path.with_file_name(name)
+}
+
+fn save_history(
+    generator: &MoodPaletteGenerator,
+    mood: &str,
+    mut options: RunOptions,
+    palettes: &[Vec<ColorMatch>],
+) -> anyhow::Result<()> {
+    if palettes.is_empty() {
+        return Ok(());
+    }
+    let history = History::open()?;
+    for (variation, palette) in palettes.iter().enumerate() {
+        options.variation = variation;
+        history.record(&NewEntry {
+            mood,
+            options: &options,
+            palette,
+            model: generator.model_name(),
+            catalog_hash: generator.catalog_hash(),
+        })?;
+    }
+    Ok(())
+}
+
+fn parse_id(matches: &clap::ArgMatches) -> anyhow::Result<i64> {
+    let id = matches.get_one::<String>("id").unwrap();
+    id.trim_start_matches('#')
+        .parse()
+        .map_err(|_| anyhow::anyhow!("'{}' is not a history id", id))
+}
+
+fn print_history_line(entry: &history::Entry) {
     println!(
-        "\n{}",
-        "🎨 Interactive Mode - Enter mood descriptions (Ctrl+C to exit)"
-            .bold()
-            .green()
+        "{:>5} {} {} {} {}{}",
+        format!("#{}", entry.id).bold(),
+        if entry.favorite {
+            "★".yellow()
+        } else {
+            " ".normal()
+        },
+        entry.created.bright_black(),
+        PaletteDisplay::swatches(&entry.palette),
+        entry.mood.italic(),
+        if entry.tags.is_empty() {
+            String::new()
+        } else {
+            format!(" [{}]", entry.tags.join(", ")).cyan().to_string()
+        }
     );
-    PaletteDisplay::display_examples();
-
-    loop {
-        print!("\n🎭 Enter mood: ");
-        io::stdout().flush().unwrap();
-
-        let mut input = String::new();
-        match io::stdin().read_line(&mut input) {
-            Ok(_) => {
-                let mood = input.trim();
-                if mood.is_empty() {
-                    println!("{}", "Please enter a mood description.".yellow());
-                    continue;
-                }
+}
 
-                if mood.to_lowercase() == "quit" || mood.to_lowercase() == "exit" {
-                    println!("{}", "Goodbye! 🌈".cyan());
-                    break;
+fn print_history(entries: &[history::Entry], empty: &str) {
+    if entries.is_empty() {
+        println!("{}", empty.yellow());
+    }
+    for entry in entries {
+        print_history_line(entry);
+    }
+}
+
+fn run_history_command(matches: &clap::ArgMatches, display: &DisplayOptions) -> anyhow::Result<()> {
+    let history = History::open()?;
+    match matches.subcommand() {
+        Some(("show", show_matches)) => {
+            let id = parse_id(show_matches)?;
+            let Some(entry) = history.get(id)? else {
+                anyhow::bail!("no history entry #{}", id);
+            };
+            print_history_line(&entry);
+            PaletteDisplay::show(&entry.palette, display);
+            print_share_code(&entry.mood, &entry.palette);
+            let config = &entry.options.config;
+            println!(
+                "{} model {}, seed {}, {} colors, diversity {}, catalog {}",
+                "⚙️  Options:".bright_blue(),
+                entry.model,
+                entry
+                    .seed
+                    .map(|seed| seed.to_string())
+                    .unwrap_or_else(|| "none".to_string()),
+                config.top_k,
+                config.diversity,
+                entry
+                    .options
+                    .catalog
+                    .as_ref()
+                    .map(|path| path.display().to_string())
+                    .unwrap_or_else(|| "built-in".to_string())
+            );
+            if !entry.options.keep.is_empty() {
+                println!(
+                    "{} {}",
+                    "🔒 Kept:".bright_blue(),
+                    entry.options.keep.join(", ")
+                );
+            }
+            if entry.options.variations > 1 {
+                println!(
+                    "{} {} of {}",
+                    "🎲 Variation:".bright_blue(),
+                    entry.options.variation + 1,
+                    entry.options.variations
+                );
+            }
+        }
+        Some(("search", search_matches)) => {
+            let text = search_matches.get_one::<String>("text").unwrap();
+            print_history(
+                &history.search(text, 50)?,
+                &format!("Nothing in the history matches '{}'.", text),
+            );
+        }
+        Some(("export", export_matches)) => {
+            let path = Path::new(export_matches.get_one::<String>("path").unwrap());
+            match export_matches.get_one::<String>("id") {
+                Some(_) => {
+                    let id = parse_id(export_matches)?;
+                    let Some(entry) = history.get(id)? else {
+                        anyhow::bail!("no history entry #{}", id);
+                    };
+                    export::export_palette(path, &entry.palette)?;
+                }
+                None => {
+                    let entries = history.all(false)?;
+                    std::fs::write(path, serde_json::to_string_pretty(&entries)?)?;
                 }
+            }
+            println!("{} {}", "💾 Exported to".green(), path.display());
+        }
+        Some(("tag", tag_matches)) => {
+            let id = parse_id(tag_matches)?;
+            let tags: Vec<String> = tag_matches
+                .get_many::<String>("tags")
+                .unwrap_or_default()
+                .cloned()
+                .collect();
+            if !history.add_tags(id, &tags)? {
+                anyhow::bail!("no history entry #{}", id);
+            }
+            println!("{} #{} with {}", "🏷️  Tagged".green(), id, tags.join(", "));
+        }
+        _ => {
+            let limit: usize = matches
+                .subcommand_matches("list")
+                .and_then(|list| list.get_one::<String>("limit"))
+                .and_then(|limit| limit.parse().ok())
+                .unwrap_or(20);
+            print_history(
+                &history.list(limit, false)?,
+                "No palettes yet. Generate one with: colorify \"cozy winter cabin\"",
+            );
+        }
+    }
+    Ok(())
+}
 
-                generate_and_display(generator, mood, show_bar, no_names).await;
+fn run_fav_command(matches: &clap::ArgMatches, display: &DisplayOptions) -> anyhow::Result<()> {
+    let history = History::open()?;
+    match matches.subcommand() {
+        Some((action @ ("add" | "rm"), fav_matches)) => {
+            let id = parse_id(fav_matches)?;
+            if !history.set_favorite(id, action == "add")? {
+                anyhow::bail!("no history entry #{}", id);
             }
-            Err(_) => {
-                println!("\n{}", "Goodbye! 🌈".cyan());
-                break;
+            if action == "add" {
+                println!("{} #{}", "★ Favorited".yellow(), id);
+            } else {
+                println!("{} #{}", "☆ Unfavorited".cyan(), id);
+            }
+        }
+        _ => {
+            let favorites = history.all(true)?;
+            print_history(
+                &favorites,
+                "No favorites yet. Add one with: colorify fav add ID",
+            );
+            if display.verbose {
+                for entry in &favorites {
+                    PaletteDisplay::show(&entry.palette, display);
+                }
             }
         }
     }
+    Ok(())
+}
+
+/// Regenerates a history entry from its saved options, warning about
+/// anything that could make the result differ from the original.
+async fn rerun_history(matches: &clap::ArgMatches, display: &DisplayOptions) -> anyhow::Result<()> {
+    let id = parse_id(matches)?;
+    let Some(entry) = History::open()?.get(id)? else {
+        anyhow::bail!("no history entry #{}", id);
+    };
+
+    let mut config = entry.options.config.clone();
+    if let Some(seed) = matches.get_one::<String>("seed") {
+        let seed: u64 = seed
+            .parse()
+            .map_err(|_| anyhow::anyhow!("invalid seed '{}'", seed))?;
+        if entry.seed != Some(seed) {
+            println!(
+                "{}",
+                "⚠️  Using a different seed, so sampled colors may change.".yellow()
+            );
+        }
+        config = config.with_seed(seed);
+    }
+
+    let colors = load_colors(entry.options.catalog.as_deref())?;
+    let mut generator = MoodPaletteGenerator::new(config, colors).await?;
+    if generator.model_name() != entry.model {
+        println!(
+            "{}",
+            format!(
+                "⚠️  Saved with model {}, now using {}; scores will differ.",
+                entry.model,
+                generator.model_name()
+            )
+            .yellow()
+        );
+    }
+    if generator.catalog_hash() != entry.catalog_hash {
+        println!(
+            "{}",
+            "⚠️  The color catalog has changed since this palette was saved.".yellow()
+        );
+    }
+    if generator.config().use_feedback && generator.feedback_count() != entry.options.feedback_count
+    {
+        println!(
+            "{}",
+            "⚠️  Feedback has changed since this palette was saved; try --no-feedback.".yellow()
+        );
+    }
+
+    let mut locked = Vec::new();
+    for spec in &entry.options.keep {
+        match generator.resolve_color(spec) {
+            Some(color) => locked.push(color),
+            None => anyhow::bail!("kept color '{}' is no longer in the catalog", spec),
+        }
+    }
+
+    let palette = if entry.options.variations > 1 {
+        generator
+            .generate_variations(&entry.mood, &locked, entry.options.variations)
+            .await?
+            .into_iter()
+            .nth(entry.options.variation)
+            .unwrap_or_default()
+    } else {
+        generator.generate_palette(&entry.mood, &locked).await?
+    };
+
+    println!(
+        "\n{} {} {}",
+        format!("🔁 Re-running #{}:", entry.id).bright_blue(),
+        entry.mood.italic().white(),
+        entry.created.bright_black()
+    );
+    PaletteDisplay::show(&palette, display);
+
+    let hexes = |palette: &[ColorMatch]| -> Vec<String> {
+        palette.iter().map(|m| m.color.hex.to_lowercase()).collect()
+    };
+    if hexes(&palette) == hexes(&entry.palette) {
+        println!("{}", "✅ Reproduced the saved palette exactly.".green());
+    } else {
+        println!(
+            "{} {}",
+            "⚠️  This differs from the saved palette:".yellow(),
+            PaletteDisplay::swatches(&entry.palette)
+        );
+    }
+    Ok(())
 }
 
 async fn generate_and_display(
     generator: &mut MoodPaletteGenerator,
     mood: &str,
-    show_bar: bool,
-    no_names: bool,
-) {
+    locked: &[Color],
+    variations: usize,
+    display: &DisplayOptions,
+) -> Vec<Vec<ColorMatch>> {
     println!(
         "\n{} {}",
         "🔍 Analyzing mood:".bright_blue(),
         mood.italic().white()
     );
 
-    let palette = match generator.generate_palette(mood).await {
+    if generator.config().pin_mentions {
+        let parsed = generator.parse_mood(mood);
+        if !parsed.mentions.is_empty() {
+            let pinned: Vec<String> = parsed
+                .mentions
+                .iter()
+                .map(|mention| format!("{} → {}", mention.token, mention.color.name))
+                .collect();
+            println!(
+                "{} {}",
+                "📌 Pinned from mood:".bright_blue(),
+                pinned.join(", ")
+            );
+        }
+    }
+
+    if display.verbose {
+        for expansion in generator.expansions(mood) {
+            println!(
+                "{} {} → {}",
+                "🧩 Expanded:".bright_blue(),
+                expansion.term,
+                expansion.query()
+            );
+        }
+    }
+
+    if variations > 1 {
+        return display_variations(generator, mood, locked, variations, display).await;
+    }
+
+    let palette = match generator.generate_palette(mood, locked).await {
         Ok(palette) => palette,
         Err(e) => {
             println!("❌ Error generating palette: {}", e);
-            return;
+            return vec![];
         }
     };
 
@@ -178,15 +1481,144 @@ async fn generate_and_display(
             "{}",
             "No matching colors found. Try a different mood description.".red()
         );
-        return;
+        report_constraints(generator);
+        suggest_moods(generator, mood).await;
+        return vec![];
     }
 
-    if show_bar {
-        PaletteDisplay::display_color_bar(&palette, no_names);
-    } else {
+    if !display.show_bar {
         // Clear the terminal before displaying the new palette
         print!("{esc}[2J{esc}[1;1H", esc = 27 as char);
+    }
+    PaletteDisplay::show(&palette, display);
+    if display.share {
+        print_share_code(mood, &palette);
+    }
+    report_constraints(generator);
+
+    let Some(confidence) = palette_confidence(&palette) else {
+        return vec![palette];
+    };
+    println!(
+        "\n{} {} (z {:.1})",
+        "📈 Match confidence:".bright_blue(),
+        confidence_label(confidence),
+        confidence
+    );
+    if confidence < VAGUE_CONFIDENCE {
+        println!(
+            "{}",
+            "🤔 This mood is vague, so these colors are only loosely related.".yellow()
+        );
+        suggest_moods(generator, mood).await;
+    }
+    vec![palette]
+}
 
-        PaletteDisplay::display_palette(&palette);
+fn parse_constraints(matches: &clap::ArgMatches) -> anyhow::Result<Constraints> {
+    let arg = |id: &str| matches.get_one::<String>(id);
+    let mut constraints = Constraints {
+        soft: matches.get_flag("soft_constraints"),
+        ..Constraints::default()
+    };
+    if let Some(band) = arg("lightness") {
+        constraints.lightness =
+            Some(Constraints::parse_lightness(band).context("invalid --lightness")?);
+    }
+    if let Some(band) = arg("chroma") {
+        constraints.chroma = Some(Constraints::parse_chroma(band).context("invalid --chroma")?);
+    }
+    if let Some(range) = arg("hue") {
+        constraints.hue = Some(Constraints::parse_hue(range).context("invalid --hue")?);
+    }
+    if let Some(tone) = arg("tone") {
+        constraints.tone = Some(Tone::parse(tone)?);
+    }
+    if let Some(delta) = arg("min_delta_e") {
+        constraints.min_delta_e = delta
+            .parse::<f32>()
+            .with_context(|| format!("invalid --min-delta-e '{}'", delta))?
+            .max(0.0);
+    }
+    Ok(constraints)
+}
+
+/// Warns when constraints left too little to choose from or spacing cut the
+/// palette short.
+fn report_constraints(generator: &MoodPaletteGenerator) {
+    let config = generator.config();
+    let report = generator.constraint_report();
+    for warning in config.constraints.warnings(config.top_k, report) {
+        println!("{}", format!("⚠️  {}", warning).yellow());
+    }
+    if let Some(report) =
+        report.filter(|report| report.soft && report.satisfied < report.candidates)
+    {
+        println!(
+            "{} {} of {} candidates fit the constraints; the rest were marked down.",
+            "🎚️".bright_blue(),
+            report.satisfied,
+            report.candidates
+        );
+    }
+}
+
+async fn suggest_moods(generator: &mut MoodPaletteGenerator, mood: &str) {
+    if let Ok(related) = generator.closest_moods(mood, EXAMPLE_MOODS, 3).await {
+        println!("💡 Try something more concrete, like:");
+        for example in related {
+            println!("   • {}", example.italic());
+        }
+    }
+}
+
+async fn display_variations(
+    generator: &mut MoodPaletteGenerator,
+    mood: &str,
+    locked: &[Color],
+    count: usize,
+    display: &DisplayOptions,
+) -> Vec<Vec<ColorMatch>> {
+    let variations = match generator.generate_variations(mood, locked, count).await {
+        Ok(variations) => variations,
+        Err(e) => {
+            println!("❌ Error generating palette: {}", e);
+            return vec![];
+        }
+    };
+
+    if variations.is_empty() {
+        println!(
+            "{}",
+            "No matching colors found. Try a different mood description.".red()
+        );
+        return vec![];
+    }
+
+    for (i, palette) in variations.iter().enumerate() {
+        println!(
+            "\n{}",
+            format!("🎲 Variation {}/{}", i + 1, variations.len())
+                .bold()
+                .magenta()
+        );
+        PaletteDisplay::show(palette, display);
+        if display.share {
+            print_share_code(mood, palette);
+        }
+    }
+
+    report_constraints(generator);
+
+    if variations.len() < count {
+        println!(
+            "{}",
+            format!(
+                "Only found {} distinct palettes; try a higher --temperature or fewer colors.",
+                variations.len()
+            )
+            .yellow()
+        );
     }
+    variations
 }
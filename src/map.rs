@@ -0,0 +1,329 @@
+use crate::colors::Color;
+use crate::display::EXAMPLE_MOODS;
+use crate::index::{dot, normalize};
+use crate::matcher::MoodPaletteGenerator;
+use anyhow::{Context, Result};
+use colored::*;
+use std::fmt::Write;
+use std::fs;
+use std::path::Path;
+
+/// Side of each square panel, in SVG units.
+const PANEL: f32 = 480.0;
+const MARGIN: f32 = 40.0;
+/// Chroma at the rim of the wheel and the right edge of the L/C plane;
+/// sRGB tops out a little above 0.32.
+const MAX_CHROMA: f32 = 0.33;
+const DOT_RADIUS: f32 = 4.0;
+const POWER_ITERATIONS: usize = 100;
+
+/// A color placed on every panel.
+struct Point<'a> {
+    color: &'a Color,
+    /// Hue in degrees, chroma and lightness
+    lch: (f32, f32, f32),
+    /// Position along the first two principal components
+    projected: (f32, f32),
+}
+
+/// Writes an SVG with three views of the catalog: an OKLCH hue/chroma
+/// wheel, a lightness/chroma plane, and a PCA projection of the embeddings
+/// with mood labels placed among the colors they are closest to.
+pub async fn run_map(
+    generator: &mut MoodPaletteGenerator,
+    output: &Path,
+    moods: Option<&str>,
+) -> Result<()> {
+    let moods: Vec<String> = match moods {
+        Some(path) => fs::read_to_string(path)
+            .with_context(|| format!("failed to read {}", path))?
+            .lines()
+            .map(str::trim)
+            .filter(|line| !line.is_empty())
+            .map(str::to_string)
+            .collect(),
+        None => EXAMPLE_MOODS.iter().map(|mood| mood.to_string()).collect(),
+    };
+
+    let embeddings = generator.color_embeddings();
+    let mood_vecs: Vec<Vec<f32>> = generator
+        .embed_texts(&moods)
+        .await?
+        .iter()
+        .map(|vector| normalize(vector))
+        .collect();
+
+    let (mean, axes) = principal_axes(&embeddings);
+    let project = |vector: &[f32]| -> (f32, f32) {
+        let centered: Vec<f32> = vector.iter().zip(&mean).map(|(v, m)| v - m).collect();
+        (dot(&centered, &axes[0]), dot(&centered, &axes[1]))
+    };
+
+    let points: Vec<Point> = generator
+        .colors()
+        .iter()
+        .zip(&embeddings)
+        .filter_map(|(color, embedding)| {
+            let lch = color.to_oklch()?;
+            Some(Point {
+                color,
+                lch: (lch.h, lch.c, lch.l),
+                projected: project(embedding),
+            })
+        })
+        .collect();
+    let labels: Vec<(&str, (f32, f32))> = moods
+        .iter()
+        .zip(&mood_vecs)
+        .map(|(mood, vector)| (mood.as_str(), project(vector)))
+        .collect();
+
+    let svg = render(&points, &labels);
+    fs::write(output, svg).with_context(|| format!("failed to write {}", output.display()))?;
+    println!(
+        "{} {} colors and {} moods to {}",
+        "🗺️  Mapped".green(),
+        points.len(),
+        labels.len(),
+        output.display()
+    );
+    Ok(())
+}
+
+/// The mean vector and the top two principal directions, found by power
+/// iteration on the centered rows without forming the covariance matrix.
+fn principal_axes(rows: &[Vec<f32>]) -> (Vec<f32>, [Vec<f32>; 2]) {
+    let dims = rows.first().map(Vec::len).unwrap_or(0);
+    let mut mean = vec![0.0; dims];
+    for row in rows {
+        for (m, v) in mean.iter_mut().zip(row) {
+            *m += v / rows.len() as f32;
+        }
+    }
+    let centered: Vec<Vec<f32>> = rows
+        .iter()
+        .map(|row| row.iter().zip(&mean).map(|(v, m)| v - m).collect())
+        .collect();
+
+    let mut axes: Vec<Vec<f32>> = Vec::with_capacity(2);
+    for component in 0..2 {
+        // Any fixed start that isn't orthogonal to the answer will do
+        let mut axis = normalize(
+            &(0..dims)
+                .map(|i| ((i * (component + 3)) % 7) as f32 - 3.0)
+                .collect::<Vec<_>>(),
+        );
+        for _ in 0..POWER_ITERATIONS {
+            let mut next = vec![0.0; dims];
+            for row in &centered {
+                let weight = dot(row, &axis);
+                for (n, v) in next.iter_mut().zip(row) {
+                    *n += weight * v;
+                }
+            }
+            // Deflate: stay orthogonal to the axes already found
+            for found in &axes {
+                let overlap = dot(&next, found);
+                for (n, f) in next.iter_mut().zip(found) {
+                    *n -= overlap * f;
+                }
+            }
+            axis = normalize(&next);
+        }
+        axes.push(axis);
+    }
+    let second = axes.pop().unwrap_or_default();
+    let first = axes.pop().unwrap_or_default();
+    (mean, [first, second])
+}
+
+fn render(points: &[Point], labels: &[(&str, (f32, f32))]) -> String {
+    let width = 3.0 * PANEL + 4.0 * MARGIN;
+    let height = PANEL + 2.0 * MARGIN + 20.0;
+    let mut svg = String::new();
+    let _ = writeln!(
+        svg,
+        r#"<svg xmlns="http://www.w3.org/2000/svg" width="{w}" height="{h}" viewBox="0 0 {w} {h}" font-family="sans-serif" font-size="11">"#,
+        w = width,
+        h = height
+    );
+    let _ = writeln!(
+        svg,
+        r##"<rect width="100%" height="100%" fill="#1e1e1e"/>"##
+    );
+
+    let top = MARGIN + 20.0;
+    hue_wheel(&mut svg, points, MARGIN, top);
+    lightness_plane(&mut svg, points, 2.0 * MARGIN + PANEL, top);
+    embedding_plot(&mut svg, points, labels, 3.0 * MARGIN + 2.0 * PANEL, top);
+
+    svg.push_str("</svg>\n");
+    svg
+}
+
+fn title(svg: &mut String, x: f32, y: f32, text: &str) {
+    let _ = writeln!(
+        svg,
+        r##"<text x="{}" y="{}" fill="#eeeeee" font-size="14">{}</text>"##,
+        x,
+        y - 12.0,
+        text
+    );
+}
+
+fn dot_at(svg: &mut String, x: f32, y: f32, color: &Color) {
+    let _ = writeln!(
+        svg,
+        r##"<circle cx="{:.1}" cy="{:.1}" r="{}" fill="{}" stroke="#000000" stroke-opacity="0.4"><title>{} {}</title></circle>"##,
+        x,
+        y,
+        DOT_RADIUS,
+        color.hex,
+        escape(&color.name),
+        color.hex
+    );
+}
+
+/// Hue as angle, chroma as distance from the center.
+fn hue_wheel(svg: &mut String, points: &[Point], left: f32, top: f32) {
+    title(svg, left, top, "Hue × chroma (OKLCH)");
+    let radius = PANEL / 2.0;
+    let (cx, cy) = (left + radius, top + radius);
+    for ring in [0.1, 0.2, 0.3] {
+        let _ = writeln!(
+            svg,
+            r##"<circle cx="{}" cy="{}" r="{:.1}" fill="none" stroke="#444444"/>"##,
+            cx,
+            cy,
+            ring / MAX_CHROMA * radius
+        );
+    }
+    for degrees in (0..360).step_by(30) {
+        let angle = (degrees as f32).to_radians();
+        let (x, y) = (cx + radius * angle.cos(), cy - radius * angle.sin());
+        let _ = writeln!(
+            svg,
+            r##"<line x1="{}" y1="{}" x2="{:.1}" y2="{:.1}" stroke="#333333"/><text x="{:.1}" y="{:.1}" fill="#888888" text-anchor="middle">{}°</text>"##,
+            cx,
+            cy,
+            x,
+            y,
+            cx + (radius + 14.0) * angle.cos(),
+            cy - (radius + 14.0) * angle.sin() + 4.0,
+            degrees
+        );
+    }
+    for point in points {
+        let (h, c, _) = point.lch;
+        let angle = h.to_radians();
+        let r = c.min(MAX_CHROMA) / MAX_CHROMA * radius;
+        dot_at(svg, cx + r * angle.cos(), cy - r * angle.sin(), point.color);
+    }
+}
+
+/// Chroma across, lightness up.
+fn lightness_plane(svg: &mut String, points: &[Point], left: f32, top: f32) {
+    title(svg, left, top, "Lightness × chroma");
+    let _ = writeln!(
+        svg,
+        r##"<rect x="{}" y="{}" width="{}" height="{}" fill="none" stroke="#444444"/>"##,
+        left, top, PANEL, PANEL
+    );
+    for step in 1..4 {
+        let l = step as f32 * 0.25;
+        let y = top + (1.0 - l) * PANEL;
+        let _ = writeln!(
+            svg,
+            r##"<line x1="{}" y1="{:.1}" x2="{}" y2="{:.1}" stroke="#333333"/><text x="{}" y="{:.1}" fill="#888888" text-anchor="end">L {:.2}</text>"##,
+            left,
+            y,
+            left + PANEL,
+            y,
+            left - 4.0,
+            y + 4.0,
+            l
+        );
+    }
+    let _ = writeln!(
+        svg,
+        r##"<text x="{}" y="{}" fill="#888888" text-anchor="end">chroma {} →</text>"##,
+        left + PANEL,
+        top + PANEL + 16.0,
+        MAX_CHROMA
+    );
+    for point in points {
+        let (_, c, l) = point.lch;
+        dot_at(
+            svg,
+            left + c.min(MAX_CHROMA) / MAX_CHROMA * PANEL,
+            top + (1.0 - l.clamp(0.0, 1.0)) * PANEL,
+            point.color,
+        );
+    }
+}
+
+/// Colors at their projected embeddings, with moods labelled in the same
+/// space; a label far from any dot is a mood the catalog covers poorly.
+fn embedding_plot(
+    svg: &mut String,
+    points: &[Point],
+    labels: &[(&str, (f32, f32))],
+    left: f32,
+    top: f32,
+) {
+    title(svg, left, top, "Embedding space (PCA)");
+    let _ = writeln!(
+        svg,
+        r##"<rect x="{}" y="{}" width="{}" height="{}" fill="none" stroke="#444444"/>"##,
+        left, top, PANEL, PANEL
+    );
+
+    let all = points
+        .iter()
+        .map(|point| point.projected)
+        .chain(labels.iter().map(|(_, position)| *position));
+    let (mut min_x, mut max_x, mut min_y, mut max_y) = (f32::MAX, f32::MIN, f32::MAX, f32::MIN);
+    for (x, y) in all {
+        min_x = min_x.min(x);
+        max_x = max_x.max(x);
+        min_y = min_y.min(y);
+        max_y = max_y.max(y);
+    }
+    let inset = 12.0;
+    let scale = |value: f32, min: f32, max: f32| {
+        inset + (value - min) / (max - min).max(f32::EPSILON) * (PANEL - 2.0 * inset)
+    };
+    let place = |(x, y): (f32, f32)| {
+        (
+            left + scale(x, min_x, max_x),
+            top + PANEL - scale(y, min_y, max_y),
+        )
+    };
+
+    for point in points {
+        let (x, y) = place(point.projected);
+        dot_at(svg, x, y, point.color);
+    }
+    for (mood, position) in labels {
+        let (x, y) = place(*position);
+        let _ = writeln!(
+            svg,
+            r##"<path d="M{x0:.1} {y:.1}H{x1:.1}M{x:.1} {y0:.1}V{y1:.1}" stroke="#ffffff"/><text x="{tx:.1}" y="{ty:.1}" fill="#ffffff">{}</text>"##,
+            escape(mood),
+            x0 = x - 5.0,
+            x1 = x + 5.0,
+            y0 = y - 5.0,
+            y1 = y + 5.0,
+            x = x,
+            y = y,
+            tx = x + 7.0,
+            ty = y - 4.0
+        );
+    }
+}
+
+fn escape(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+}
@@ -1,80 +1,1426 @@
+use crate::cache::{fingerprint, EmbeddingCache};
+use crate::color_space::{hue_harmony, parse_hex, Oklab};
 use crate::colors::Color;
-use crate::config::Config;
+use crate::config::{Config, EmbedderKind, Fusion, IndexKind, Pooling};
+use crate::constraints::ConstraintReport;
 use crate::embedding::EmbeddingGenerator;
+use crate::expansion::{Expansion, Thesaurus, EXPANSION_WEIGHT};
+use crate::explain::{Ablation, Explanation};
+use crate::feedback::FeedbackStore;
+use crate::index::{
+    dot, normalize, BruteForceIndex, EmbeddingMatrix, HnswIndex, HnswParams, VectorIndex,
+};
+use crate::lexical::{tokenize, LexicalIndex};
+use crate::query::{parse_query, ParsedQuery};
+use crate::tags::TagFilter;
 use anyhow::Result;
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ColorMatch {
+    #[serde(flatten)]
     pub color: Color,
     pub score: f32,
+    /// Embedding similarity between the mood and the color
+    pub semantic: f32,
+    /// BM25 match of the mood's words against the color's name and description
+    pub lexical: f32,
+    /// Standard deviations the semantic score sits above a typical catalog
+    /// color for the same mood
+    pub confidence: f32,
+    /// Score shift learned from thumbs up/down on this color
+    #[serde(default, skip_serializing_if = "is_zero")]
+    pub feedback: f32,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub explanation: Option<Explanation>,
+    /// Kept by the caller rather than chosen for the mood
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub locked: bool,
+}
+
+impl ColorMatch {
+    /// A color that wasn't scored against any mood.
+    pub fn unscored(color: Color) -> Self {
+        Self {
+            color,
+            score: 0.0,
+            semantic: 0.0,
+            lexical: 0.0,
+            confidence: 0.0,
+            feedback: 0.0,
+            explanation: None,
+            locked: false,
+        }
+    }
+}
+
+/// Sampling temperature used for `--variations` when none is configured.
+const DEFAULT_VARIATION_TEMPERATURE: f32 = 0.05;
+/// How many samples per requested variation before giving up on finding
+/// palettes distinct enough from the ones already accepted.
+const MAX_VARIATION_ATTEMPTS: usize = 20;
+/// Catalogs at least this large get an HNSW index under `IndexKind::Auto`.
+const HNSW_THRESHOLD: usize = 5000;
+/// Best lexical hits always considered, even when the semantic index
+/// doesn't return them.
+const LEXICAL_POOL: usize = 50;
+/// Catalog rows sampled to estimate how a mood scores against colors in
+/// general, the baseline for confidence.
+const BASELINE_SAMPLE: usize = 256;
+/// Palettes whose mean confidence falls below this are reported as vague.
+pub const VAGUE_CONFIDENCE: f32 = 3.0;
+/// Locked colors closer than this (ΔEok) crowd out candidates entirely.
+const MIN_LOCKED_DISTANCE: f32 = 0.08;
+/// How much a clash with the locked colors can cost a candidate's score.
+const LOCK_PENALTY: f32 = 0.15;
+
+/// The catalog's semantic and lexical indexes, shared by scoring threads.
+struct SearchIndex {
+    /// One row per color and template, template-major
+    vectors: Box<dyn VectorIndex>,
+    lexical: LexicalIndex,
+    baseline: Vec<usize>,
+    colors: usize,
+    templates: usize,
+}
+
+impl SearchIndex {
+    /// Up to `k` colors as `(color, similarity)`, best first, each color
+    /// scored by its best-matching template.
+    fn search(&self, query: &[f32], k: usize) -> Result<Vec<(usize, f32)>> {
+        if self.templates == 1 {
+            return self.vectors.search(query, k);
+        }
+
+        let mut seen = HashSet::new();
+        let mut hits: Vec<(usize, f32)> = self
+            .vectors
+            .search(query, k * self.templates)?
+            .into_iter()
+            .map(|(row, score)| (row % self.colors, score))
+            .filter(|(color, _)| seen.insert(*color))
+            .collect();
+        hits.truncate(k);
+        Ok(hits)
+    }
+
+    fn similarity(&self, color: usize, query: &[f32]) -> Result<f32> {
+        let mut best = f32::NEG_INFINITY;
+        for template in 0..self.templates {
+            best = best.max(
+                self.vectors
+                    .similarity(template * self.colors + color, query)?,
+            );
+        }
+        Ok(best)
+    }
+
+    /// A color's embedding, averaged over its templates.
+    fn color_vector(&self, color: usize) -> Vec<f32> {
+        let matrix = self.vectors.vectors();
+        let mut sum = matrix.row(color).to_vec();
+        for template in 1..self.templates {
+            let row = matrix.row(template * self.colors + color);
+            for (total, value) in sum.iter_mut().zip(row) {
+                *total += value;
+            }
+        }
+        normalize(&sum)
+    }
+}
+
+/// A mood's ranking together with the colors named in it, which every front
+/// end keeps locked.
+pub struct MoodRanking {
+    pub ranking: Vec<ColorMatch>,
+    pub pinned: Vec<Color>,
 }
 
 pub struct MoodPaletteGenerator {
     colors: Vec<Color>,
     config: Config,
     embedder: EmbeddingGenerator,
-    color_embeddings: Vec<Vec<f32>>,
+    search: SearchIndex,
+    catalog_key: String,
+    catalog_hash: String,
+    thesaurus: Option<Thesaurus>,
+    feedback: FeedbackStore,
+    rng: StdRng,
+    /// What the constraints did to the latest mood's ranking
+    constraint_report: Option<ConstraintReport>,
 }
 
 impl MoodPaletteGenerator {
     pub async fn new(config: Config, colors: Vec<Color>) -> Result<Self> {
-        let mut embedder = EmbeddingGenerator::new().await?;
+        let mut embedder = match config.embedder {
+            EmbedderKind::Model => EmbeddingGenerator::new().await?,
+            EmbedderKind::Hashing => EmbeddingGenerator::hashing(),
+        };
 
-        println!("🔄 Pre-computing color embeddings...");
-        let color_texts: Vec<String> = colors
+        let color_texts: Vec<String> = config
+            .templates
             .iter()
-            .map(|color| format!("{}, {}", color.name, color.description))
+            .flat_map(|template| {
+                colors
+                    .iter()
+                    .map(move |color| render_template(template, color))
+            })
             .collect();
 
-        let color_embeddings = embedder.generate_embeddings(&color_texts).await?;
-        println!(
-            "✅ Pre-computed {} color embeddings",
-            color_embeddings.len()
+        let cache = EmbeddingCache::new();
+        let catalog_key = fingerprint(
+            std::iter::once(embedder.model_name()).chain(color_texts.iter().map(String::as_str)),
+        );
+
+        let color_embeddings = match cache.load_embeddings(&catalog_key) {
+            Some(cached) if cached.len() == color_texts.len() => {
+                eprintln!("✅ Loaded {} cached color embeddings", cached.len());
+                cached
+            }
+            _ => {
+                eprintln!("🔄 Pre-computing color embeddings...");
+                let computed = embedder.generate_embeddings(&color_texts).await?;
+                eprintln!("✅ Pre-computed {} color embeddings", computed.len());
+                if let Err(e) = cache.save_embeddings(&catalog_key, &computed) {
+                    eprintln!("⚠️  Could not cache color embeddings: {}", e);
+                }
+                computed
+            }
+        };
+
+        // Mean pooling folds the templates into one row per color up front
+        let templates = config.templates.len();
+        let (rows, templates) = if config.pooling == Pooling::Mean && templates > 1 {
+            (mean_pool(&color_embeddings, colors.len()), 1)
+        } else {
+            (color_embeddings, templates)
+        };
+        let index_key = fingerprint([catalog_key.as_str(), config.pooling.name()]);
+
+        let search = SearchIndex {
+            vectors: build_index(config.index, &cache, &index_key, &rows)?,
+            lexical: LexicalIndex::new(&colors),
+            baseline: (0..colors.len())
+                .step_by((colors.len() / BASELINE_SAMPLE).max(1))
+                .collect(),
+            colors: colors.len(),
+            templates,
+        };
+
+        let thesaurus = if config.expand {
+            Some(Thesaurus::load(config.thesaurus.as_deref())?)
+        } else {
+            None
+        };
+
+        let catalog_hash = fingerprint(
+            colors
+                .iter()
+                .flat_map(|c| [c.name.as_str(), c.hex.as_str(), c.description.as_str()]),
         );
 
+        let feedback = if config.use_feedback {
+            FeedbackStore::load().unwrap_or_else(|e| {
+                eprintln!("⚠️  Ignoring saved feedback: {:#}", e);
+                FeedbackStore::default()
+            })
+        } else {
+            FeedbackStore::default()
+        };
+
+        let rng = match config.seed {
+            Some(seed) => StdRng::seed_from_u64(seed),
+            None => StdRng::from_os_rng(),
+        };
+
         Ok(Self {
             colors,
             config,
             embedder,
-            color_embeddings,
+            search,
+            catalog_key,
+            catalog_hash,
+            thesaurus,
+            feedback,
+            rng,
+            constraint_report: None,
+        })
+    }
+
+    pub fn config(&self) -> &Config {
+        &self.config
+    }
+
+    pub fn colors(&self) -> &[Color] {
+        &self.colors
+    }
+
+    /// Identifies the loaded model and catalog text; cached artifacts are
+    /// stored under this key.
+    pub fn catalog_key(&self) -> &str {
+        &self.catalog_key
+    }
+
+    /// Fingerprint of the catalog's names, hex values and descriptions.
+    pub fn catalog_hash(&self) -> &str {
+        &self.catalog_hash
+    }
+
+    pub fn model_name(&self) -> &'static str {
+        self.embedder.model_name()
+    }
+
+    /// How many candidates the constraints kept for the last ranked mood;
+    /// `None` when no constraint limits colors.
+    pub fn constraint_report(&self) -> Option<ConstraintReport> {
+        self.constraint_report
+    }
+
+    /// Number of thumbs up/down currently shaping the scores.
+    pub fn feedback_count(&self) -> usize {
+        self.feedback.entries().len()
+    }
+
+    /// The `n` candidates whose embeddings are closest to the mood's.
+    pub async fn closest_moods<'a>(
+        &mut self,
+        mood: &str,
+        candidates: &[&'a str],
+        n: usize,
+    ) -> Result<Vec<&'a str>> {
+        let texts: Vec<String> = std::iter::once(mood)
+            .chain(candidates.iter().copied())
+            .map(str::to_string)
+            .collect();
+        let embeddings = self.embedder.generate_embeddings(&texts).await?;
+        let mood_vec = normalize(&embeddings[0]);
+
+        let mut scored: Vec<(f32, &'a str)> = embeddings[1..]
+            .iter()
+            .zip(candidates)
+            .map(|(vec, candidate)| (dot(&mood_vec, &normalize(vec)), *candidate))
+            .collect();
+        scored.sort_by(|a, b| b.0.total_cmp(&a.0));
+        Ok(scored.into_iter().take(n).map(|(_, c)| c).collect())
+    }
+
+    /// One embedding per catalog color, in catalog order.
+    pub fn color_embeddings(&self) -> Vec<Vec<f32>> {
+        (0..self.colors.len())
+            .map(|color| self.search.color_vector(color))
+            .collect()
+    }
+
+    pub async fn embed_texts(&mut self, texts: &[String]) -> Result<Vec<Vec<f32>>> {
+        self.embedder.generate_embeddings(texts).await
+    }
+
+    /// Generates a palette that keeps `locked` (catalog colors or arbitrary
+    /// hex values) and fills the remaining slots with mood matches that fit
+    /// alongside them.
+    pub async fn generate_palette(
+        &mut self,
+        mood_text: &str,
+        locked: &[Color],
+    ) -> Result<Vec<ColorMatch>> {
+        let (mood_text, locked) = self.pin_mentions(mood_text, locked);
+        let ranking = self.rank_all(&mood_text).await?;
+        let (mut palette, spaced_out) =
+            self.build_palette(&ranking, &locked, self.config.temperature);
+        self.note_spacing(spaced_out);
+        if self.config.explain {
+            let ablation = self.ablate(&mood_text).await?;
+            self.explain(&ablation, &mood_text, &mut palette)?;
+        }
+        Ok(palette)
+    }
+
+    /// Embeds the mood once whole and once per left-out word, through the
+    /// same expansion as ranking so contributions are measured against the
+    /// query that chose the palette.
+    async fn ablate(&mut self, mood_text: &str) -> Result<Ablation> {
+        let words = Ablation::words(mood_text);
+        let mut texts = vec![mood_text.to_string()];
+        texts.extend(Ablation::variants(&words));
+
+        let mut embeddings = self.embed_moods(&texts).await?.into_iter();
+        let full = embeddings.next().unwrap_or_default();
+        Ok(Ablation {
+            words,
+            full,
+            without: embeddings.collect(),
+        })
+    }
+
+    /// Attaches an explanation to every palette color that comes from the
+    /// catalog; pinned hex values have no embedding to attribute.
+    fn explain(
+        &self,
+        ablation: &Ablation,
+        mood_text: &str,
+        palette: &mut [ColorMatch],
+    ) -> Result<()> {
+        if ablation.words.is_empty() {
+            return Ok(());
+        }
+
+        for color_match in palette.iter_mut() {
+            let Some(row) = self
+                .colors
+                .iter()
+                .position(|c| c.name == color_match.color.name && c.hex == color_match.color.hex)
+            else {
+                continue;
+            };
+
+            let full = self.search.similarity(row, &ablation.full)?;
+            let without = if ablation.without.is_empty() {
+                None
+            } else {
+                Some(
+                    ablation
+                        .without
+                        .iter()
+                        .map(|vec| self.search.similarity(row, vec))
+                        .collect::<Result<Vec<f32>>>()?,
+                )
+            };
+            color_match.explanation = Some(Explanation::new(
+                &ablation.words,
+                full,
+                without,
+                mood_text,
+                &color_match.color,
+            ));
+        }
+        Ok(())
+    }
+
+    /// Samples up to `n` palettes for one mood, each sharing at most half of
+    /// its unlocked colors with any other. Falls back to a default
+    /// temperature when sampling isn't already switched on.
+    pub async fn generate_variations(
+        &mut self,
+        mood_text: &str,
+        locked: &[Color],
+        n: usize,
+    ) -> Result<Vec<Vec<ColorMatch>>> {
+        let (mood_text, locked) = self.pin_mentions(mood_text, locked);
+        let locked = locked.as_slice();
+        let ranking = self.rank_all(&mood_text).await?;
+        if ranking.is_empty() {
+            return Ok(vec![]);
+        }
+
+        let temperature = Some(
+            self.config
+                .temperature
+                .unwrap_or(DEFAULT_VARIATION_TEMPERATURE),
+        );
+        let mut variations: Vec<Vec<ColorMatch>> = Vec::with_capacity(n);
+
+        for _ in 0..n * MAX_VARIATION_ATTEMPTS {
+            if variations.len() == n {
+                break;
+            }
+            let (palette, spaced_out) = self.build_palette(&ranking, locked, temperature);
+            self.note_spacing(spaced_out);
+            if variations
+                .iter()
+                .all(|other| palettes_differ(other, &palette))
+            {
+                variations.push(palette);
+            }
+        }
+
+        if self.config.explain && !variations.is_empty() {
+            let ablation = self.ablate(&mood_text).await?;
+            for palette in &mut variations {
+                self.explain(&ablation, &mood_text, palette)?;
+            }
+        }
+
+        Ok(variations)
+    }
+
+    /// Builds one palette from a ranking, and says whether `min_delta_e`
+    /// turned away candidates it needed: it only runs short while unused
+    /// candidates remain when spacing rejected them.
+    fn build_palette(
+        &mut self,
+        ranking: &[ColorMatch],
+        locked: &[Color],
+        temperature: Option<f32>,
+    ) -> (Vec<ColorMatch>, bool) {
+        if ranking.is_empty() {
+            return (vec![], false);
+        }
+
+        let kept: Vec<ColorMatch> = locked
+            .iter()
+            .take(self.config.top_k)
+            .map(|color| keep_color(ranking, color))
+            .collect();
+
+        let fitted = fit_to_locked(ranking, locked);
+        let count = self.config.top_k - kept.len();
+        let fill = match temperature {
+            Some(temperature) if temperature > 0.0 => sample_palette(
+                &fitted,
+                count,
+                self.config.diversity,
+                self.config.constraints.min_delta_e,
+                &kept,
+                temperature,
+                &mut self.rng,
+            ),
+            _ => select_palette(
+                &fitted,
+                count,
+                self.config.diversity,
+                self.config.constraints.min_delta_e,
+                &kept,
+            ),
+        };
+
+        let spaced_out = fill.len() < count
+            && fitted.iter().any(|candidate| {
+                !kept
+                    .iter()
+                    .chain(&fill)
+                    .any(|chosen| chosen.color.hex == candidate.color.hex)
+            });
+        let mut palette = kept;
+        palette.extend(fill);
+        (palette, spaced_out)
+    }
+
+    /// Records on the latest constraint report that spacing cut a palette
+    /// short.
+    fn note_spacing(&mut self, spaced_out: bool) {
+        if let Some(report) = self.constraint_report.as_mut() {
+            report.spaced_out |= spaced_out;
+        }
+    }
+
+    /// Finds colors written out in the mood, snapping them to the catalog
+    /// when configured to.
+    pub fn parse_mood(&self, mood_text: &str) -> ParsedQuery {
+        let mut parsed = parse_query(mood_text, &self.colors);
+        if self.config.snap_mentions {
+            for mention in &mut parsed.mentions {
+                if let Some(nearest) = self.nearest_color(&mention.color) {
+                    mention.color = nearest.clone();
+                }
+            }
+        }
+        parsed
+    }
+
+    /// Adds colors mentioned in the mood to `locked` and returns the text
+    /// left for the semantic search. A mood made only of color mentions is
+    /// searched by the mentioned colors' names instead.
+    fn pin_mentions(&self, mood_text: &str, locked: &[Color]) -> (String, Vec<Color>) {
+        let mut locked = locked.to_vec();
+        if !self.config.pin_mentions {
+            return (mood_text.to_string(), locked);
+        }
+
+        let parsed = self.parse_mood(mood_text);
+        if parsed.mentions.is_empty() {
+            return (mood_text.to_string(), locked);
+        }
+
+        for mention in &parsed.mentions {
+            if !locked
+                .iter()
+                .any(|kept| kept.hex.eq_ignore_ascii_case(&mention.color.hex))
+            {
+                locked.push(mention.color.clone());
+            }
+        }
+
+        let text = if tokenize(&parsed.text).is_empty() {
+            parsed
+                .mentions
+                .iter()
+                .map(|mention| mention.color.text_description())
+                .collect::<Vec<_>>()
+                .join("; ")
+        } else {
+            parsed.text
+        };
+        (text, locked)
+    }
+
+    /// The perceptually closest catalog color (ΔEok).
+    pub fn nearest_color(&self, color: &Color) -> Option<&Color> {
+        let target = color.to_oklab()?;
+        self.colors
+            .iter()
+            .filter_map(|candidate| Some((candidate, candidate.to_oklab()?.delta_e(&target))))
+            .min_by(|a, b| a.1.total_cmp(&b.1))
+            .map(|(candidate, _)| candidate)
+    }
+
+    /// Looks up a catalog color by name (case-insensitive) or hex value.
+    /// Hex values outside the catalog become standalone colors.
+    pub fn resolve_color(&self, spec: &str) -> Option<Color> {
+        let spec = spec.trim();
+        if let Some(color) = self
+            .colors
+            .iter()
+            .find(|color| color.name.eq_ignore_ascii_case(spec))
+        {
+            return Some(color.clone());
+        }
+
+        let rgb = parse_hex(spec)?;
+        Some(
+            self.colors
+                .iter()
+                .find(|color| color.get_rgb() == Some(rgb))
+                .cloned()
+                .unwrap_or_else(|| Color::from_hex(spec).unwrap()),
+        )
+    }
+
+    /// Pins the colors the mood names and ranks the text left over; what
+    /// the REPL and TUI build their palettes from.
+    pub async fn rank_mood(&mut self, mood_text: &str) -> Result<MoodRanking> {
+        let (text, pinned) = self.pin_mentions(mood_text, &[]);
+        Ok(MoodRanking {
+            ranking: self.rank_all(&text).await?,
+            pinned,
         })
     }
 
-    pub async fn generate_palette(&mut self, mood_text: &str) -> Result<Vec<ColorMatch>> {
+    /// Scores the mood's candidate colors, best match first. Colors named in
+    /// the mood are ranked like any other word; see `rank_mood`.
+    pub async fn rank_all(&mut self, mood_text: &str) -> Result<Vec<ColorMatch>> {
         if mood_text.trim().is_empty() {
             return Ok(vec![]);
         }
 
         // Generate embedding for the mood text
-        let mood_embedding = self
+        let mood_embedding = self.embed_moods(&[mood_text.to_string()]).await?;
+
+        if mood_embedding.is_empty() {
+            return Ok(vec![]);
+        }
+
+        let mut ranking = rank_colors(
+            &self.colors,
+            &self.search,
+            mood_text,
+            &mood_embedding[0],
+            &self.config,
+        )?;
+        self.feedback.rerank(&mut ranking, &mood_embedding[0]);
+        self.constraint_report = self.config.constraints.apply(&mut ranking);
+        Ok(ranking)
+    }
+
+    /// Stores a thumbs up or down on `color` for this mood; later rankings
+    /// for similar moods shift accordingly.
+    pub async fn record_feedback(
+        &mut self,
+        mood_text: &str,
+        color: &Color,
+        thumbs_up: bool,
+    ) -> Result<()> {
+        let mut embedding = self
             .embedder
             .generate_embeddings(&[mood_text.to_string()])
             .await?;
+        self.feedback
+            .record(mood_text, embedding.remove(0), color, thumbs_up)
+    }
 
-        if mood_embedding.is_empty() {
-            return Ok(vec![]);
+    /// Thesaurus expansions for the mood's abstract terms, when expansion is
+    /// switched on.
+    pub fn expansions(&self, mood_text: &str) -> Vec<Expansion> {
+        self.thesaurus
+            .as_ref()
+            .map(|thesaurus| thesaurus.expand(mood_text))
+            .unwrap_or_default()
+    }
+
+    /// Embeds each mood together with its expansions in one model call and
+    /// fuses them into a single query vector per mood.
+    async fn embed_moods(&mut self, moods: &[String]) -> Result<Vec<Vec<f32>>> {
+        let expansions: Vec<Vec<Expansion>> =
+            moods.iter().map(|mood| self.expansions(mood)).collect();
+        if expansions.iter().all(Vec::is_empty) {
+            return self.embedder.generate_embeddings(moods).await;
         }
 
-        let mood_vec = &mood_embedding[0];
+        let texts: Vec<String> = moods
+            .iter()
+            .zip(&expansions)
+            .flat_map(|(mood, expanded)| {
+                std::iter::once(mood.clone()).chain(expanded.iter().map(Expansion::query))
+            })
+            .collect();
+        let mut embeddings = self.embedder.generate_embeddings(&texts).await?.into_iter();
 
-        // Calculate cosine similarity with all color embeddings
-        let mut matches: Vec<ColorMatch> = self
-            .colors
+        Ok(expansions
             .iter()
-            .zip(self.color_embeddings.iter())
-            .map(|(color, color_embedding)| {
-                let score = EmbeddingGenerator::cosine_similarity(mood_vec, color_embedding);
-                ColorMatch {
-                    color: color.clone(),
-                    score,
+            .map(|expanded| {
+                let mood = embeddings.next().unwrap_or_default();
+                let expanded: Vec<Vec<f32>> = embeddings.by_ref().take(expanded.len()).collect();
+                fuse_queries(&mood, &expanded)
+            })
+            .collect())
+    }
+
+    /// Generates palettes for many moods at once, embedding them in chunks of
+    /// `config.batch_size` and spreading the scoring over `jobs` worker threads.
+    /// Each palette comes with what the constraints did to its mood's ranking.
+    pub async fn generate_palettes(
+        &mut self,
+        moods: &[String],
+        jobs: usize,
+    ) -> Result<Vec<(Vec<ColorMatch>, Option<ConstraintReport>)>> {
+        let mut palettes = Vec::with_capacity(moods.len());
+
+        for chunk in moods.chunks(self.config.batch_size) {
+            let (texts, pinned): (Vec<String>, Vec<Vec<Color>>) = chunk
+                .iter()
+                .map(|mood| self.pin_mentions(mood, &[]))
+                .unzip();
+
+            // Blank moods get an empty palette, matching `generate_palette`
+            let searched: Vec<String> = texts
+                .iter()
+                .filter(|text| !text.trim().is_empty())
+                .cloned()
+                .collect();
+            let mut embeddings = self.embed_moods(&searched).await?.into_iter();
+
+            let mood_vecs: Vec<Option<Vec<f32>>> = texts
+                .iter()
+                .map(|text| {
+                    if text.trim().is_empty() {
+                        None
+                    } else {
+                        embeddings.next()
+                    }
+                })
+                .collect();
+
+            // Sampling draws from one seeded generator, so palettes are built
+            // in mood order after the parallel scoring
+            let temperature = self.config.temperature;
+            let mut ranked: Vec<(Vec<ColorMatch>, Option<ConstraintReport>)> = self
+                .rank_many(&texts, &mood_vecs, jobs)?
+                .into_iter()
+                .zip(&pinned)
+                .map(|((ranking, mut report), locked)| {
+                    let (palette, spaced_out) = self.build_palette(&ranking, locked, temperature);
+                    if let Some(report) = report.as_mut() {
+                        report.spaced_out |= spaced_out;
+                    }
+                    (palette, report)
+                })
+                .collect();
+            if self.config.explain {
+                for (text, (palette, _)) in texts.iter().zip(&mut ranked) {
+                    let ablation = self.ablate(text).await?;
+                    self.explain(&ablation, text, palette)?;
                 }
+            }
+            palettes.extend(ranked);
+        }
+
+        Ok(palettes)
+    }
+
+    /// Scores each mood's embedding against the catalog, spreading the work
+    /// over `jobs` worker threads.
+    fn rank_many(
+        &self,
+        moods: &[String],
+        mood_vecs: &[Option<Vec<f32>>],
+        jobs: usize,
+    ) -> Result<Vec<(Vec<ColorMatch>, Option<ConstraintReport>)>> {
+        let rank = |(mood, mood_vec): (&String, &Option<Vec<f32>>)| {
+            let Some(vec) = mood_vec else {
+                return Ok((vec![], None));
+            };
+            let mut ranking = rank_colors(&self.colors, &self.search, mood, vec, &self.config)?;
+            self.feedback.rerank(&mut ranking, vec);
+            let report = self.config.constraints.apply(&mut ranking);
+            Ok((ranking, report))
+        };
+
+        if jobs <= 1 || mood_vecs.len() <= 1 {
+            return moods.iter().zip(mood_vecs).map(rank).collect();
+        }
+
+        let per_worker = mood_vecs.len().div_ceil(jobs);
+        std::thread::scope(|scope| {
+            let workers: Vec<_> = moods
+                .chunks(per_worker)
+                .zip(mood_vecs.chunks(per_worker))
+                .map(|(moods, vecs)| {
+                    scope.spawn(move || moods.iter().zip(vecs).map(rank).collect::<Vec<_>>())
+                })
+                .collect();
+
+            workers
+                .into_iter()
+                .flat_map(|worker| worker.join().expect("scoring worker panicked"))
+                .collect()
+        })
+    }
+}
+
+/// `color` as a locked palette entry, keeping its scores when the ranking
+/// has it.
+pub fn keep_color(ranking: &[ColorMatch], color: &Color) -> ColorMatch {
+    match ranking
+        .iter()
+        .find(|m| m.color.hex.eq_ignore_ascii_case(&color.hex))
+    {
+        Some(found) => ColorMatch {
+            color: color.clone(),
+            locked: true,
+            ..found.clone()
+        },
+        None => ColorMatch {
+            locked: true,
+            ..ColorMatch::unscored(color.clone())
+        },
+    }
+}
+
+/// Re-orders a ranking so candidates that clash with the locked colors sink:
+/// anything perceptually too close to a locked color is dropped, and the rest
+/// lose up to `LOCK_PENALTY` for poor hue harmony or lightness mismatch.
+pub fn fit_to_locked(ranking: &[ColorMatch], locked: &[Color]) -> Vec<ColorMatch> {
+    let locked_labs: Vec<Oklab> = locked.iter().filter_map(Color::to_oklab).collect();
+    if locked_labs.is_empty() {
+        return ranking.to_vec();
+    }
+
+    let mut fitted: Vec<(f32, &ColorMatch)> = ranking
+        .iter()
+        .filter_map(|candidate| {
+            let Some(lab) = candidate.color.to_oklab() else {
+                return Some((candidate.score - LOCK_PENALTY, candidate));
+            };
+            if locked_labs
+                .iter()
+                .any(|kept| kept.delta_e(&lab) < MIN_LOCKED_DISTANCE)
+            {
+                return None;
+            }
+
+            let lch = lab.to_oklch();
+            let compatibility = locked_labs
+                .iter()
+                .map(|kept| {
+                    let kept = kept.to_oklch();
+                    0.6 * hue_harmony(&lch, &kept) + 0.4 * (1.0 - (lch.l - kept.l).abs())
+                })
+                .sum::<f32>()
+                / locked_labs.len() as f32;
+
+            Some((
+                candidate.score - LOCK_PENALTY * (1.0 - compatibility),
+                candidate,
+            ))
+        })
+        .collect();
+
+    fitted.sort_by(|a, b| b.0.total_cmp(&a.0));
+    fitted.into_iter().map(|(_, m)| m.clone()).collect()
+}
+
+/// Picks `count` colors from a ranking, best first. With a non-zero
+/// `diversity` this is a maximal marginal relevance pass: each pick trades its
+/// mood score against how close it sits to colors already in the palette,
+/// including the `fixed` ones the caller is keeping. Candidates closer than
+/// `min_delta_e` (ΔEok) to an earlier pick are skipped, so the palette may
+/// come back short.
+pub fn select_palette(
+    ranking: &[ColorMatch],
+    count: usize,
+    diversity: f32,
+    min_delta_e: f32,
+    fixed: &[ColorMatch],
+) -> Vec<ColorMatch> {
+    pick_palette(ranking, count, diversity, min_delta_e, fixed, None)
+}
+
+/// Like `select_palette`, but each pick is drawn from a softmax over the
+/// (diversity-adjusted) scores divided by `temperature` instead of taking
+/// the best one. Scores only span a few tenths, so useful temperatures are
+/// small: around 0.02 stays close to the top matches, 0.2 is near uniform.
+pub fn sample_palette(
+    ranking: &[ColorMatch],
+    count: usize,
+    diversity: f32,
+    min_delta_e: f32,
+    fixed: &[ColorMatch],
+    temperature: f32,
+    rng: &mut StdRng,
+) -> Vec<ColorMatch> {
+    pick_palette(
+        ranking,
+        count,
+        diversity,
+        min_delta_e,
+        fixed,
+        Some((temperature, rng)),
+    )
+}
+
+fn pick_palette(
+    ranking: &[ColorMatch],
+    count: usize,
+    diversity: f32,
+    min_delta_e: f32,
+    fixed: &[ColorMatch],
+    mut sampler: Option<(f32, &mut StdRng)>,
+) -> Vec<ColorMatch> {
+    let candidates = ranking.iter().filter(|candidate| {
+        !fixed
+            .iter()
+            .any(|kept| kept.color.hex == candidate.color.hex)
+    });
+
+    if diversity <= 0.0 && sampler.is_none() && min_delta_e <= 0.0 {
+        return candidates.take(count).cloned().collect();
+    }
+
+    let mut pool: Vec<(&ColorMatch, Option<Oklab>)> = candidates
+        .map(|candidate| (candidate, candidate.color.to_oklab()))
+        .collect();
+    let mut chosen: Vec<Option<Oklab>> = fixed.iter().map(|kept| kept.color.to_oklab()).collect();
+    if min_delta_e > 0.0 {
+        pool.retain(|(_, lab)| {
+            !chosen
+                .iter()
+                .any(|kept| too_close(kept.as_ref(), lab.as_ref(), min_delta_e))
+        });
+    }
+    let mut palette = Vec::with_capacity(count);
+
+    while palette.len() < count && !pool.is_empty() {
+        let values: Vec<f32> = pool
+            .iter()
+            .map(|(candidate, lab)| {
+                if diversity <= 0.0 {
+                    return candidate.score;
+                }
+                let redundancy = chosen
+                    .iter()
+                    .map(|other| color_similarity(lab.as_ref(), other.as_ref()))
+                    .fold(0.0, f32::max);
+                (1.0 - diversity) * candidate.score - diversity * redundancy
             })
             .collect();
 
-        // Sort by similarity score (highest first)
-        matches.sort_by(|a, b| b.score.partial_cmp(&a.score).unwrap());
+        let pick = match sampler.as_mut() {
+            Some((temperature, rng)) => sample_index(&values, *temperature, rng),
+            None => {
+                values
+                    .iter()
+                    .enumerate()
+                    .max_by(|a, b| a.1.total_cmp(b.1))
+                    .unwrap()
+                    .0
+            }
+        };
+
+        let (candidate, lab) = pool.remove(pick);
+        chosen.push(lab);
+        palette.push(candidate.clone());
+        if min_delta_e > 0.0 {
+            pool.retain(|(_, other)| !too_close(lab.as_ref(), other.as_ref(), min_delta_e));
+        }
+    }
+
+    palette
+}
+
+/// Colors without a parseable hex are never too close to anything.
+fn too_close(a: Option<&Oklab>, b: Option<&Oklab>, min_delta_e: f32) -> bool {
+    matches!((a, b), (Some(a), Some(b)) if a.delta_e(b) < min_delta_e)
+}
+
+/// Draws an index with probability softmax(values / temperature).
+fn sample_index(values: &[f32], temperature: f32, rng: &mut StdRng) -> usize {
+    let max = values.iter().copied().fold(f32::NEG_INFINITY, f32::max);
+    let weights: Vec<f32> = values
+        .iter()
+        .map(|value| ((value - max) / temperature).exp())
+        .collect();
+    let total: f32 = weights.iter().sum();
+
+    let mut target = rng.random::<f32>() * total;
+    for (i, weight) in weights.iter().enumerate() {
+        if target < *weight {
+            return i;
+        }
+        target -= weight;
+    }
+    weights.len() - 1
+}
+
+/// Two palettes differ meaningfully when at most half of the unlocked colors
+/// in `b` have a near-identical counterpart in `a`.
+fn palettes_differ(a: &[ColorMatch], b: &[ColorMatch]) -> bool {
+    let a_labs: Vec<Option<Oklab>> = a
+        .iter()
+        .filter(|m| !m.locked)
+        .map(|m| m.color.to_oklab())
+        .collect();
+    let b_free: Vec<&ColorMatch> = b.iter().filter(|m| !m.locked).collect();
+
+    let shared = b_free
+        .iter()
+        .filter(|m| {
+            let lab = m.color.to_oklab();
+            a_labs
+                .iter()
+                .any(|other| color_similarity(lab.as_ref(), other.as_ref()) > 0.8)
+        })
+        .count();
+
+    shared * 2 <= b_free.len()
+}
+
+/// 1.0 for identical colors, falling to 0.0 once they are clearly distinct.
+fn color_similarity(a: Option<&Oklab>, b: Option<&Oklab>) -> f32 {
+    match (a, b) {
+        (Some(a), Some(b)) => 1.0 - (a.delta_e(b) / 0.3).min(1.0),
+        _ => 0.0,
+    }
+}
+
+fn is_zero(value: &f32) -> bool {
+    *value == 0.0
+}
+
+fn render_template(template: &str, color: &Color) -> String {
+    template
+        .replace("{name}", &color.name)
+        .replace("{description}", &color.description)
+}
+
+/// Averages each color's normalized template embeddings into one row.
+/// Cosine against the average ranks like the mean of the per-template
+/// cosines.
+fn mean_pool(rows: &[Vec<f32>], colors: usize) -> Vec<Vec<f32>> {
+    (0..colors)
+        .map(|color| {
+            let mut pooled = vec![0.0; rows[color].len()];
+            for template_row in rows.iter().skip(color).step_by(colors) {
+                for (sum, x) in pooled.iter_mut().zip(normalize(template_row)) {
+                    *sum += x;
+                }
+            }
+            pooled
+        })
+        .collect()
+}
+
+fn build_index(
+    kind: IndexKind,
+    cache: &EmbeddingCache,
+    catalog_key: &str,
+    embeddings: &[Vec<f32>],
+) -> Result<Box<dyn VectorIndex>> {
+    let use_hnsw = match kind {
+        IndexKind::Auto => embeddings.len() >= HNSW_THRESHOLD,
+        IndexKind::BruteForce => false,
+        IndexKind::Hnsw => true,
+    };
+    if !use_hnsw {
+        return Ok(Box::new(BruteForceIndex::new(EmbeddingMatrix::from_rows(
+            embeddings,
+        )?)));
+    }
+
+    let params = HnswParams::default();
+    let path = cache.path(catalog_key, "hnsw");
+    if let Ok(index) = HnswIndex::load(&path, EmbeddingMatrix::from_rows(embeddings)?, params) {
+        eprintln!("✅ Loaded HNSW index");
+        return Ok(Box::new(index));
+    }
+
+    eprintln!("🔄 Building HNSW index over {} colors...", embeddings.len());
+    let index = HnswIndex::build(EmbeddingMatrix::from_rows(embeddings)?, params);
+    if let Err(e) = index.save(&path) {
+        eprintln!("⚠️  Could not cache HNSW index: {}", e);
+    }
+    Ok(Box::new(index))
+}
+
+/// Scores the index's nearest colors, plus the strongest literal word
+/// matches, and returns them best first.
+fn rank_colors(
+    colors: &[Color],
+    search: &SearchIndex,
+    mood_text: &str,
+    mood_vec: &[f32],
+    config: &Config,
+) -> Result<Vec<ColorMatch>> {
+    let filter = TagFilter::new(&config.only, &config.exclude);
+    // Filters can leave little of a partial pool, so search everything
+    let narrowed =
+        !filter.is_empty() || (config.constraints.limits_colors() && !config.constraints.soft);
+    let pool = match config.candidate_pool {
+        _ if narrowed => colors.len(),
+        0 => colors.len(),
+        pool => pool.min(colors.len()),
+    };
+    let lexical_all = search.lexical.scores(mood_text);
+
+    let mut hits = search.search(mood_vec, pool)?;
+    hits.retain(|(row, _)| filter.allows(&colors[*row]));
+    let mut seen: HashSet<usize> = hits.iter().map(|(row, _)| *row).collect();
+
+    let mut lexical_rows: Vec<usize> = (0..lexical_all.len())
+        .filter(|row| lexical_all[*row] > 0.0 && filter.allows(&colors[*row]))
+        .collect();
+    lexical_rows.sort_by(|a, b| lexical_all[*b].total_cmp(&lexical_all[*a]));
+    for row in lexical_rows.into_iter().take(LEXICAL_POOL) {
+        if seen.insert(row) {
+            hits.push((row, search.similarity(row, mood_vec)?));
+        }
+    }
+
+    let (mean, spread) = baseline_stats(search, mood_vec)?;
+    let semantic: Vec<f32> = hits.iter().map(|(_, score)| *score).collect();
+    let lexical: Vec<f32> = hits.iter().map(|(row, _)| lexical_all[*row]).collect();
+    let scores = fuse_scores(&semantic, &lexical, config.fusion);
+
+    let mut matches: Vec<ColorMatch> = hits
+        .iter()
+        .enumerate()
+        .map(|(i, (row, _))| ColorMatch {
+            color: colors[*row].clone(),
+            score: scores[i],
+            semantic: semantic[i],
+            lexical: lexical[i],
+            confidence: (semantic[i] - mean) / spread,
+            feedback: 0.0,
+            explanation: None,
+            locked: false,
+        })
+        .collect();
+
+    // Literal word matches are kept even when the embedding disagrees
+    let best = semantic.iter().copied().fold(0.0, f32::max);
+    let floor = config.min_score.max(best * config.relative_floor);
+    matches.retain(|m| m.semantic >= floor || m.lexical > 0.0);
+
+    // Sort by similarity score (highest first)
+    matches.sort_by(|a, b| b.score.total_cmp(&a.score));
+
+    Ok(matches)
+}
+
+/// Weighted sum of the normalized mood and expansion vectors. Cosine
+/// against it ranks colors exactly like the same weighted mean of each
+/// query's own cosine scores, so one index search covers them all.
+fn fuse_queries(mood: &[f32], expanded: &[Vec<f32>]) -> Vec<f32> {
+    let mut fused = normalize(mood);
+    for vec in expanded {
+        for (sum, x) in fused.iter_mut().zip(normalize(vec)) {
+            *sum += EXPANSION_WEIGHT * x;
+        }
+    }
+    fused
+}
+
+/// Mean and standard deviation of the mood's similarity to the baseline
+/// sample of the catalog.
+fn baseline_stats(search: &SearchIndex, mood_vec: &[f32]) -> Result<(f32, f32)> {
+    let scores = search
+        .baseline
+        .iter()
+        .map(|row| search.similarity(*row, mood_vec))
+        .collect::<Result<Vec<f32>>>()?;
+    if scores.is_empty() {
+        return Ok((0.0, 1.0));
+    }
+
+    let mean = scores.iter().sum::<f32>() / scores.len() as f32;
+    let variance = scores.iter().map(|s| (s - mean).powi(2)).sum::<f32>() / scores.len() as f32;
+    Ok((mean, variance.sqrt().max(1e-6)))
+}
+
+/// Mean confidence of the palette's matched (unlocked) colors, or `None`
+/// when nothing matched.
+pub fn palette_confidence(palette: &[ColorMatch]) -> Option<f32> {
+    let matched: Vec<f32> = palette
+        .iter()
+        .filter(|m| !m.locked)
+        .map(|m| m.confidence)
+        .collect();
+    if matched.is_empty() {
+        return None;
+    }
+    Some(matched.iter().sum::<f32>() / matched.len() as f32)
+}
+
+pub fn confidence_label(confidence: f32) -> &'static str {
+    if confidence >= 5.0 {
+        "high"
+    } else if confidence >= VAGUE_CONFIDENCE {
+        "moderate"
+    } else {
+        "low"
+    }
+}
+
+fn fuse_scores(semantic: &[f32], lexical: &[f32], fusion: Fusion) -> Vec<f32> {
+    let best_lexical = lexical.iter().copied().fold(0.0, f32::max);
+    if best_lexical <= 0.0 {
+        return semantic.to_vec();
+    }
+
+    match fusion {
+        Fusion::Semantic => semantic.to_vec(),
+        Fusion::Linear { weight } => semantic
+            .iter()
+            .zip(lexical)
+            .map(|(s, l)| (1.0 - weight) * s + weight * l / best_lexical)
+            .collect(),
+        Fusion::Rrf { k } => {
+            let semantic_ranks = ranks(semantic);
+            let lexical_ranks = ranks(lexical);
+            (0..semantic.len())
+                .map(|i| {
+                    let mut fused = 1.0 / (k + semantic_ranks[i] as f32);
+                    if lexical[i] > 0.0 {
+                        fused += 1.0 / (k + lexical_ranks[i] as f32);
+                    }
+                    fused * (k + 1.0) / 2.0
+                })
+                .collect()
+        }
+    }
+}
+
+/// 1-based rank of each score, highest first.
+fn ranks(scores: &[f32]) -> Vec<usize> {
+    let mut order: Vec<usize> = (0..scores.len()).collect();
+    order.sort_by(|a, b| scores[*b].total_cmp(&scores[*a]));
+
+    let mut ranks = vec![0; scores.len()];
+    for (rank, i) in order.into_iter().enumerate() {
+        ranks[i] = rank + 1;
+    }
+    ranks
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::color_space::{hsl_to_rgb, to_hex};
+    use crate::colors::get_color_data;
+    use crate::constraints::Constraints;
+
+    fn ranked(name: &str, hex: &str, score: f32) -> ColorMatch {
+        ColorMatch {
+            score,
+            semantic: score,
+            ..ColorMatch::unscored(Color::new(name, hex, ""))
+        }
+    }
+
+    /// A generator over the built-in catalog that needs no model download,
+    /// ignores saved feedback and keeps every color in the ranking.
+    async fn hashing(config: Config) -> MoodPaletteGenerator {
+        let config = config
+            .with_embedder(EmbedderKind::Hashing)
+            .with_feedback(false)
+            .with_min_score(0.0)
+            .with_relative_floor(0.0);
+        MoodPaletteGenerator::new(config, get_color_data())
+            .await
+            .unwrap()
+    }
+
+    fn spaced(top_k: usize, min_delta_e: f32) -> Config {
+        Config::new()
+            .with_top_k(top_k)
+            .with_constraints(Constraints {
+                min_delta_e,
+                ..Constraints::default()
+            })
+    }
+
+    fn hexes(palette: &[ColorMatch]) -> Vec<&str> {
+        palette.iter().map(|m| m.color.hex.as_str()).collect()
+    }
+
+    #[tokio::test]
+    async fn locked_colors_stay_in_their_slot_across_regeneration() {
+        let config = Config::new()
+            .with_top_k(5)
+            .with_temperature(0.05)
+            .with_seed(3)
+            .with_pin_mentions(false);
+        let mut generator = hashing(config).await;
+        let catalog = generator.colors()[0].clone();
+        let locked = [Color::from_hex("#3A5F0B").unwrap(), catalog.clone()];
+
+        let mut unlocked = HashSet::new();
+        for _ in 0..4 {
+            let palette = generator
+                .generate_palette("cozy cabin in winter", &locked)
+                .await
+                .unwrap();
+            assert_eq!(palette.len(), 5);
+            assert_eq!(hexes(&palette[..2]), ["#3A5F0B", catalog.hex.as_str()]);
+            assert!(palette[..2].iter().all(|m| m.locked));
+            assert!(palette[2..].iter().all(|m| !m.locked));
+            unlocked.extend(palette[2..].iter().map(|m| m.color.hex.clone()));
+        }
+        assert!(unlocked.len() > 3, "unlocked slots should regenerate");
+    }
+
+    #[test]
+    fn fit_to_locked_drops_clashes_and_keeps_scores() {
+        let ranking = vec![
+            ranked("Red", "#FF0000", 0.9),
+            ranked("Almost Red", "#FA0505", 0.8),
+            ranked("Sky", "#87CEEB", 0.7),
+        ];
+        let locked = [Color::new("Brand Red", "#FE0101", "")];
+
+        let fitted = fit_to_locked(&ranking, &locked);
+        assert_eq!(hexes(&fitted), ["#87CEEB"]);
+
+        let kept = keep_color(&ranking, &Color::new("Sky Blue", "#87ceeb", ""));
+        assert!(kept.locked);
+        assert_eq!(kept.color.name, "Sky Blue");
+        assert_eq!(kept.score, 0.7);
+        assert_eq!(keep_color(&ranking, &locked[0]).score, 0.0);
+    }
+
+    fn spread_ranking() -> Vec<ColorMatch> {
+        (0..30)
+            .map(|i| {
+                let (r, g, b) = hsl_to_rgb(i as f32 * 12.0, 0.6, 0.5);
+                ranked(
+                    &format!("Hue {}", i),
+                    &to_hex(r, g, b),
+                    0.6 - i as f32 * 0.005,
+                )
+            })
+            .collect()
+    }
+
+    #[test]
+    fn sampling_follows_the_seed() {
+        let ranking = spread_ranking();
+        let sample = |seed| {
+            let mut rng = StdRng::seed_from_u64(seed);
+            let palette = sample_palette(&ranking, 5, 0.0, 0.0, &[], 0.2, &mut rng);
+            hexes(&palette).join(" ")
+        };
+
+        assert_eq!(sample(1), sample(1));
+        assert_ne!(sample(1), sample(2));
+    }
+
+    #[tokio::test]
+    async fn seeded_generators_repeat_their_palettes() {
+        let mood = "tropical beach sunset";
+        let mut palettes = vec![];
+        for seed in [5, 5, 6] {
+            let config = Config::new().with_temperature(0.2).with_seed(seed);
+            let mut generator = hashing(config).await;
+            palettes.push(generator.generate_palette(mood, &[]).await.unwrap());
+        }
+
+        assert_eq!(hexes(&palettes[0]), hexes(&palettes[1]));
+        assert_ne!(hexes(&palettes[0]), hexes(&palettes[2]));
+    }
+
+    #[tokio::test]
+    async fn zero_temperature_is_deterministic() {
+        let mood = "tropical beach sunset";
+        let mut greedy = hashing(Config::new()).await;
+        let expected = greedy.generate_palette(mood, &[]).await.unwrap();
+
+        for seed in [1, 2] {
+            let config = Config::new().with_temperature(0.0).with_seed(seed);
+            let mut generator = hashing(config).await;
+            let palette = generator.generate_palette(mood, &[]).await.unwrap();
+            assert_eq!(hexes(&palette), hexes(&expected));
+        }
+    }
+
+    #[test]
+    fn fusion_lifts_an_exact_lexical_hit() {
+        let semantic = [0.62, 0.58, 0.55, 0.41];
+        let lexical = [0.0, 0.0, 3.2, 0.0];
+        let best = |scores: Vec<f32>| ranks(&scores).iter().position(|rank| *rank == 1);
+
+        assert_eq!(
+            best(fuse_scores(&semantic, &lexical, Fusion::Semantic)),
+            Some(0)
+        );
+        for fusion in [Fusion::Linear { weight: 0.25 }, Fusion::Rrf { k: 60.0 }] {
+            assert_eq!(best(fuse_scores(&semantic, &lexical, fusion)), Some(2));
+        }
+        // Without any lexical hit both fall back to the embedding scores
+        assert_eq!(
+            fuse_scores(&semantic, &[0.0; 4], Fusion::Rrf { k: 60.0 }),
+            semantic
+        );
+    }
+
+    #[tokio::test]
+    async fn named_colors_rank_first_under_either_fusion() {
+        for fusion in [Fusion::Linear { weight: 0.25 }, Fusion::Rrf { k: 60.0 }] {
+            let config = Config::new().with_fusion(fusion);
+            let mut generator = hashing(config).await;
+            let ranking = generator.rank_all("a quiet celadon morning").await.unwrap();
+            assert_eq!(ranking[0].color.name, "Celadon", "{:?}", fusion);
+            assert!(ranking[0].lexical > 0.0);
+        }
+    }
+
+    #[tokio::test]
+    async fn colors_named_in_the_mood_are_pinned() {
+        let mut generator = hashing(Config::new().with_top_k(4)).await;
+        let palette = generator
+            .generate_palette("stormy sea with rgb(255, 107, 53) and teal", &[])
+            .await
+            .unwrap();
+        assert_eq!(hexes(&palette[..2]), ["#FF6B35", "#008080"]);
+        assert!(palette[..2].iter().all(|m| m.locked));
+        assert_eq!(palette.len(), 4);
+
+        let ranked = generator.rank_mood("teal").await.unwrap();
+        assert_eq!(ranked.pinned[0].name, "Teal");
+        assert!(!ranked.ranking.is_empty());
+    }
+
+    #[tokio::test]
+    async fn reports_when_spacing_cuts_a_palette_short() {
+        let mut generator = hashing(spaced(8, 0.6)).await;
+        let palette = generator.generate_palette("forest", &[]).await.unwrap();
+        assert!(palette.len() < 8);
+        assert!(generator.constraint_report().unwrap().spaced_out);
 
-        // Return top k matches
-        Ok(matches.into_iter().take(self.config.top_k).collect())
+        // Short only because the catalog runs out
+        let mut generator = hashing(spaced(1000, 1e-6)).await;
+        let palette = generator.generate_palette("forest", &[]).await.unwrap();
+        assert!(palette.len() < 1000);
+        assert!(!generator.constraint_report().unwrap().spaced_out);
     }
 }
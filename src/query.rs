@@ -0,0 +1,234 @@
+use crate::color_space::{hsl_to_rgb, parse_hex};
+use crate::colors::Color;
+use regex::Regex;
+use std::collections::HashMap;
+use std::ops::Range;
+use std::sync::OnceLock;
+
+/// A color spelled out in the mood text rather than described.
+#[derive(Debug, Clone)]
+pub struct ColorMention {
+    /// The text as written, e.g. `#FF6B35`, `rgb(255, 107, 53)` or `teal`
+    pub token: String,
+    pub color: Color,
+}
+
+#[derive(Debug, Clone)]
+pub struct ParsedQuery {
+    /// The mood with every mention cut out, for the semantic search
+    pub text: String,
+    pub mentions: Vec<ColorMention>,
+}
+
+fn literal_pattern() -> &'static Regex {
+    static PATTERN: OnceLock<Regex> = OnceLock::new();
+    PATTERN.get_or_init(|| {
+        Regex::new(
+            r"(?ix)
+            (?P<hex>\#(?:[0-9a-f]{6}|[0-9a-f]{3})\b)
+            | (?P<rgb>\brgba?\(\s*(?P<r>[0-9.]+%?)\s*[,\s]\s*(?P<g>[0-9.]+%?)\s*[,\s]\s*(?P<b>[0-9.]+%?)\s*(?:[,/]\s*[0-9.]+%?\s*)?\))
+            | (?P<hsl>\bhsla?\(\s*(?P<h>[0-9.]+)(?:deg)?\s*[,\s]\s*(?P<s>[0-9.]+)%\s*[,\s]\s*(?P<l>[0-9.]+)%\s*(?:[,/]\s*[0-9.]+%?\s*)?\))",
+        )
+        .unwrap()
+    })
+}
+
+/// Finds hex codes, `rgb()`/`hsl()` literals and exact catalog color names in
+/// a mood, longest name first so "Forest Green" wins over "Green".
+pub fn parse_query(mood: &str, catalog: &[Color]) -> ParsedQuery {
+    let mut found: Vec<(Range<usize>, Color)> = Vec::new();
+
+    for captures in literal_pattern().captures_iter(mood) {
+        let whole = captures.get(0).unwrap();
+        let rgb = if captures.name("hex").is_some() {
+            parse_hex(whole.as_str())
+        } else if captures.name("rgb").is_some() {
+            (|| {
+                Some((
+                    parse_channel(&captures["r"])?,
+                    parse_channel(&captures["g"])?,
+                    parse_channel(&captures["b"])?,
+                ))
+            })()
+        } else {
+            (|| {
+                let h: f32 = captures["h"].parse().ok()?;
+                let s: f32 = captures["s"].parse().ok()?;
+                let l: f32 = captures["l"].parse().ok()?;
+                Some(hsl_to_rgb(h, s / 100.0, l / 100.0))
+            })()
+        };
+
+        if let Some((r, g, b)) = rgb {
+            found.push((whole.range(), Color::from_rgb(r, g, b)));
+        }
+    }
+
+    for (range, color) in find_names(mood, catalog) {
+        if !found.iter().any(|(taken, _)| overlaps(taken, &range)) {
+            found.push((range, color));
+        }
+    }
+    found.sort_by_key(|(range, _)| range.start);
+
+    let mut text = String::with_capacity(mood.len());
+    let mut cursor = 0;
+    let mut mentions = Vec::with_capacity(found.len());
+    for (range, color) in found {
+        text.push_str(&mood[cursor..range.start]);
+        text.push(' ');
+        cursor = range.end;
+        mentions.push(ColorMention {
+            token: mood[range].to_string(),
+            color,
+        });
+    }
+    text.push_str(&mood[cursor..]);
+
+    ParsedQuery {
+        text: text.split_whitespace().collect::<Vec<_>>().join(" "),
+        mentions,
+    }
+}
+
+fn find_names(mood: &str, catalog: &[Color]) -> Vec<(Range<usize>, Color)> {
+    let mut by_name: HashMap<String, &Color> = HashMap::new();
+    let mut longest = 1;
+    for color in catalog {
+        let words = name_words(&color.name);
+        longest = longest.max(words.len());
+        by_name.entry(words.join(" ")).or_insert(color);
+    }
+
+    let words: Vec<(Range<usize>, String)> = word_spans(mood);
+    let mut found = Vec::new();
+    let mut i = 0;
+    while i < words.len() {
+        let matched = (1..=longest.min(words.len() - i)).rev().find_map(|len| {
+            let key = words[i..i + len]
+                .iter()
+                .map(|(_, word)| word.as_str())
+                .collect::<Vec<_>>()
+                .join(" ");
+            by_name.get(&key).map(|color| (len, *color))
+        });
+
+        match matched {
+            Some((len, color)) => {
+                let range = words[i].0.start..words[i + len - 1].0.end;
+                found.push((range, color.clone()));
+                i += len;
+            }
+            None => i += 1,
+        }
+    }
+    found
+}
+
+fn name_words(name: &str) -> Vec<String> {
+    word_spans(name).into_iter().map(|(_, word)| word).collect()
+}
+
+/// Lowercased words (letters, digits and apostrophes) with their byte ranges.
+fn word_spans(text: &str) -> Vec<(Range<usize>, String)> {
+    let mut spans = Vec::new();
+    let mut start = None;
+    for (i, c) in text
+        .char_indices()
+        .chain(std::iter::once((text.len(), ' ')))
+    {
+        let in_word = c.is_alphanumeric() || c == '\'';
+        match (start, in_word) {
+            (None, true) => start = Some(i),
+            (Some(s), false) => {
+                spans.push((s..i, text[s..i].to_lowercase()));
+                start = None;
+            }
+            _ => {}
+        }
+    }
+    spans
+}
+
+fn parse_channel(value: &str) -> Option<u8> {
+    let channel = match value.strip_suffix('%') {
+        Some(percent) => percent.parse::<f32>().ok()? * 2.55,
+        None => value.parse::<f32>().ok()?,
+    };
+    Some(channel.round().clamp(0.0, 255.0) as u8)
+}
+
+fn overlaps(a: &Range<usize>, b: &Range<usize>) -> bool {
+    a.start < b.end && b.start < a.end
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// The text left for the search and each mention as (token, hex).
+    fn parse(mood: &str) -> (String, Vec<(String, String)>) {
+        let catalog = [
+            Color::new("Green", "#00FF00", ""),
+            Color::new("Forest Green", "#228B22", ""),
+            Color::new("Teal", "#008080", ""),
+        ];
+        let parsed = parse_query(mood, &catalog);
+        let mentions = parsed
+            .mentions
+            .into_iter()
+            .map(|mention| (mention.token, mention.color.hex))
+            .collect();
+        (parsed.text, mentions)
+    }
+
+    fn mention(token: &str, hex: &str) -> (String, String) {
+        (token.to_string(), hex.to_string())
+    }
+
+    #[test]
+    fn pins_hex_literals() {
+        assert_eq!(
+            parse("sunset with #ff6b35 and #abc glow"),
+            (
+                "sunset with and glow".to_string(),
+                vec![mention("#ff6b35", "#FF6B35"), mention("#abc", "#AABBCC")]
+            )
+        );
+        assert_eq!(parse("issue #12345 notes").1, vec![]);
+    }
+
+    #[test]
+    fn pins_rgb_and_hsl_literals() {
+        assert_eq!(
+            parse("calm rgb(255, 107, 53) sea"),
+            (
+                "calm sea".to_string(),
+                vec![mention("rgb(255, 107, 53)", "#FF6B35")]
+            )
+        );
+        assert_eq!(
+            parse("rgba(100% 0% 0% / 50%) hsl(120deg, 100%, 25%)").1,
+            vec![
+                mention("rgba(100% 0% 0% / 50%)", "#FF0000"),
+                mention("hsl(120deg, 100%, 25%)", "#008000"),
+            ]
+        );
+    }
+
+    #[test]
+    fn pins_the_longest_catalog_name() {
+        assert_eq!(
+            parse("Misty forest green, teal and green"),
+            (
+                "Misty , and".to_string(),
+                vec![
+                    mention("forest green", "#228B22"),
+                    mention("teal", "#008080"),
+                    mention("green", "#00FF00"),
+                ]
+            )
+        );
+        assert_eq!(parse("greenhouse evergreen").1, vec![]);
+    }
+}
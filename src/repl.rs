@@ -0,0 +1,642 @@
+use crate::colors::Color;
+use crate::display::{DisplayOptions, PaletteDisplay};
+use crate::export::export_palette;
+use crate::lexical::tokenize;
+use crate::matcher::{fit_to_locked, keep_color, select_palette, ColorMatch, MoodPaletteGenerator};
+use crate::share::SharedPalette;
+use colored::*;
+use rustyline::completion::{Completer, Pair};
+use rustyline::error::ReadlineError;
+use rustyline::highlight::Highlighter;
+use rustyline::hint::Hinter;
+use rustyline::history::DefaultHistory;
+use rustyline::validate::Validator;
+use rustyline::{Context, Editor, Helper};
+use std::collections::{BTreeMap, HashSet};
+use std::path::{Path, PathBuf};
+
+const COMMANDS: &[(&str, &str, &str)] = &[
+    (":count", "N", "set how many colors are shown"),
+    (":bar", "", "toggle the compact color bar"),
+    (
+        ":avoid",
+        "X",
+        "skip colors mentioning X (no argument clears)",
+    ),
+    (":more", "", "show the next page of matches"),
+    (
+        ":lock",
+        "N",
+        "keep color N and regenerate the others (no argument clears)",
+    ),
+    (
+        ":export",
+        "PATH",
+        "save the palette (.json, .css, .gpl, .ase, .kpl or plain text)",
+    ),
+    (
+        ":share",
+        "",
+        "print a code teammates can open with colorify decode",
+    ),
+    (
+        ":up",
+        "N",
+        "thumbs up color N: rank it higher for moods like this",
+    ),
+    (
+        ":down",
+        "N",
+        "thumbs down color N: rank it lower for moods like this",
+    ),
+    (":undo", "", "go back to the previous palette"),
+    (":help", "", "show this list"),
+    (":quit", "", "leave interactive mode"),
+];
+
+/// Everything needed to rebuild the palette on screen; snapshots of it make
+/// up the undo stack.
+#[derive(Clone)]
+struct ReplState {
+    mood: Option<String>,
+    ranking: Vec<ColorMatch>,
+    count: usize,
+    display: DisplayOptions,
+    avoid: Vec<String>,
+    page: usize,
+    diversity: f32,
+    min_delta_e: f32,
+    locked: BTreeMap<usize, ColorMatch>,
+}
+
+impl ReplState {
+    /// True when every word of an avoided term appears in the color's name
+    /// or description, compared as stemmed tokens so "reds" avoids "Red" but
+    /// "red" leaves "Tired Grey" alone.
+    fn is_avoided(&self, color_match: &ColorMatch) -> bool {
+        let color = &color_match.color;
+        let words: HashSet<String> = tokenize(&color.name)
+            .into_iter()
+            .chain(tokenize(&color.description))
+            .collect();
+        self.avoid.iter().any(|term| {
+            let term = tokenize(term);
+            !term.is_empty() && term.iter().all(|word| words.contains(word))
+        })
+    }
+
+    fn kept(&self) -> Vec<ColorMatch> {
+        self.locked
+            .iter()
+            .filter(|(slot, _)| **slot < self.count)
+            .map(|(_, kept)| kept.clone())
+            .collect()
+    }
+
+    /// The ranking re-ordered to suit the locked colors, minus avoided terms.
+    fn candidates(&self) -> Vec<ColorMatch> {
+        let locked: Vec<Color> = self.kept().into_iter().map(|m| m.color).collect();
+        fit_to_locked(&self.ranking, &locked)
+            .into_iter()
+            .filter(|m| !self.is_avoided(m))
+            .collect()
+    }
+
+    fn free_slots(&self) -> usize {
+        self.count
+            - self
+                .locked
+                .keys()
+                .filter(|slot| **slot < self.count)
+                .count()
+    }
+
+    fn has_page(&self, page: usize) -> bool {
+        let free = self.free_slots();
+        free == 0 || page * free < self.candidates().len()
+    }
+
+    /// Fills locked slots with their kept colors and every other slot with
+    /// the next candidates for the current page.
+    fn palette(&self) -> Vec<ColorMatch> {
+        let free = self.free_slots();
+        let candidates = self.candidates();
+        let offset = (self.page * free).min(candidates.len());
+        let mut fill = select_palette(
+            &candidates[offset..],
+            free,
+            self.diversity,
+            self.min_delta_e,
+            &self.kept(),
+        )
+        .into_iter();
+
+        (0..self.count)
+            .filter_map(|slot| match self.locked.get(&slot) {
+                Some(locked) => Some(locked.clone()),
+                None => fill.next(),
+            })
+            .collect()
+    }
+}
+
+struct ReplHelper {
+    color_names: Vec<String>,
+}
+
+impl Completer for ReplHelper {
+    type Candidate = Pair;
+
+    fn complete(
+        &self,
+        line: &str,
+        pos: usize,
+        _ctx: &Context<'_>,
+    ) -> rustyline::Result<(usize, Vec<Pair>)> {
+        let before = &line[..pos];
+        let start = before.rfind(' ').map(|i| i + 1).unwrap_or(0);
+        let word = &before[start..];
+
+        if start == 0 && word.starts_with(':') {
+            let commands = COMMANDS
+                .iter()
+                .filter(|(command, _, _)| command.starts_with(word))
+                .map(|(command, _, _)| Pair {
+                    display: command.to_string(),
+                    replacement: command.to_string(),
+                })
+                .collect();
+            return Ok((start, commands));
+        }
+
+        if word.is_empty() {
+            return Ok((start, vec![]));
+        }
+
+        let prefix = word.to_lowercase();
+        let names = self
+            .color_names
+            .iter()
+            .filter(|name| name.to_lowercase().starts_with(&prefix))
+            .map(|name| Pair {
+                display: name.clone(),
+                replacement: name.clone(),
+            })
+            .collect();
+        Ok((start, names))
+    }
+}
+
+impl Hinter for ReplHelper {
+    type Hint = String;
+}
+
+impl Highlighter for ReplHelper {}
+
+impl Validator for ReplHelper {}
+
+impl Helper for ReplHelper {}
+
+pub async fn run_interactive_mode(generator: &mut MoodPaletteGenerator, display: DisplayOptions) {
+    PaletteDisplay::display_header();
+    println!(
+        "\n{}",
+        "🎨 Interactive Mode - Enter mood descriptions or :help for commands (Ctrl+D to exit)"
+            .bold()
+            .green()
+    );
+    PaletteDisplay::display_examples();
+
+    let mut editor: Editor<ReplHelper, DefaultHistory> = match Editor::new() {
+        Ok(editor) => editor,
+        Err(e) => {
+            println!("❌ Failed to start line editor: {}", e);
+            return;
+        }
+    };
+
+    let mut color_names: Vec<String> = generator
+        .colors()
+        .iter()
+        .map(|color| color.name.clone())
+        .collect();
+    color_names.sort();
+    color_names.dedup();
+    editor.set_helper(Some(ReplHelper { color_names }));
+
+    let history_path = history_path();
+    if let Some(path) = &history_path {
+        // A missing history file just means this is the first session
+        let _ = editor.load_history(path);
+    }
+
+    let mut state = ReplState {
+        mood: None,
+        ranking: vec![],
+        count: generator.config().top_k,
+        display,
+        avoid: vec![],
+        page: 0,
+        diversity: generator.config().diversity,
+        min_delta_e: generator.config().constraints.min_delta_e,
+        locked: BTreeMap::new(),
+    };
+    let mut undo_stack: Vec<ReplState> = Vec::new();
+
+    loop {
+        let input = match editor.readline("\n🎭 Enter mood: ") {
+            Ok(line) => line,
+            Err(ReadlineError::Interrupted) | Err(ReadlineError::Eof) => {
+                println!("\n{}", "Goodbye! 🌈".cyan());
+                break;
+            }
+            Err(e) => {
+                println!("❌ Failed to read input: {}", e);
+                break;
+            }
+        };
+
+        let input = input.trim();
+        if input.is_empty() {
+            println!("{}", "Please enter a mood description.".yellow());
+            continue;
+        }
+        let _ = editor.add_history_entry(input);
+
+        let lowered = input.to_lowercase();
+        if lowered == "quit" || lowered == "exit" || lowered == ":quit" || lowered == ":q" {
+            println!("{}", "Goodbye! 🌈".cyan());
+            break;
+        }
+
+        if let Some(command) = input.strip_prefix(':') {
+            let (name, arg) = match command.split_once(char::is_whitespace) {
+                Some((name, arg)) => (name, arg.trim()),
+                None => (command, ""),
+            };
+            if name == "up" || name == "down" {
+                give_feedback(generator, &mut state, arg, name == "up").await;
+            } else {
+                handle_command(name, arg, &mut state, &mut undo_stack);
+            }
+            continue;
+        }
+
+        let ranked = match generator.rank_mood(input).await {
+            Ok(ranked) => ranked,
+            Err(e) => {
+                println!("❌ Error generating palette: {}", e);
+                continue;
+            }
+        };
+
+        undo_stack.push(state.clone());
+        state.mood = Some(input.to_string());
+        state.page = 0;
+        state.locked = ranked
+            .pinned
+            .iter()
+            .take(state.count)
+            .enumerate()
+            .map(|(slot, color)| (slot, keep_color(&ranked.ranking, color)))
+            .collect();
+        state.ranking = ranked.ranking;
+
+        println!(
+            "\n{} {}",
+            "🔍 Analyzing mood:".bright_blue(),
+            input.italic().white()
+        );
+        if !ranked.pinned.is_empty() {
+            let names: Vec<&str> = ranked.pinned.iter().map(|c| c.name.as_str()).collect();
+            println!(
+                "{} {}",
+                "📌 Pinned from mood:".bright_blue(),
+                names.join(", ")
+            );
+        }
+        show_palette(&state);
+    }
+
+    if let Some(path) = &history_path {
+        if let Some(dir) = path.parent() {
+            let _ = std::fs::create_dir_all(dir);
+        }
+        if let Err(e) = editor.save_history(path) {
+            println!("⚠️  Could not save history: {}", e);
+        }
+    }
+}
+
+/// Records a thumbs up or down on a palette color and re-ranks the current
+/// mood so the verdict shows immediately.
+async fn give_feedback(
+    generator: &mut MoodPaletteGenerator,
+    state: &mut ReplState,
+    arg: &str,
+    thumbs_up: bool,
+) {
+    let Some(mood) = state.mood.clone() else {
+        println!("{}", "Enter a mood first.".yellow());
+        return;
+    };
+
+    let palette = state.palette();
+    let color = match arg.parse::<usize>() {
+        Ok(n) if n >= 1 && n <= palette.len() => palette[n - 1].color.clone(),
+        _ => {
+            let command = if thumbs_up { ":up" } else { ":down" };
+            println!(
+                "{}",
+                format!("Usage: {} N (1-{})", command, palette.len().max(1)).yellow()
+            );
+            return;
+        }
+    };
+
+    if let Err(e) = generator.record_feedback(&mood, &color, thumbs_up).await {
+        println!("❌ Could not save feedback: {}", e);
+        return;
+    }
+    let thumb = if thumbs_up { "👍" } else { "👎" };
+    println!("{} {} {}", thumb, "Noted for".cyan(), color.name.bold());
+
+    match generator.rank_mood(&mood).await {
+        Ok(ranked) => {
+            state.ranking = ranked.ranking;
+            show_palette(state);
+        }
+        Err(e) => println!("❌ Error generating palette: {}", e),
+    }
+}
+
+fn handle_command(name: &str, arg: &str, state: &mut ReplState, undo_stack: &mut Vec<ReplState>) {
+    match name {
+        "help" | "h" | "?" => display_help(),
+        "undo" => match undo_stack.pop() {
+            Some(previous) => {
+                *state = previous;
+                println!("{}", "↩️  Undone.".cyan());
+                show_palette(state);
+            }
+            None => println!("{}", "Nothing to undo.".yellow()),
+        },
+        "count" => match arg.parse::<usize>() {
+            Ok(count) if count > 0 => {
+                undo_stack.push(state.clone());
+                state.count = count;
+                state.page = 0;
+                show_palette(state);
+            }
+            _ => println!("{}", "Usage: :count N (N > 0)".yellow()),
+        },
+        "bar" => {
+            undo_stack.push(state.clone());
+            state.display.show_bar = !state.display.show_bar;
+            show_palette(state);
+        }
+        "avoid" => {
+            undo_stack.push(state.clone());
+            if arg.is_empty() {
+                state.avoid.clear();
+                println!("{}", "Cleared avoided terms.".cyan());
+            } else {
+                state.avoid.push(arg.to_lowercase());
+                println!("{} {}", "Avoiding:".cyan(), state.avoid.join(", ").italic());
+            }
+            state.page = 0;
+            show_palette(state);
+        }
+        "more" => {
+            if state.mood.is_none() {
+                println!("{}", "Enter a mood first.".yellow());
+            } else if !state.has_page(state.page + 1) {
+                println!("{}", "No more matches for this mood.".yellow());
+            } else {
+                undo_stack.push(state.clone());
+                state.page += 1;
+                show_palette(state);
+            }
+        }
+        "lock" => {
+            if arg.is_empty() {
+                undo_stack.push(state.clone());
+                state.locked.clear();
+                println!("{}", "Cleared locked colors.".cyan());
+                show_palette(state);
+                return;
+            }
+
+            let palette = state.palette();
+            match arg.parse::<usize>() {
+                Ok(n) if n >= 1 && n <= palette.len() => {
+                    undo_stack.push(state.clone());
+                    let mut kept = palette[n - 1].clone();
+                    kept.locked = true;
+                    state.locked.insert(n - 1, kept);
+                    // Move the unlocked slots on to fresh matches
+                    if state.has_page(state.page + 1) {
+                        state.page += 1;
+                    }
+                    show_palette(state);
+                }
+                _ => println!(
+                    "{}",
+                    format!("Usage: :lock N (1-{})", palette.len().max(1)).yellow()
+                ),
+            }
+        }
+        "export" => {
+            if arg.is_empty() {
+                println!("{}", "Usage: :export PATH".yellow());
+                return;
+            }
+            let palette = state.palette();
+            if palette.is_empty() {
+                println!("{}", "Nothing to export yet.".yellow());
+                return;
+            }
+            match export_palette(Path::new(arg), &palette) {
+                Ok(()) => println!("{} {}", "💾 Saved palette to".green(), arg),
+                Err(e) => println!("❌ Export failed: {}", e),
+            }
+        }
+        "share" => {
+            let palette = state.palette();
+            if palette.is_empty() {
+                println!("{}", "Nothing to share yet.".yellow());
+                return;
+            }
+            match SharedPalette::new(state.mood.as_deref(), &palette).encode() {
+                Ok(code) => println!("{} {}", "🔗 Share code:".bright_blue(), code),
+                Err(e) => println!("❌ Share failed: {}", e),
+            }
+        }
+        _ => println!(
+            "{}",
+            format!("Unknown command :{} (try :help)", name).yellow()
+        ),
+    }
+}
+
+fn show_palette(state: &ReplState) {
+    if state.mood.is_none() {
+        return;
+    }
+
+    let palette = state.palette();
+    if palette.is_empty() {
+        println!(
+            "{}",
+            "No matching colors found. Try a different mood description.".red()
+        );
+        return;
+    }
+
+    PaletteDisplay::show(&palette, &state.display);
+
+    if !state.locked.is_empty() {
+        let slots: Vec<String> = state
+            .locked
+            .keys()
+            .filter(|slot| **slot < state.count)
+            .map(|slot| (slot + 1).to_string())
+            .collect();
+        println!("{} {}", "🔒 Locked:".bright_black(), slots.join(", "));
+    }
+}
+
+fn display_help() {
+    println!("\n{}", "⌨️  Commands:".bold().yellow());
+    for (command, arg, help) in COMMANDS {
+        println!(
+            "   {:<14} {}",
+            format!("{} {}", command, arg).bold(),
+            help.bright_black()
+        );
+    }
+    println!(
+        "   {}",
+        "Anything else is treated as a new mood. Tab completes commands and color names.".italic()
+    );
+}
+
+fn history_path() -> Option<PathBuf> {
+    dirs::data_dir().map(|dir| dir.join("colorify").join("history.txt"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn ranked(name: &str, hex: &str, description: &str, score: f32) -> ColorMatch {
+        ColorMatch {
+            score,
+            semantic: score,
+            ..ColorMatch::unscored(Color::new(name, hex, description))
+        }
+    }
+
+    fn ocean(count: usize) -> ReplState {
+        ReplState {
+            mood: Some("ocean".to_string()),
+            ranking: vec![
+                ranked("Teal", "#008080", "Calm blue-green water.", 0.81),
+                ranked("Navy", "#000080", "Deep night sea.", 0.74),
+                ranked("Sand", "#C2B280", "Warm beach sand.", 0.62),
+                ranked("Coral", "#FF7F50", "Bright reddish reef coral.", 0.55),
+                ranked("Tired Grey", "#8A8A8A", "Overcast morning sky.", 0.5),
+            ],
+            count,
+            display: DisplayOptions::default(),
+            avoid: vec![],
+            page: 0,
+            diversity: 0.0,
+            min_delta_e: 0.0,
+            locked: BTreeMap::new(),
+        }
+    }
+
+    fn run(state: &mut ReplState, undo_stack: &mut Vec<ReplState>, line: &str) {
+        let command = line.strip_prefix(':').unwrap();
+        let (name, arg) = command.split_once(' ').unwrap_or((command, ""));
+        handle_command(name, arg, state, undo_stack);
+    }
+
+    fn names(state: &ReplState) -> Vec<String> {
+        state.palette().into_iter().map(|m| m.color.name).collect()
+    }
+
+    #[test]
+    fn avoid_matches_whole_words_in_names_and_descriptions() {
+        let mut state = ocean(5);
+        state.avoid = vec!["reddish".to_string(), "sea".to_string()];
+        assert_eq!(names(&state), ["Teal", "Sand", "Tired Grey"]);
+
+        state.avoid = vec!["red".to_string()];
+        assert_eq!(names(&state).len(), 5);
+
+        state.avoid = vec!["beach sands".to_string()];
+        assert_eq!(names(&state), ["Teal", "Navy", "Coral", "Tired Grey"]);
+
+        state.avoid = vec!["the".to_string()];
+        assert_eq!(names(&state).len(), 5);
+    }
+
+    #[test]
+    fn locked_colors_keep_their_slot_on_later_pages() {
+        let mut state = ocean(2);
+        let mut undo_stack = vec![];
+        assert_eq!(names(&state), ["Teal", "Navy"]);
+
+        run(&mut state, &mut undo_stack, ":lock 2");
+        assert_eq!(names(&state), ["Sand", "Navy"]);
+        assert!(state.palette()[1].locked);
+
+        run(&mut state, &mut undo_stack, ":more");
+        assert_eq!(names(&state), ["Coral", "Navy"]);
+
+        run(&mut state, &mut undo_stack, ":lock");
+        assert!(state.locked.is_empty());
+    }
+
+    #[test]
+    fn more_stops_at_the_last_page() {
+        let mut state = ocean(3);
+        let mut undo_stack = vec![];
+        run(&mut state, &mut undo_stack, ":more");
+        assert_eq!(names(&state), ["Coral", "Tired Grey"]);
+        run(&mut state, &mut undo_stack, ":more");
+        assert_eq!(state.page, 1);
+        assert_eq!(undo_stack.len(), 1);
+    }
+
+    #[test]
+    fn undo_restores_the_previous_state() {
+        let mut state = ocean(2);
+        let mut undo_stack = vec![];
+        run(&mut state, &mut undo_stack, ":count 4");
+        run(&mut state, &mut undo_stack, ":avoid coral");
+        assert_eq!(names(&state), ["Teal", "Navy", "Sand", "Tired Grey"]);
+
+        run(&mut state, &mut undo_stack, ":undo");
+        assert!(state.avoid.is_empty());
+        assert_eq!(state.count, 4);
+        run(&mut state, &mut undo_stack, ":undo");
+        assert_eq!(state.count, 2);
+        run(&mut state, &mut undo_stack, ":undo");
+        assert_eq!(state.count, 2);
+    }
+
+    #[test]
+    fn rejects_bad_arguments_without_touching_state() {
+        let mut state = ocean(2);
+        let mut undo_stack = vec![];
+        run(&mut state, &mut undo_stack, ":count 0");
+        run(&mut state, &mut undo_stack, ":lock 3");
+        run(&mut state, &mut undo_stack, ":lock two");
+        assert_eq!(state.count, 2);
+        assert!(state.locked.is_empty());
+        assert!(undo_stack.is_empty());
+    }
+}
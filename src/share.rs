@@ -0,0 +1,332 @@
+use crate::color_space::parse_hex;
+use crate::colors::Color;
+use crate::matcher::ColorMatch;
+use anyhow::{bail, Context, Result};
+
+/// Layout version written as the first byte of every code.
+const FORMAT_VERSION: u8 = 1;
+/// Every code starts with this so it is recognisable when pasted around.
+const PREFIX: &str = "cfy";
+const HAS_MOOD: u8 = 0b0000_0001;
+const CHECKSUM_LEN: usize = 4;
+const ALPHABET: &[u8; 64] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789-_";
+
+/// A palette in the form it travels in: colors and, optionally, the mood it
+/// was made for. Scores are not carried.
+#[derive(Debug, Clone)]
+pub struct SharedPalette {
+    pub mood: Option<String>,
+    pub colors: Vec<Color>,
+}
+
+impl SharedPalette {
+    pub fn new(mood: Option<&str>, palette: &[ColorMatch]) -> Self {
+        Self {
+            mood: mood.map(str::to_string),
+            colors: palette.iter().map(|m| m.color.clone()).collect(),
+        }
+    }
+
+    /// Encodes as `cfy` followed by unpadded URL-safe base64 of:
+    /// version, flags, color count, then per color its RGB bytes and name
+    /// (empty for colors outside the catalog), the mood if flagged, and an
+    /// FNV-1a checksum of everything before it. Fails rather than sharing a
+    /// different palette when a color has no valid hex value or there are
+    /// more than 255 colors.
+    pub fn encode(&self) -> Result<String> {
+        if self.colors.len() > u8::MAX as usize {
+            bail!(
+                "palette codes hold at most {} colors, not {}",
+                u8::MAX,
+                self.colors.len()
+            );
+        }
+        let mut bytes = vec![
+            FORMAT_VERSION,
+            if self.mood.is_some() { HAS_MOOD } else { 0 },
+            self.colors.len() as u8,
+        ];
+        for color in &self.colors {
+            let Some((r, g, b)) = parse_hex(&color.hex) else {
+                bail!(
+                    "cannot share {}: '{}' is not a hex color",
+                    color.name,
+                    color.hex
+                );
+            };
+            bytes.extend([r, g, b]);
+            let name = if color.name.eq_ignore_ascii_case(&color.hex) {
+                ""
+            } else {
+                color.name.as_str()
+            };
+            push_str(&mut bytes, name);
+        }
+        if let Some(mood) = &self.mood {
+            push_str(&mut bytes, mood);
+        }
+        let checksum = fnv1a(&bytes);
+        bytes.extend(checksum.to_be_bytes());
+
+        Ok(format!("{}{}", PREFIX, base64_encode(&bytes)))
+    }
+
+    pub fn decode(code: &str) -> Result<Self> {
+        let code = code.trim();
+        let Some(body) = code.strip_prefix(PREFIX) else {
+            bail!("not a colorify palette code (they start with '{}')", PREFIX);
+        };
+        let bytes = base64_decode(body)?;
+        // Later versions may lay out the checksum differently, so the
+        // version is checked first
+        if let Some(version) = bytes.first().filter(|v| **v != FORMAT_VERSION) {
+            bail!(
+                "palette code uses format v{}, this colorify reads v{}",
+                version,
+                FORMAT_VERSION
+            );
+        }
+        if bytes.len() < 3 + CHECKSUM_LEN {
+            bail!("palette code is too short");
+        }
+        let (payload, checksum) = bytes.split_at(bytes.len() - CHECKSUM_LEN);
+        if fnv1a(payload).to_be_bytes() != checksum {
+            bail!("palette code is damaged (checksum mismatch); was it copied in full?");
+        }
+
+        let mut reader = Reader {
+            bytes: payload,
+            pos: 3,
+        };
+        let flags = payload[1];
+        let mut colors = Vec::with_capacity(payload[2] as usize);
+        for _ in 0..payload[2] {
+            let rgb = reader.take(3)?;
+            let mut color = Color::from_rgb(rgb[0], rgb[1], rgb[2]);
+            let name = reader.string()?;
+            if !name.is_empty() {
+                color.name = name;
+            }
+            colors.push(color);
+        }
+        let mood = if flags & HAS_MOOD != 0 {
+            Some(reader.string()?)
+        } else {
+            None
+        };
+        if reader.pos != payload.len() {
+            bail!("palette code has unexpected trailing data");
+        }
+        Ok(Self { mood, colors })
+    }
+
+    /// Fills in descriptions for colors that match a catalog entry by name
+    /// and hex, so exports read the same as on the sender's machine.
+    pub fn describe_from(&mut self, catalog: &[Color]) {
+        for color in &mut self.colors {
+            if let Some(known) = catalog.iter().find(|known| {
+                known.name.eq_ignore_ascii_case(&color.name)
+                    && known.hex.eq_ignore_ascii_case(&color.hex)
+            }) {
+                color.description = known.description.clone();
+            }
+        }
+    }
+
+    pub fn to_matches(&self) -> Vec<ColorMatch> {
+        self.colors
+            .iter()
+            .cloned()
+            .map(ColorMatch::unscored)
+            .collect()
+    }
+}
+
+struct Reader<'a> {
+    bytes: &'a [u8],
+    pos: usize,
+}
+
+impl Reader<'_> {
+    fn take(&mut self, n: usize) -> Result<&[u8]> {
+        let end = self.pos + n;
+        let Some(slice) = self.bytes.get(self.pos..end) else {
+            bail!("palette code is truncated");
+        };
+        self.pos = end;
+        Ok(slice)
+    }
+
+    fn varint(&mut self) -> Result<usize> {
+        let mut value = 0usize;
+        for shift in (0..28).step_by(7) {
+            let byte = self.take(1)?[0];
+            value |= ((byte & 0x7f) as usize) << shift;
+            if byte & 0x80 == 0 {
+                return Ok(value);
+            }
+        }
+        bail!("palette code has an oversized length")
+    }
+
+    fn string(&mut self) -> Result<String> {
+        let len = self.varint()?;
+        let bytes = self.take(len)?;
+        String::from_utf8(bytes.to_vec()).context("palette code has invalid text")
+    }
+}
+
+/// Writes a LEB128 length followed by the UTF-8 bytes.
+fn push_str(bytes: &mut Vec<u8>, text: &str) {
+    let mut len = text.len();
+    loop {
+        let byte = (len & 0x7f) as u8;
+        len >>= 7;
+        if len == 0 {
+            bytes.push(byte);
+            break;
+        }
+        bytes.push(byte | 0x80);
+    }
+    bytes.extend_from_slice(text.as_bytes());
+}
+
+fn fnv1a(bytes: &[u8]) -> u32 {
+    let mut hash: u32 = 0x811c_9dc5;
+    for byte in bytes {
+        hash ^= *byte as u32;
+        hash = hash.wrapping_mul(0x0100_0193);
+    }
+    hash
+}
+
+fn base64_encode(bytes: &[u8]) -> String {
+    let mut out = String::with_capacity(bytes.len().div_ceil(3) * 4);
+    for chunk in bytes.chunks(3) {
+        let n = (chunk[0] as u32) << 16
+            | (*chunk.get(1).unwrap_or(&0) as u32) << 8
+            | *chunk.get(2).unwrap_or(&0) as u32;
+        for i in 0..=chunk.len() {
+            out.push(ALPHABET[(n >> (18 - 6 * i) & 0x3f) as usize] as char);
+        }
+    }
+    out
+}
+
+fn base64_decode(text: &str) -> Result<Vec<u8>> {
+    let mut values = Vec::with_capacity(text.len());
+    for c in text.bytes() {
+        match ALPHABET.iter().position(|a| *a == c) {
+            Some(value) => values.push(value as u32),
+            None => bail!(
+                "palette code contains '{}', which is not allowed",
+                c as char
+            ),
+        }
+    }
+    if values.len() % 4 == 1 {
+        bail!("palette code is truncated");
+    }
+
+    let mut bytes = Vec::with_capacity(values.len() * 3 / 4);
+    for chunk in values.chunks(4) {
+        let n = chunk
+            .iter()
+            .enumerate()
+            .fold(0u32, |n, (i, v)| n | v << (18 - 6 * i));
+        // Bits past the last byte are always zero when encoding; anything
+        // else means the final character was altered
+        if n << (8 * (chunk.len() - 1)) & 0xff_ffff != 0 {
+            bail!("palette code is damaged; was it copied in full?");
+        }
+        bytes.extend(n.to_be_bytes()[1..chunk.len()].iter());
+    }
+    Ok(bytes)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn shared() -> SharedPalette {
+        SharedPalette {
+            mood: Some("misty fjord at dawn 🌅".to_string()),
+            colors: vec![
+                Color::new("Teal", "#008080", ""),
+                Color::from_hex("#1A2B3C").unwrap(),
+                Color::new("Crème brûlée", "#FFE5B4", ""),
+            ],
+        }
+    }
+
+    #[test]
+    fn round_trips() {
+        let code = shared().encode().unwrap();
+        assert!(code.starts_with(PREFIX));
+
+        let decoded = SharedPalette::decode(&format!("  {}\n", code)).unwrap();
+        assert_eq!(decoded.mood, shared().mood);
+        let colors: Vec<(&str, &str)> = decoded
+            .colors
+            .iter()
+            .map(|c| (c.name.as_str(), c.hex.as_str()))
+            .collect();
+        assert_eq!(
+            colors,
+            [
+                ("Teal", "#008080"),
+                ("#1A2B3C", "#1A2B3C"),
+                ("Crème brûlée", "#FFE5B4")
+            ]
+        );
+
+        let moodless = SharedPalette::new(None, &[]).encode().unwrap();
+        assert!(SharedPalette::decode(&moodless).unwrap().mood.is_none());
+    }
+
+    #[test]
+    fn refuses_palettes_it_cannot_carry() {
+        let mut bad_hex = shared();
+        bad_hex.colors.push(Color::new("Oops", "#12345", ""));
+        assert!(bad_hex.encode().is_err());
+
+        let too_many = SharedPalette {
+            mood: None,
+            colors: vec![Color::from_hex("#000000").unwrap(); 256],
+        };
+        assert!(too_many.encode().is_err());
+    }
+
+    #[test]
+    fn rejects_any_single_changed_character() {
+        let code = shared().encode().unwrap();
+        for (i, c) in code.char_indices().skip(PREFIX.len()) {
+            let replacement = if c == 'A' { 'B' } else { 'A' };
+            let mut flipped = code.clone();
+            flipped.replace_range(i..i + 1, &replacement.to_string());
+            assert!(SharedPalette::decode(&flipped).is_err(), "position {}", i);
+        }
+    }
+
+    #[test]
+    fn rejects_truncated_codes() {
+        let code = shared().encode().unwrap();
+        for len in 0..code.len() {
+            assert!(
+                SharedPalette::decode(&code[..len]).is_err(),
+                "length {}",
+                len
+            );
+        }
+    }
+
+    #[test]
+    fn reports_unknown_versions_as_newer() {
+        let mut bytes = vec![2, 0, 0, 0xde, 0xad];
+        bytes.extend([0xbe, 0xef, 0x00]);
+        let code = format!("{}{}", PREFIX, base64_encode(&bytes));
+
+        let error = SharedPalette::decode(&code).unwrap_err().to_string();
+        assert_eq!(error, "palette code uses format v2, this colorify reads v1");
+    }
+}
@@ -0,0 +1,545 @@
+//! Readers for the swatch libraries design tools keep: GIMP `.gpl`, Adobe
+//! `.ase` and `.aco`, Krita `.kpl` and Procreate `.swatches`. Names are kept
+//! when the file has them and left empty otherwise; `describe::fill_missing`
+//! fills the gaps. Writers cover `.gpl`, `.ase` and `.kpl`.
+
+use crate::color_space::{cmyk_to_rgb, hsv_to_rgb, lab_to_rgb, to_hex};
+use crate::colors::Color;
+use anyhow::{bail, Context, Result};
+use regex::Regex;
+use serde::Deserialize;
+use std::fs;
+use std::io::{Cursor, Read, Write};
+use std::path::Path;
+use zip::write::SimpleFileOptions;
+use zip::{CompressionMethod, ZipArchive, ZipWriter};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SwatchFormat {
+    Gpl,
+    Ase,
+    Aco,
+    Kpl,
+    Procreate,
+}
+
+impl SwatchFormat {
+    pub fn from_path(path: &Path) -> Option<Self> {
+        match path
+            .extension()
+            .and_then(|ext| ext.to_str())
+            .map(|ext| ext.to_lowercase())
+            .as_deref()
+        {
+            Some("gpl") => Some(SwatchFormat::Gpl),
+            Some("ase") => Some(SwatchFormat::Ase),
+            Some("aco") => Some(SwatchFormat::Aco),
+            Some("kpl") => Some(SwatchFormat::Kpl),
+            Some("swatches") => Some(SwatchFormat::Procreate),
+            _ => None,
+        }
+    }
+}
+
+/// Reads every color in a swatch file; names the file lacks are empty.
+pub fn load_swatches(path: &Path, format: SwatchFormat) -> Result<Vec<Color>> {
+    let bytes = fs::read(path).with_context(|| format!("failed to read {}", path.display()))?;
+    let colors = match format {
+        SwatchFormat::Gpl => parse_gpl(&String::from_utf8_lossy(&bytes)),
+        SwatchFormat::Ase => parse_ase(&bytes),
+        SwatchFormat::Aco => parse_aco(&bytes),
+        SwatchFormat::Kpl => parse_kpl(&bytes),
+        SwatchFormat::Procreate => parse_procreate(&bytes),
+    };
+    colors.with_context(|| format!("{} is not a readable swatch file", path.display()))
+}
+
+fn color(name: &str, (r, g, b): (u8, u8, u8)) -> Color {
+    Color {
+        name: name.trim().to_string(),
+        hex: to_hex(r, g, b),
+        ..Color::default()
+    }
+}
+
+/// GIMP palette: a `GIMP Palette` header, optional `Name:`/`Columns:` lines
+/// and comments, then `R G B [name]` per line.
+pub fn parse_gpl(text: &str) -> Result<Vec<Color>> {
+    let mut lines = text.lines();
+    if lines.next().map(str::trim) != Some("GIMP Palette") {
+        bail!("missing 'GIMP Palette' header");
+    }
+
+    let mut colors = Vec::new();
+    for (line_number, line) in lines.enumerate() {
+        let line = line.trim();
+        if line.is_empty()
+            || line.starts_with('#')
+            || line.starts_with("Name:")
+            || line.starts_with("Columns:")
+        {
+            continue;
+        }
+
+        // The name is the rest of the line, tabs and repeated spaces included
+        let mut rest = line;
+        let mut channel = || -> Result<u8> {
+            let field = rest.trim_start();
+            let end = field.find(char::is_whitespace).unwrap_or(field.len());
+            rest = &field[end..];
+            field[..end]
+                .parse()
+                .ok()
+                .with_context(|| format!("line {}: expected 'R G B [name]'", line_number + 2))
+        };
+        let rgb = (channel()?, channel()?, channel()?);
+        let name = match rest.trim() {
+            // GIMP writes this for swatches nobody named
+            "Untitled" => "",
+            name => name,
+        };
+        colors.push(color(name, rgb));
+    }
+    Ok(colors)
+}
+
+/// Big-endian cursor over a binary swatch file.
+struct Reader<'a> {
+    bytes: &'a [u8],
+    pos: usize,
+}
+
+impl<'a> Reader<'a> {
+    fn new(bytes: &'a [u8]) -> Self {
+        Self { bytes, pos: 0 }
+    }
+
+    fn take(&mut self, n: usize) -> Result<&'a [u8]> {
+        let Some(slice) = self.bytes.get(self.pos..self.pos + n) else {
+            bail!("unexpected end of file at byte {}", self.pos);
+        };
+        self.pos += n;
+        Ok(slice)
+    }
+
+    fn u16(&mut self) -> Result<u16> {
+        let bytes = self.take(2)?;
+        Ok(u16::from_be_bytes([bytes[0], bytes[1]]))
+    }
+
+    fn u32(&mut self) -> Result<u32> {
+        let bytes = self.take(4)?;
+        Ok(u32::from_be_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]))
+    }
+
+    fn f32(&mut self) -> Result<f32> {
+        Ok(f32::from_bits(self.u32()?))
+    }
+
+    /// UTF-16BE text of `units` code units, dropping the NUL terminator.
+    fn utf16(&mut self, units: usize) -> Result<String> {
+        let bytes = self.take(units * 2)?;
+        let mut units: Vec<u16> = bytes
+            .chunks_exact(2)
+            .map(|pair| u16::from_be_bytes([pair[0], pair[1]]))
+            .collect();
+        while units.last() == Some(&0) {
+            units.pop();
+        }
+        Ok(String::from_utf16_lossy(&units))
+    }
+
+    fn is_empty(&self) -> bool {
+        self.pos >= self.bytes.len()
+    }
+}
+
+const ASE_GROUP_START: u16 = 0xC001;
+const ASE_GROUP_END: u16 = 0xC002;
+const ASE_COLOR: u16 = 0x0001;
+
+/// Adobe Swatch Exchange: `ASEF`, version, block count, then color and group
+/// blocks. Colors may be RGB, CMYK, Lab or Gray, stored as 32-bit floats.
+pub fn parse_ase(bytes: &[u8]) -> Result<Vec<Color>> {
+    let mut reader = Reader::new(bytes);
+    if reader.take(4)? != b"ASEF" {
+        bail!("missing ASEF signature");
+    }
+    let _version = (reader.u16()?, reader.u16()?);
+    let blocks = reader.u32()?;
+
+    let mut colors = Vec::new();
+    for _ in 0..blocks {
+        let kind = reader.u16()?;
+        let length = reader.u32()? as usize;
+        let body = reader.take(length)?;
+        if kind != ASE_COLOR {
+            if kind != ASE_GROUP_START && kind != ASE_GROUP_END {
+                bail!("unknown block type {:#06x}", kind);
+            }
+            continue;
+        }
+
+        let mut block = Reader::new(body);
+        let name_units = block.u16()? as usize;
+        let name = block.utf16(name_units)?;
+        let model = block.take(4)?;
+        let rgb = match model {
+            b"RGB " => {
+                let (r, g, b) = (block.f32()?, block.f32()?, block.f32()?);
+                let channel = |v: f32| (v.clamp(0.0, 1.0) * 255.0).round() as u8;
+                (channel(r), channel(g), channel(b))
+            }
+            b"CMYK" => cmyk_to_rgb(block.f32()?, block.f32()?, block.f32()?, block.f32()?),
+            // L is stored as a fraction of 100
+            b"LAB " => lab_to_rgb(block.f32()? * 100.0, block.f32()?, block.f32()?),
+            b"Gray" => {
+                let gray = (block.f32()?.clamp(0.0, 1.0) * 255.0).round() as u8;
+                (gray, gray, gray)
+            }
+            other => bail!(
+                "unsupported color model '{}'",
+                String::from_utf8_lossy(other)
+            ),
+        };
+        colors.push(color(&name, rgb));
+    }
+    Ok(colors)
+}
+
+/// Photoshop color swatches: a version 1 section of unnamed colors, usually
+/// followed by a version 2 section repeating them with names.
+pub fn parse_aco(bytes: &[u8]) -> Result<Vec<Color>> {
+    let mut reader = Reader::new(bytes);
+    let mut colors = Vec::new();
+    while !reader.is_empty() {
+        let version = reader.u16()?;
+        if version != 1 && version != 2 {
+            bail!("unsupported .aco version {}", version);
+        }
+        let count = reader.u16()?;
+        let mut section = Vec::with_capacity(count as usize);
+        for _ in 0..count {
+            let space = reader.u16()?;
+            let values = [reader.u16()?, reader.u16()?, reader.u16()?, reader.u16()?];
+            let name = if version == 2 {
+                let units = reader.u32()? as usize;
+                reader.utf16(units)?
+            } else {
+                String::new()
+            };
+            section.push(color(&name, aco_rgb(space, values)?));
+        }
+        // The named section supersedes the plain one
+        colors = section;
+    }
+    Ok(colors)
+}
+
+fn aco_rgb(space: u16, [w, x, y, z]: [u16; 4]) -> Result<(u8, u8, u8)> {
+    let unit = |v: u16| v as f32 / 65535.0;
+    Ok(match space {
+        0 => ((w >> 8) as u8, (x >> 8) as u8, (y >> 8) as u8),
+        1 => hsv_to_rgb(unit(w) * 360.0, unit(x), unit(y)),
+        // 0 means full ink
+        2 => cmyk_to_rgb(1.0 - unit(w), 1.0 - unit(x), 1.0 - unit(y), 1.0 - unit(z)),
+        7 => lab_to_rgb(
+            w as f32 / 100.0,
+            x as i16 as f32 / 100.0,
+            y as i16 as f32 / 100.0,
+        ),
+        8 => {
+            let gray = 255 - (w.min(10000) as u32 * 255 / 10000) as u8;
+            (gray, gray, gray)
+        }
+        other => bail!("unsupported color space {}", other),
+    })
+}
+
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum ProcreateFile {
+    Many(Vec<ProcreatePalette>),
+    One(ProcreatePalette),
+}
+
+#[derive(Deserialize)]
+struct ProcreatePalette {
+    #[serde(default)]
+    swatches: Vec<Option<ProcreateSwatch>>,
+}
+
+#[derive(Deserialize)]
+struct ProcreateSwatch {
+    hue: f32,
+    saturation: f32,
+    brightness: f32,
+    #[serde(default)]
+    name: String,
+}
+
+/// Procreate swatches: a zip holding `Swatches.json`, with HSB values in
+/// 0..1 and empty slots as `null`.
+pub fn parse_procreate(bytes: &[u8]) -> Result<Vec<Color>> {
+    let mut archive = ZipArchive::new(Cursor::new(bytes)).context("not a zip archive")?;
+    let mut json = String::new();
+    archive
+        .by_name("Swatches.json")
+        .context("archive has no Swatches.json")?
+        .read_to_string(&mut json)?;
+
+    let palettes = match serde_json::from_str(&json).context("invalid Swatches.json")? {
+        ProcreateFile::Many(palettes) => palettes,
+        ProcreateFile::One(palette) => vec![palette],
+    };
+    Ok(palettes
+        .into_iter()
+        .flat_map(|palette| palette.swatches)
+        .flatten()
+        .map(|swatch| {
+            color(
+                &swatch.name,
+                hsv_to_rgb(swatch.hue * 360.0, swatch.saturation, swatch.brightness),
+            )
+        })
+        .collect())
+}
+
+const KPL_MIMETYPE: &str = "krita/x-colorset";
+
+/// Krita palette: a zip with a `colorset.xml` of `ColorSetEntry` elements,
+/// each holding an `RGB` element with channels in 0..1.
+pub fn parse_kpl(bytes: &[u8]) -> Result<Vec<Color>> {
+    let mut archive = ZipArchive::new(Cursor::new(bytes)).context("not a zip archive")?;
+    let mut xml = String::new();
+    archive
+        .by_name("colorset.xml")
+        .context("archive has no colorset.xml")?
+        .read_to_string(&mut xml)?;
+
+    let entry = Regex::new(r#"(?s)<ColorSetEntry\b([^>]*)>(.*?)</ColorSetEntry>"#)?;
+    let name = Regex::new(r#"\bname="([^"]*)""#)?;
+    let rgb = Regex::new(r#"<RGB\b[^>]*?\br="([^"]+)"[^>]*?\bg="([^"]+)"[^>]*?\bb="([^"]+)""#)?;
+
+    let mut colors = Vec::new();
+    for captures in entry.captures_iter(&xml) {
+        let Some(channels) = rgb.captures(&captures[2]) else {
+            // Only RGB entries are supported; skip CMYK, Lab and gray ones
+            continue;
+        };
+        let channel = |i: usize| -> Result<u8> {
+            let value: f32 = channels[i]
+                .parse()
+                .with_context(|| format!("invalid channel value '{}'", &channels[i]))?;
+            Ok((value.clamp(0.0, 1.0) * 255.0).round() as u8)
+        };
+        let entry_name = name
+            .captures(&captures[1])
+            .map(|name| xml_unescape(&name[1]))
+            .unwrap_or_default();
+        colors.push(color(&entry_name, (channel(1)?, channel(2)?, channel(3)?)));
+    }
+    Ok(colors)
+}
+
+/// GIMP palette. Names are the rest of each line, so line breaks in them
+/// become spaces.
+pub fn write_gpl(name: &str, colors: &[Color]) -> Result<String> {
+    let one_line = |text: &str| text.replace(['\r', '\n'], " ");
+    let mut gpl = format!(
+        "GIMP Palette\nName: {}\nColumns: {}\n#\n",
+        one_line(name),
+        colors.len().min(16)
+    );
+    for color in colors {
+        let (r, g, b) = rgb(color)?;
+        gpl.push_str(&format!(
+            "{:3} {:3} {:3}\t{}\n",
+            r,
+            g,
+            b,
+            one_line(&color.name)
+        ));
+    }
+    Ok(gpl)
+}
+
+/// Adobe Swatch Exchange with one RGB color block per color. Names are
+/// NUL-terminated, so NULs inside them become spaces, and names past the
+/// format's 65535 UTF-16 units are cut short.
+pub fn write_ase(colors: &[Color]) -> Result<Vec<u8>> {
+    let mut ase = b"ASEF".to_vec();
+    ase.extend(1u16.to_be_bytes());
+    ase.extend(0u16.to_be_bytes());
+    ase.extend((colors.len() as u32).to_be_bytes());
+
+    for color in colors {
+        let (r, g, b) = rgb(color)?;
+        let mut name: Vec<u16> = Vec::new();
+        for c in color.name.chars().map(|c| if c == '\0' { ' ' } else { c }) {
+            // Leave room for the terminator, and never split a surrogate pair
+            if name.len() + c.len_utf16() >= u16::MAX as usize {
+                break;
+            }
+            name.extend(c.encode_utf16(&mut [0; 2]).iter());
+        }
+        name.push(0);
+
+        let mut block = Vec::new();
+        block.extend((name.len() as u16).to_be_bytes());
+        block.extend(name.iter().flat_map(|unit| unit.to_be_bytes()));
+        block.extend(b"RGB ");
+        for channel in [r, g, b] {
+            block.extend((channel as f32 / 255.0).to_be_bytes());
+        }
+        // Normal (not global or spot) color
+        block.extend(2u16.to_be_bytes());
+
+        ase.extend(ASE_COLOR.to_be_bytes());
+        ase.extend((block.len() as u32).to_be_bytes());
+        ase.extend(block);
+    }
+    Ok(ase)
+}
+
+/// Krita palette archive; the mimetype entry comes first and uncompressed,
+/// as Krita expects.
+pub fn write_kpl(name: &str, colors: &[Color]) -> Result<Vec<u8>> {
+    let columns = colors.len().clamp(1, 16);
+    let mut xml = format!(
+        "<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n\
+         <Colorset version=\"1.0\" name=\"{}\" columns=\"{}\" rows=\"{}\" readonly=\"false\" comment=\"\">\n",
+        xml_escape(name),
+        columns,
+        colors.len().div_ceil(columns).max(1)
+    );
+    for (i, color) in colors.iter().enumerate() {
+        let (r, g, b) = rgb(color)?;
+        xml.push_str(&format!(
+            " <ColorSetEntry name=\"{}\" id=\"{}\" spot=\"false\" bitdepth=\"U8\">\n  \
+             <RGB r=\"{}\" g=\"{}\" b=\"{}\" space=\"sRGB-elle-V2-srgbtrc.icc\"/>\n  \
+             <Position row=\"{}\" column=\"{}\"/>\n </ColorSetEntry>\n",
+            xml_escape(&color.name),
+            i + 1,
+            r as f32 / 255.0,
+            g as f32 / 255.0,
+            b as f32 / 255.0,
+            i / columns,
+            i % columns
+        ));
+    }
+    xml.push_str("</Colorset>\n");
+
+    let mut archive = ZipWriter::new(Cursor::new(Vec::new()));
+    archive.start_file(
+        "mimetype",
+        SimpleFileOptions::default().compression_method(CompressionMethod::Stored),
+    )?;
+    archive.write_all(KPL_MIMETYPE.as_bytes())?;
+    let deflated = SimpleFileOptions::default().compression_method(CompressionMethod::Deflated);
+    archive.start_file("colorset.xml", deflated)?;
+    archive.write_all(xml.as_bytes())?;
+    archive.start_file("profiles.xml", deflated)?;
+    archive.write_all(b"<Profiles/>\n")?;
+    Ok(archive.finish()?.into_inner())
+}
+
+/// A color's channels for writing; exporting a bad hex value as black would
+/// hide the problem.
+fn rgb(color: &Color) -> Result<(u8, u8, u8)> {
+    match color.get_rgb() {
+        Some(rgb) => Ok(rgb),
+        None => bail!(
+            "cannot export {}: '{}' is not a hex color",
+            color.name,
+            color.hex
+        ),
+    }
+}
+
+fn xml_escape(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+fn xml_unescape(text: &str) -> String {
+    text.replace("&quot;", "\"")
+        .replace("&lt;", "<")
+        .replace("&gt;", ">")
+        .replace("&apos;", "'")
+        .replace("&amp;", "&")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn palette(names: &[&str]) -> Vec<Color> {
+        let hexes = ["#FF6B35", "#004E89", "#1A936F", "#F7F7F2"];
+        names
+            .iter()
+            .zip(hexes)
+            .map(|(name, hex)| Color::new(name, hex, ""))
+            .collect()
+    }
+
+    fn assert_round_trip(written: &[Color], read: &[Color]) {
+        let pairs = |colors: &[Color]| -> Vec<(String, String)> {
+            colors
+                .iter()
+                .map(|color| (color.name.clone(), color.hex.clone()))
+                .collect()
+        };
+        assert_eq!(pairs(read), pairs(written));
+    }
+
+    #[test]
+    fn gpl_round_trips() {
+        // Fields are whitespace-separated, so names keep tabs and runs of spaces
+        let colors = palette(&["Crème brûlée 🍮", "Sea\tFoam  Green", "", "#7 Chalk"]);
+        let gpl = write_gpl("Test", &colors).unwrap();
+        assert_round_trip(&colors, &parse_gpl(&gpl).unwrap());
+
+        let broken = palette(&["Two\nLines"]);
+        let read = parse_gpl(&write_gpl("Line\nBreak", &broken).unwrap()).unwrap();
+        assert_eq!(read[0].name, "Two Lines");
+    }
+
+    #[test]
+    fn ase_round_trips() {
+        // Names are NUL-terminated UTF-16
+        let colors = palette(&["Crème brûlée 🍮", "Nul\0Inside", "", "夜の海"]);
+        let read = parse_ase(&write_ase(&colors).unwrap()).unwrap();
+        let expected = palette(&["Crème brûlée 🍮", "Nul Inside", "", "夜の海"]);
+        assert_round_trip(&expected, &read);
+
+        // The length field is 16 bits and counts the terminator
+        let long = palette(&[&"🌊".repeat(40_000)]);
+        let read = parse_ase(&write_ase(&long).unwrap()).unwrap();
+        assert_eq!(read[0].name.encode_utf16().count(), 65_534);
+        assert_eq!(read[0].hex, "#FF6B35");
+    }
+
+    #[test]
+    fn refuses_to_write_invalid_hex() {
+        let colors = vec![Color::new("Typo", "#12345", "")];
+        assert!(write_gpl("Test", &colors).is_err());
+        assert!(write_ase(&colors).is_err());
+        assert!(write_kpl("Test", &colors).is_err());
+    }
+
+    #[test]
+    fn kpl_round_trips() {
+        // Names are quoted XML attributes
+        let colors = palette(&[
+            "Crème brûlée 🍮",
+            "Say \"cheese\" & <smile>",
+            "",
+            "it's &amp; literal",
+        ]);
+        let bytes = write_kpl("\"Quoted\" palette", &colors).unwrap();
+        assert_round_trip(&colors, &parse_kpl(&bytes).unwrap());
+    }
+}
@@ -0,0 +1,250 @@
+//! Hue families, tags and provenance on catalog colors, and the
+//! `--only`/`--exclude` filters built on them.
+
+use crate::colors::Color;
+
+/// Tags the built-in catalog uses; external catalogs may add their own.
+pub const KNOWN_TAGS: &[&str] = &[
+    "pastel", "earthy", "neon", "metallic", "muted", "jewel", "seasonal", "spring", "summer",
+    "autumn", "winter",
+];
+
+/// Source recorded on colors from the built-in catalog.
+pub const BUILT_IN_SOURCE: &str = "built-in";
+
+const METALLIC_WORDS: &[&str] = &[
+    "metallic", "gold", "silver", "silvery", "bronze", "copper", "brass", "platinum", "pewter",
+    "chrome", "steel", "gunmetal", "titanium",
+];
+const JEWEL_WORDS: &[&str] = &[
+    "emerald",
+    "sapphire",
+    "ruby",
+    "amethyst",
+    "jade",
+    "garnet",
+    "topaz",
+    "gemstone",
+    "gem",
+    "jewel",
+    "tourmaline",
+    "lapis",
+];
+const EARTH_WORDS: &[&str] = &[
+    "earth",
+    "earthy",
+    "clay",
+    "soil",
+    "terracotta",
+    "ochre",
+    "sienna",
+    "umber",
+    "moss",
+    "olive",
+    "rust",
+    "sand",
+    "sandy",
+    "khaki",
+    "mud",
+    "wood",
+    "bark",
+    "loam",
+];
+const NEON_WORDS: &[&str] = &["neon", "electric", "fluorescent", "fluorescence", "dayglo"];
+const SEASON_WORDS: [(&str, &[&str]); 4] = [
+    ("spring", &["spring", "blossom", "bloom"]),
+    ("summer", &["summer", "tropical", "sunny"]),
+    (
+        "autumn",
+        &["autumn", "autumnal", "harvest", "pumpkin", "foliage"],
+    ),
+    (
+        "winter",
+        &[
+            "winter",
+            "wintry",
+            "frost",
+            "frosty",
+            "snow",
+            "icy",
+            "christmas",
+        ],
+    ),
+];
+
+/// Fills in the hue family, source and (when a catalog gave none) derived
+/// tags of every color.
+pub fn tag_catalog(colors: &mut [Color], source: &str) {
+    for color in colors {
+        let Some(lch) = color.to_oklch() else {
+            continue;
+        };
+        color.family.get_or_insert(lch.hue_family());
+        if color.source.is_empty() {
+            color.source = source.to_string();
+        }
+        if color.tags.is_empty() {
+            color.tags = derive_tags(color);
+        } else {
+            color.tags = color.tags.iter().map(|tag| normalize_tag(tag)).collect();
+        }
+    }
+}
+
+/// Tags read off a color's OKLCH values and the words in its name and
+/// description.
+pub fn derive_tags(color: &Color) -> Vec<String> {
+    let Some(lch) = color.to_oklch() else {
+        return vec![];
+    };
+    let text = format!("{} {}", color.name, color.description).to_lowercase();
+    let words: Vec<&str> = text
+        .split(|c: char| !c.is_alphabetic())
+        .filter(|word| !word.is_empty())
+        .collect();
+    let mentions = |list: &[&str]| list.iter().any(|word| words.contains(word));
+    let chromatic = lch.c >= crate::color_space::Oklch::NEUTRAL_CHROMA;
+
+    let mut tags = Vec::new();
+    if chromatic && lch.l >= 0.8 && lch.c < 0.12 {
+        tags.push("pastel");
+    }
+    if (lch.c >= 0.22 && lch.l >= 0.65) || mentions(NEON_WORDS) {
+        tags.push("neon");
+    }
+    if chromatic && lch.c < 0.08 && lch.l < 0.8 {
+        tags.push("muted");
+    }
+    // Browns, ochres and olives: warm hues, low chroma, not too light
+    let earthy_hue = (20.0..130.0).contains(&lch.h);
+    if (earthy_hue && chromatic && lch.c < 0.14 && (0.25..0.7).contains(&lch.l))
+        || mentions(EARTH_WORDS)
+    {
+        tags.push("earthy");
+    }
+    if mentions(METALLIC_WORDS) {
+        tags.push("metallic");
+    }
+    if mentions(JEWEL_WORDS) {
+        tags.push("jewel");
+    }
+    let seasons: Vec<&str> = SEASON_WORDS
+        .iter()
+        .filter(|(_, words)| mentions(words))
+        .map(|(season, _)| *season)
+        .collect();
+    if !seasons.is_empty() {
+        tags.push("seasonal");
+        tags.extend(seasons);
+    }
+    tags.into_iter().map(str::to_string).collect()
+}
+
+fn normalize_tag(tag: &str) -> String {
+    tag.trim().to_lowercase()
+}
+
+/// Splits a catalog's tag column on commas, semicolons and whitespace.
+pub fn parse_tags(field: &str) -> Vec<String> {
+    field
+        .split(|c: char| c == ',' || c == ';' || c.is_whitespace())
+        .filter(|tag| !tag.is_empty())
+        .map(normalize_tag)
+        .collect()
+}
+
+/// Keeps colors carrying any of `only` and none of `exclude`; each term is
+/// matched against tags, the hue family and the source.
+#[derive(Debug, Clone, Default)]
+pub struct TagFilter {
+    pub only: Vec<String>,
+    pub exclude: Vec<String>,
+}
+
+impl TagFilter {
+    pub fn new(only: &[String], exclude: &[String]) -> Self {
+        Self {
+            only: only.iter().map(|tag| normalize_tag(tag)).collect(),
+            exclude: exclude.iter().map(|tag| normalize_tag(tag)).collect(),
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.only.is_empty() && self.exclude.is_empty()
+    }
+
+    pub fn allows(&self, color: &Color) -> bool {
+        let has = |term: &String| {
+            color.tags.iter().any(|tag| tag == term)
+                || color.family.is_some_and(|family| family.name() == term)
+                || color.source.eq_ignore_ascii_case(term)
+        };
+        (self.only.is_empty() || self.only.iter().any(has)) && !self.exclude.iter().any(has)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn tagged(name: &str, hex: &str, tags: &str, source: &str) -> Color {
+        let mut color = Color::new(name, hex, "");
+        color.tags = parse_tags(tags);
+        tag_catalog(std::slice::from_mut(&mut color), source);
+        color
+    }
+
+    fn filter(only: &[&str], exclude: &[&str]) -> TagFilter {
+        let terms = |terms: &[&str]| terms.iter().map(|t| t.to_string()).collect::<Vec<_>>();
+        TagFilter::new(&terms(only), &terms(exclude))
+    }
+
+    #[test]
+    fn parses_and_normalizes_tags() {
+        assert_eq!(
+            parse_tags(" Pastel;spring,  Muted "),
+            ["pastel", "spring", "muted"]
+        );
+        let mint = tagged("Mint", "#98FF98", "Pastel SPRING", "mine.csv");
+        assert_eq!(mint.tags, ["pastel", "spring"]);
+        assert_eq!(mint.family, Some(crate::color_space::HueFamily::Green));
+        assert_eq!(mint.source, "mine.csv");
+    }
+
+    #[test]
+    fn filters_on_tags_family_and_source() {
+        let colors = [
+            tagged("Mint", "#98FF98", "pastel spring", "mine.csv"),
+            tagged("Rust", "#B7410E", "earthy autumn", BUILT_IN_SOURCE),
+            tagged("Navy", "#000080", "", BUILT_IN_SOURCE),
+        ];
+        let kept = |filter: TagFilter| -> Vec<&str> {
+            colors
+                .iter()
+                .filter(|c| filter.allows(c))
+                .map(|c| c.name.as_str())
+                .collect()
+        };
+
+        assert_eq!(kept(filter(&[], &[])), ["Mint", "Rust", "Navy"]);
+        assert_eq!(kept(filter(&["Pastel"], &[])), ["Mint"]);
+        assert_eq!(kept(filter(&["blue", "earthy"], &[])), ["Rust", "Navy"]);
+        assert_eq!(kept(filter(&[], &["built-in"])), ["Mint"]);
+        assert_eq!(colors[1].family, Some(crate::color_space::HueFamily::Red));
+        assert_eq!(kept(filter(&["MINE.CSV", "red"], &["pastel"])), ["Rust"]);
+        assert_eq!(kept(filter(&["green"], &["green"])), Vec::<&str>::new());
+    }
+
+    #[test]
+    fn derives_tags_from_values_and_words() {
+        let derived = |name: &str, hex: &str, description: &str| {
+            derive_tags(&Color::new(name, hex, description))
+        };
+        assert_eq!(derived("Blush", "#F4D6DB", ""), ["pastel"]);
+        assert!(derived("Tan", "#A0784A", "").contains(&"earthy".to_string()));
+        assert_eq!(
+            derived("Frost", "#F0F0F0", "A frosty winter morning."),
+            ["seasonal", "winter"]
+        );
+    }
+}
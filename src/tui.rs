@@ -0,0 +1,829 @@
+use crate::color_space::{contrast_ratio, rgb_to_hsl};
+use crate::colors::Color;
+use crate::export::export_palette;
+use crate::matcher::{
+    fit_to_locked, keep_color, select_palette, ColorMatch, MoodPaletteGenerator, MoodRanking,
+};
+use anyhow::Result;
+use ratatui::crossterm::event::{self, Event, KeyCode, KeyEvent, KeyEventKind, KeyModifiers};
+use ratatui::layout::{Alignment, Constraint, Layout, Rect};
+use ratatui::style::{Color as TermColor, Modifier, Style};
+use ratatui::text::{Line, Span};
+use ratatui::widgets::{Block, Borders, List, ListItem, ListState, Paragraph, Wrap};
+use ratatui::{DefaultTerminal, Frame};
+use std::collections::BTreeMap;
+use std::path::Path;
+
+/// Every slot needs a digit key to lock it.
+const MAX_COUNT: usize = 9;
+const DEFAULT_EXPORT_PATH: &str = "palette.css";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Mode {
+    Editing,
+    Browsing,
+    Exporting,
+}
+
+pub struct App {
+    mode: Mode,
+    input: String,
+    export_path: String,
+    mood: Option<String>,
+    ranking: Vec<ColorMatch>,
+    palette: Vec<ColorMatch>,
+    count: usize,
+    diversity: f32,
+    min_delta_e: f32,
+    page: usize,
+    locked: BTreeMap<usize, ColorMatch>,
+    list_state: ListState,
+    status: String,
+    should_quit: bool,
+}
+
+impl App {
+    pub fn new(count: usize, diversity: f32, min_delta_e: f32) -> Self {
+        Self {
+            mode: Mode::Editing,
+            input: String::new(),
+            export_path: DEFAULT_EXPORT_PATH.to_string(),
+            mood: None,
+            ranking: vec![],
+            palette: vec![],
+            count: count.clamp(1, MAX_COUNT),
+            diversity,
+            min_delta_e,
+            page: 0,
+            locked: BTreeMap::new(),
+            list_state: ListState::default(),
+            status: "Type a mood and press Enter".to_string(),
+            should_quit: false,
+        }
+    }
+
+    /// Shows a new mood's matches, locking the colors it names into the
+    /// first slots.
+    fn set_ranking(&mut self, mood: String, ranked: MoodRanking) {
+        self.status = format!("{} matches for \"{}\"", ranked.ranking.len(), mood);
+        if !ranked.pinned.is_empty() {
+            let names: Vec<&str> = ranked.pinned.iter().map(|c| c.name.as_str()).collect();
+            self.status
+                .push_str(&format!(" · pinned {}", names.join(", ")));
+        }
+        self.mood = Some(mood);
+        self.page = 0;
+        self.locked = ranked
+            .pinned
+            .iter()
+            .take(self.count)
+            .enumerate()
+            .map(|(slot, color)| (slot, keep_color(&ranked.ranking, color)))
+            .collect();
+        self.ranking = ranked.ranking;
+        self.list_state.select(if self.ranking.is_empty() {
+            None
+        } else {
+            Some(0)
+        });
+        self.rebuild_palette();
+    }
+
+    /// Keeps locked slots in place and fills the rest from the current page
+    /// of the ranking.
+    fn rebuild_palette(&mut self) {
+        self.locked.retain(|slot, _| *slot < self.count);
+        let fixed: Vec<ColorMatch> = self.locked.values().cloned().collect();
+        let free = self.count - fixed.len();
+
+        let locked: Vec<Color> = fixed.iter().map(|kept| kept.color.clone()).collect();
+        let candidates = fit_to_locked(&self.ranking, &locked);
+        if free > 0 && self.page * free >= candidates.len() {
+            self.page = 0;
+        }
+
+        let offset = (self.page * free).min(candidates.len());
+        let picks = select_palette(
+            &candidates[offset..],
+            free,
+            self.diversity,
+            self.min_delta_e,
+            &fixed,
+        );
+        let mut picks = picks.into_iter();
+
+        self.palette = (0..self.count)
+            .filter_map(|slot| match self.locked.get(&slot) {
+                Some(kept) => Some(kept.clone()),
+                None => picks.next(),
+            })
+            .collect();
+    }
+
+    fn palette_slot(&self, color: &Color) -> Option<usize> {
+        self.palette.iter().position(|m| m.color.hex == color.hex)
+    }
+
+    fn toggle_slot_lock(&mut self, slot: usize) {
+        if slot >= self.palette.len() {
+            return;
+        }
+        if self.locked.remove(&slot).is_some() {
+            self.status = format!("Unlocked color {}", slot + 1);
+        } else {
+            let mut kept = self.palette[slot].clone();
+            kept.locked = true;
+            self.locked.insert(slot, kept);
+            self.status = format!("Locked {}", self.palette[slot].color.name);
+        }
+    }
+
+    /// Locks the highlighted match, pulling it into the palette if needed.
+    fn toggle_selected_lock(&mut self) {
+        let Some(selected) = self
+            .list_state
+            .selected()
+            .and_then(|i| self.ranking.get(i).cloned())
+        else {
+            return;
+        };
+
+        if let Some(slot) = self.palette_slot(&selected.color) {
+            self.toggle_slot_lock(slot);
+            return;
+        }
+
+        match (0..self.count).find(|slot| !self.locked.contains_key(slot)) {
+            Some(slot) => {
+                self.status = format!("Locked {}", selected.color.name);
+                let mut kept = selected;
+                kept.locked = true;
+                self.locked.insert(slot, kept);
+                self.rebuild_palette();
+            }
+            None => self.status = "Every slot is locked".to_string(),
+        }
+    }
+
+    fn move_selection(&mut self, delta: isize) {
+        if self.ranking.is_empty() {
+            return;
+        }
+        let current = self.list_state.selected().unwrap_or(0) as isize;
+        let next = (current + delta).clamp(0, self.ranking.len() as isize - 1);
+        self.list_state.select(Some(next as usize));
+    }
+
+    fn selected(&self) -> Option<&ColorMatch> {
+        self.list_state.selected().and_then(|i| self.ranking.get(i))
+    }
+
+    /// Handles a key press, returning the mood to look up when one is
+    /// submitted.
+    fn handle_key(&mut self, key: KeyEvent) -> Option<String> {
+        if key.modifiers.contains(KeyModifiers::CONTROL) && key.code == KeyCode::Char('c') {
+            self.should_quit = true;
+            return None;
+        }
+
+        match self.mode {
+            Mode::Editing => match key.code {
+                KeyCode::Enter => {
+                    let mood = self.input.trim().to_string();
+                    if mood.is_empty() {
+                        self.status = "Please enter a mood description".to_string();
+                        return None;
+                    }
+                    self.mode = Mode::Browsing;
+                    return Some(mood);
+                }
+                KeyCode::Esc | KeyCode::Tab if self.mood.is_some() => {
+                    self.mode = Mode::Browsing;
+                }
+                KeyCode::Esc => self.should_quit = true,
+                KeyCode::Backspace => {
+                    self.input.pop();
+                }
+                KeyCode::Char(c) => self.input.push(c),
+                _ => {}
+            },
+            Mode::Exporting => match key.code {
+                KeyCode::Enter => {
+                    self.mode = Mode::Browsing;
+                    self.export();
+                }
+                KeyCode::Esc => self.mode = Mode::Browsing,
+                KeyCode::Backspace => {
+                    self.export_path.pop();
+                }
+                KeyCode::Char(c) => self.export_path.push(c),
+                _ => {}
+            },
+            Mode::Browsing => match key.code {
+                KeyCode::Char('q') | KeyCode::Esc => self.should_quit = true,
+                KeyCode::Char('/') | KeyCode::Char('i') | KeyCode::Tab => {
+                    self.mode = Mode::Editing;
+                }
+                KeyCode::Up | KeyCode::Char('k') => self.move_selection(-1),
+                KeyCode::Down | KeyCode::Char('j') => self.move_selection(1),
+                KeyCode::PageUp => self.move_selection(-10),
+                KeyCode::PageDown => self.move_selection(10),
+                KeyCode::Char(' ') => self.toggle_selected_lock(),
+                KeyCode::Char(c @ '1'..='9') => {
+                    self.toggle_slot_lock(c as usize - '1' as usize);
+                }
+                KeyCode::Char('r') => {
+                    self.page += 1;
+                    self.rebuild_palette();
+                    self.status = "Rerolled unlocked colors".to_string();
+                }
+                KeyCode::Char('+') | KeyCode::Char('=') => {
+                    self.count = (self.count + 1).min(MAX_COUNT);
+                    self.rebuild_palette();
+                }
+                KeyCode::Char('-') => self.shrink(),
+                KeyCode::Char(']') => {
+                    self.diversity = (self.diversity + 0.1).min(1.0);
+                    self.rebuild_palette();
+                }
+                KeyCode::Char('[') => {
+                    self.diversity = (self.diversity - 0.1).max(0.0);
+                    self.rebuild_palette();
+                }
+                KeyCode::Char('e') => {
+                    if self.palette.is_empty() {
+                        self.status = "Nothing to export yet".to_string();
+                    } else {
+                        self.mode = Mode::Exporting;
+                    }
+                }
+                _ => {}
+            },
+        }
+
+        None
+    }
+
+    /// Drops the last slot, unless it's locked: shrinking would silently
+    /// throw the lock away.
+    fn shrink(&mut self) {
+        if self.count == 1 {
+            return;
+        }
+        if self.locked.contains_key(&(self.count - 1)) {
+            self.status = format!("Unlock color {} before removing it", self.count);
+            return;
+        }
+        self.count -= 1;
+        self.rebuild_palette();
+    }
+
+    fn export(&mut self) {
+        let path = self.export_path.trim();
+        if path.is_empty() {
+            self.status = "Export cancelled".to_string();
+            return;
+        }
+        self.status = match export_palette(Path::new(path), &self.palette) {
+            Ok(()) => format!("Saved palette to {}", path),
+            Err(e) => format!("Export failed: {}", e),
+        };
+    }
+}
+
+pub async fn run_tui(
+    generator: &mut MoodPaletteGenerator,
+    initial_mood: Option<&str>,
+) -> Result<()> {
+    let config = generator.config();
+    let mut app = App::new(
+        config.top_k,
+        config.diversity,
+        config.constraints.min_delta_e,
+    );
+
+    if let Some(mood) = initial_mood {
+        app.input = mood.to_string();
+        let ranked = generator.rank_mood(mood).await?;
+        app.set_ranking(mood.to_string(), ranked);
+        app.mode = Mode::Browsing;
+    }
+
+    let mut terminal = ratatui::try_init()?;
+    let result = event_loop(&mut terminal, &mut app, generator).await;
+    ratatui::restore();
+    result
+}
+
+async fn event_loop(
+    terminal: &mut DefaultTerminal,
+    app: &mut App,
+    generator: &mut MoodPaletteGenerator,
+) -> Result<()> {
+    while !app.should_quit {
+        terminal.draw(|frame| draw(frame, app))?;
+
+        let Event::Key(key) = event::read()? else {
+            continue;
+        };
+        if key.kind != KeyEventKind::Press {
+            continue;
+        }
+
+        if let Some(mood) = app.handle_key(key) {
+            app.status = format!("Analyzing \"{}\"...", mood);
+            terminal.draw(|frame| draw(frame, app))?;
+
+            match generator.rank_mood(&mood).await {
+                Ok(ranked) => app.set_ranking(mood, ranked),
+                Err(e) => app.status = format!("Error generating palette: {}", e),
+            }
+        }
+    }
+
+    Ok(())
+}
+
+/// Renders one frame of the explorer; kept free of terminal I/O so it can
+/// be drawn onto any backend.
+pub fn draw(frame: &mut Frame, app: &mut App) {
+    let [input_area, strip_area, body_area, footer_area] = Layout::vertical([
+        Constraint::Length(3),
+        Constraint::Length(5),
+        Constraint::Min(6),
+        Constraint::Length(2),
+    ])
+    .areas(frame.area());
+    let [list_area, details_area] =
+        Layout::horizontal([Constraint::Percentage(60), Constraint::Percentage(40)])
+            .areas(body_area);
+
+    draw_input(frame, app, input_area);
+    draw_palette_strip(frame, app, strip_area);
+    draw_match_list(frame, app, list_area);
+    draw_details(frame, app, details_area);
+    draw_footer(frame, app, footer_area);
+}
+
+fn draw_input(frame: &mut Frame, app: &App, area: Rect) {
+    let (title, text, active) = match app.mode {
+        Mode::Exporting => (" Export to ", app.export_path.as_str(), true),
+        Mode::Editing => (" Mood ", app.input.as_str(), true),
+        Mode::Browsing => (" Mood ", app.input.as_str(), false),
+    };
+    let border = if active {
+        Style::default().fg(TermColor::Cyan)
+    } else {
+        Style::default().fg(TermColor::DarkGray)
+    };
+
+    let input = Paragraph::new(text).block(
+        Block::default()
+            .borders(Borders::ALL)
+            .border_style(border)
+            .title(title),
+    );
+    frame.render_widget(input, area);
+
+    if active {
+        let cursor_x = area.x + 1 + text.chars().count() as u16;
+        frame.set_cursor_position((cursor_x.min(area.right().saturating_sub(2)), area.y + 1));
+    }
+}
+
+fn draw_palette_strip(frame: &mut Frame, app: &App, area: Rect) {
+    let block = Block::default().borders(Borders::ALL).title(format!(
+        " Palette · {} colors · diversity {:.1} ",
+        app.count, app.diversity
+    ));
+    let inner = block.inner(area);
+    frame.render_widget(block, area);
+
+    if app.palette.is_empty() {
+        return;
+    }
+
+    let cells = Layout::horizontal(
+        app.palette
+            .iter()
+            .map(|_| Constraint::Ratio(1, app.palette.len() as u32)),
+    )
+    .split(inner);
+
+    for (slot, (color_match, cell)) in app.palette.iter().zip(cells.iter()).enumerate() {
+        let color = &color_match.color;
+        let style = match color.get_rgb() {
+            Some((r, g, b)) => {
+                let fg = if color.get_text_color() == "black" {
+                    TermColor::Black
+                } else {
+                    TermColor::White
+                };
+                Style::default().bg(TermColor::Rgb(r, g, b)).fg(fg)
+            }
+            None => Style::default(),
+        };
+        let lock = if app.locked.contains_key(&slot) {
+            "◆ "
+        } else {
+            ""
+        };
+
+        let swatch = Paragraph::new(vec![
+            Line::from(format!("{}{}. {}", lock, slot + 1, color.name)),
+            Line::from(color.hex.clone()),
+            Line::from(""),
+        ])
+        .alignment(Alignment::Center)
+        .style(style);
+        frame.render_widget(swatch, *cell);
+    }
+}
+
+fn draw_match_list(frame: &mut Frame, app: &mut App, area: Rect) {
+    let description_width = area.width.saturating_sub(10) as usize;
+    let items: Vec<ListItem> = app
+        .ranking
+        .iter()
+        .enumerate()
+        .map(|(rank, color_match)| {
+            let color = &color_match.color;
+            let marker = match app.palette_slot(color) {
+                Some(slot) if app.locked.contains_key(&slot) => "◆",
+                Some(_) => "●",
+                None => " ",
+            };
+            let swatch = match color.get_rgb() {
+                Some((r, g, b)) => Span::styled("██", Style::default().fg(TermColor::Rgb(r, g, b))),
+                None => Span::raw("██"),
+            };
+
+            ListItem::new(vec![
+                Line::from(vec![
+                    Span::styled(
+                        format!("{} {:>3}. ", marker, rank + 1),
+                        Style::default().fg(TermColor::DarkGray),
+                    ),
+                    swatch,
+                    Span::styled(
+                        format!(" {}", color.name),
+                        Style::default().add_modifier(Modifier::BOLD),
+                    ),
+                    Span::styled(
+                        format!("  {}", color.hex),
+                        Style::default().fg(TermColor::DarkGray),
+                    ),
+                    Span::styled(
+                        format!("  {:.3}", color_match.score),
+                        Style::default().fg(TermColor::Green),
+                    ),
+                ]),
+                Line::from(Span::styled(
+                    format!(
+                        "        {}",
+                        truncate(&color.description, description_width)
+                    ),
+                    Style::default()
+                        .fg(TermColor::DarkGray)
+                        .add_modifier(Modifier::ITALIC),
+                )),
+            ])
+        })
+        .collect();
+
+    let title = match &app.mood {
+        Some(mood) => format!(" Matches for \"{}\" ", mood),
+        None => " Matches ".to_string(),
+    };
+    let list = List::new(items)
+        .block(Block::default().borders(Borders::ALL).title(title))
+        .highlight_style(Style::default().bg(TermColor::Rgb(40, 40, 40)))
+        .highlight_symbol("▶");
+    frame.render_stateful_widget(list, area, &mut app.list_state);
+}
+
+fn draw_details(frame: &mut Frame, app: &App, area: Rect) {
+    let block = Block::default().borders(Borders::ALL).title(" Details ");
+    let Some(color_match) = app.selected() else {
+        frame.render_widget(block, area);
+        return;
+    };
+    let color = &color_match.color;
+
+    let mut lines = vec![Line::from(Span::styled(
+        color.name.clone(),
+        Style::default().add_modifier(Modifier::BOLD),
+    ))];
+
+    if let Some((r, g, b)) = color.get_rgb() {
+        lines.push(Line::from(Span::styled(
+            " ".repeat(area.width.saturating_sub(2) as usize),
+            Style::default().bg(TermColor::Rgb(r, g, b)),
+        )));
+        lines.push(Line::from(""));
+
+        let (h, s, l) = rgb_to_hsl(r, g, b);
+        lines.push(Line::from(format!("HEX    {}", color.hex)));
+        lines.push(Line::from(format!("RGB    {}, {}, {}", r, g, b)));
+        lines.push(Line::from(format!(
+            "HSL    {:.0}°, {:.0}%, {:.0}%",
+            h,
+            s * 100.0,
+            l * 100.0
+        )));
+        if let Some(lch) = color.to_oklch() {
+            lines.push(Line::from(format!(
+                "OKLCH  {:.1}% {:.3} {:.0}°",
+                lch.l * 100.0,
+                lch.c,
+                lch.h
+            )));
+        }
+        lines.push(Line::from(""));
+
+        for (label, background) in [("on white", (255, 255, 255)), ("on black", (0, 0, 0))] {
+            let ratio = contrast_ratio((r, g, b), background);
+            lines.push(Line::from(format!(
+                "{:<9} {:>5.2}:1  {}",
+                label,
+                ratio,
+                wcag_grade(ratio)
+            )));
+        }
+        lines.push(Line::from(""));
+    }
+
+    lines.push(Line::from(format!("Score  {:.3}", color_match.score)));
+    lines.push(Line::from(""));
+    lines.push(Line::from(Span::styled(
+        color.description.clone(),
+        Style::default().add_modifier(Modifier::ITALIC),
+    )));
+
+    let details = Paragraph::new(lines).block(block).wrap(Wrap { trim: true });
+    frame.render_widget(details, area);
+}
+
+fn draw_footer(frame: &mut Frame, app: &App, area: Rect) {
+    let keys = match app.mode {
+        Mode::Editing => "Enter search · Tab/Esc browse · Ctrl+C quit",
+        Mode::Exporting => "Enter save (.json, .css, .gpl, .ase, .kpl or text) · Esc cancel",
+        Mode::Browsing => {
+            "↑↓ move · Space lock · 1-9 lock slot · r reroll · +/- count · [/] diversity · e export · / mood · q quit"
+        }
+    };
+
+    let footer = Paragraph::new(vec![
+        Line::from(Span::styled(keys, Style::default().fg(TermColor::DarkGray))),
+        Line::from(Span::styled(
+            app.status.clone(),
+            Style::default().fg(TermColor::Yellow),
+        )),
+    ]);
+    frame.render_widget(footer, area);
+}
+
+fn wcag_grade(ratio: f32) -> &'static str {
+    if ratio >= 7.0 {
+        "AAA"
+    } else if ratio >= 4.5 {
+        "AA"
+    } else if ratio >= 3.0 {
+        "AA large"
+    } else {
+        "fail"
+    }
+}
+
+fn truncate(text: &str, max_chars: usize) -> String {
+    if text.chars().count() <= max_chars {
+        return text.to_string();
+    }
+    let cut: String = text.chars().take(max_chars.saturating_sub(1)).collect();
+    format!("{}…", cut)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ratatui::backend::TestBackend;
+    use ratatui::buffer::Buffer;
+    use ratatui::Terminal;
+
+    fn ranked(name: &str, hex: &str, description: &str, score: f32) -> ColorMatch {
+        ColorMatch {
+            score,
+            semantic: score,
+            ..ColorMatch::unscored(Color::new(name, hex, description))
+        }
+    }
+
+    fn ocean(pinned: Vec<Color>) -> MoodRanking {
+        MoodRanking {
+            ranking: vec![
+                ranked("Teal", "#008080", "Calm blue-green water.", 0.81),
+                ranked("Navy", "#000080", "Deep night sea.", 0.74),
+                ranked("Sand", "#C2B280", "Warm beach sand.", 0.62),
+                ranked("Coral", "#FF7F50", "Bright reef coral.", 0.55),
+            ],
+            pinned,
+        }
+    }
+
+    fn browsing(count: usize) -> App {
+        let mut app = App::new(count, 0.0, 0.0);
+        app.input = "ocean".to_string();
+        app.mode = Mode::Browsing;
+        app.set_ranking("ocean".to_string(), ocean(vec![]));
+        app
+    }
+
+    fn press(app: &mut App, code: KeyCode) -> Option<String> {
+        app.handle_key(KeyEvent::new(code, KeyModifiers::NONE))
+    }
+
+    fn render(app: &mut App) -> Buffer {
+        let mut terminal = Terminal::new(TestBackend::new(72, 20)).unwrap();
+        terminal.draw(|frame| draw(frame, app)).unwrap();
+        terminal.backend().buffer().clone()
+    }
+
+    /// Compares the rendered text cell by cell; styles are checked
+    /// separately where they matter.
+    fn assert_text(buffer: &Buffer, expected: &[&str]) {
+        let expected = Buffer::with_lines(expected.iter().copied());
+        assert_eq!(buffer.area, expected.area);
+        for y in 0..buffer.area.height {
+            let line =
+                |b: &Buffer| -> String { (0..b.area.width).map(|x| b[(x, y)].symbol()).collect() };
+            assert_eq!(line(buffer), line(&expected), "line {}", y);
+        }
+    }
+
+    #[test]
+    fn draws_empty_state() {
+        let mut app = App::new(3, 0.5, 0.0);
+        assert_text(
+            &render(&mut app),
+            &[
+                "┌ Mood ────────────────────────────────────────────────────────────────┐",
+                "│                                                                      │",
+                "└──────────────────────────────────────────────────────────────────────┘",
+                "┌ Palette · 3 colors · diversity 0.5 ──────────────────────────────────┐",
+                "│                                                                      │",
+                "│                                                                      │",
+                "│                                                                      │",
+                "└──────────────────────────────────────────────────────────────────────┘",
+                "┌ Matches ────────────────────────────────┐┌ Details ──────────────────┐",
+                "│                                         ││                           │",
+                "│                                         ││                           │",
+                "│                                         ││                           │",
+                "│                                         ││                           │",
+                "│                                         ││                           │",
+                "│                                         ││                           │",
+                "│                                         ││                           │",
+                "│                                         ││                           │",
+                "└─────────────────────────────────────────┘└───────────────────────────┘",
+                "Enter search · Tab/Esc browse · Ctrl+C quit                             ",
+                "Type a mood and press Enter                                             ",
+            ],
+        );
+    }
+
+    #[test]
+    fn draws_palette_with_locked_slots() {
+        let mut app = browsing(3);
+        press(&mut app, KeyCode::Char('2'));
+        press(&mut app, KeyCode::Char('3'));
+        press(&mut app, KeyCode::Down);
+
+        let buffer = render(&mut app);
+        assert_text(
+            &buffer,
+            &[
+                "┌ Mood ────────────────────────────────────────────────────────────────┐",
+                "│ocean                                                                 │",
+                "└──────────────────────────────────────────────────────────────────────┘",
+                "┌ Palette · 3 colors · diversity 0.0 ──────────────────────────────────┐",
+                "│        1. Teal                ◆ 2. Navy              ◆ 3. Sand       │",
+                "│        #008080                 #000080                #C2B280        │",
+                "│                                                                      │",
+                "└──────────────────────────────────────────────────────────────────────┘",
+                "┌ Matches for \"ocean\" ────────────────────┐┌ Details ──────────────────┐",
+                "│ ●   1. ██ Teal  #008080  0.810          ││Navy                       │",
+                "│         Calm blue-green water.          ││                           │",
+                "│▶◆   2. ██ Navy  #000080  0.740          ││                           │",
+                "│         Deep night sea.                 ││HEX    #000080             │",
+                "│ ◆   3. ██ Sand  #C2B280  0.620          ││RGB    0, 0, 128           │",
+                "│         Warm beach sand.                ││HSL    240°, 100%, 25%     │",
+                "│     4. ██ Coral  #FF7F50  0.550         ││OKLCH  27.1% 0.188 264°    │",
+                "│         Bright reef coral.              ││                           │",
+                "└─────────────────────────────────────────┘└───────────────────────────┘",
+                "↑↓ move · Space lock · 1-9 lock slot · r reroll · +/- count · [/] divers",
+                "Locked Sand                                                             ",
+            ],
+        );
+        // Swatches are painted in their own color with readable text
+        assert_eq!(buffer[(30, 4)].bg, TermColor::Rgb(0, 0, 128));
+        assert_eq!(buffer[(30, 4)].fg, TermColor::White);
+        assert_eq!(buffer[(60, 5)].bg, TermColor::Rgb(194, 178, 128));
+        assert_eq!(buffer[(60, 5)].fg, TermColor::Black);
+    }
+
+    #[test]
+    fn draws_export_prompt() {
+        let mut app = browsing(2);
+        press(&mut app, KeyCode::Char('e'));
+        assert_eq!(app.mode, Mode::Exporting);
+
+        let buffer = render(&mut app);
+        assert_text(
+            &buffer,
+            &[
+                "┌ Export to ───────────────────────────────────────────────────────────┐",
+                "│palette.css                                                           │",
+                "└──────────────────────────────────────────────────────────────────────┘",
+                "┌ Palette · 2 colors · diversity 0.0 ──────────────────────────────────┐",
+                "│              1. Teal                            2. Navy              │",
+                "│              #008080                            #000080              │",
+                "│                                                                      │",
+                "└──────────────────────────────────────────────────────────────────────┘",
+                "┌ Matches for \"ocean\" ────────────────────┐┌ Details ──────────────────┐",
+                "│▶●   1. ██ Teal  #008080  0.810          ││Teal                       │",
+                "│         Calm blue-green water.          ││                           │",
+                "│ ●   2. ██ Navy  #000080  0.740          ││                           │",
+                "│         Deep night sea.                 ││HEX    #008080             │",
+                "│     3. ██ Sand  #C2B280  0.620          ││RGB    0, 128, 128         │",
+                "│         Warm beach sand.                ││HSL    180°, 100%, 25%     │",
+                "│     4. ██ Coral  #FF7F50  0.550         ││OKLCH  54.3% 0.093 195°    │",
+                "│         Bright reef coral.              ││                           │",
+                "└─────────────────────────────────────────┘└───────────────────────────┘",
+                "Enter save (.json, .css, .gpl, .ase, .kpl or text) · Esc cancel         ",
+                "4 matches for \"ocean\"                                                   ",
+            ],
+        );
+        assert_eq!(buffer[(0, 0)].fg, TermColor::Cyan);
+    }
+
+    #[test]
+    fn shrinking_keeps_locked_slots() {
+        let mut app = browsing(3);
+        press(&mut app, KeyCode::Char('3'));
+
+        press(&mut app, KeyCode::Char('-'));
+        assert_eq!(app.count, 3);
+        assert_eq!(app.status, "Unlock color 3 before removing it");
+        assert_eq!(app.palette[2].color.name, "Sand");
+        assert!(app.locked.contains_key(&2));
+
+        press(&mut app, KeyCode::Char('3'));
+        press(&mut app, KeyCode::Char('-'));
+        assert_eq!(app.count, 2);
+        assert_eq!(app.palette.len(), 2);
+    }
+
+    #[test]
+    fn locks_colors_named_in_the_mood() {
+        let mut app = App::new(3, 0.0, 0.0);
+        let coral = Color::new("Coral", "#FF7F50", "Bright reef coral.");
+        app.set_ranking("ocean with coral".to_string(), ocean(vec![coral]));
+
+        assert_eq!(app.palette[0].color.name, "Coral");
+        assert!(app.palette[0].locked);
+        assert_eq!(app.palette[0].score, 0.55);
+        assert!(app.locked.contains_key(&0));
+        assert_eq!(app.palette.len(), 3);
+        assert_eq!(
+            app.status,
+            "4 matches for \"ocean with coral\" · pinned Coral"
+        );
+    }
+
+    #[test]
+    fn every_slot_has_a_lock_key() {
+        let mut app = App::new(20, 0.0, 0.0);
+        assert_eq!(app.count, MAX_COUNT);
+        app.mode = Mode::Browsing;
+        app.set_ranking(
+            "ocean".to_string(),
+            MoodRanking {
+                ranking: (0..12)
+                    .map(|i| {
+                        ranked(
+                            &format!("Blue {}", i),
+                            &format!("#0000{:02X}", i * 20),
+                            "",
+                            0.5,
+                        )
+                    })
+                    .collect(),
+                pinned: vec![],
+            },
+        );
+        press(&mut app, KeyCode::Char('+'));
+        assert_eq!(app.count, MAX_COUNT);
+
+        press(&mut app, KeyCode::Char('9'));
+        assert!(app.locked.contains_key(&(MAX_COUNT - 1)));
+    }
+}
@@ -0,0 +1,396 @@
+//! Light and dark mode counterparts of a palette: each color moved into a
+//! mode's lightness band in OKLCH, keeping its hue and as much of its
+//! chroma as sRGB allows.
+
+use crate::color_space::{contrast_ratio, hue_distance, to_hex, Oklab, Oklch};
+use crate::colors::Color;
+use crate::display::{DisplayOptions, PaletteDisplay};
+use crate::matcher::ColorMatch;
+use anyhow::{bail, Context, Result};
+use colored::*;
+use std::collections::HashSet;
+use std::fs;
+use std::path::Path;
+
+/// WCAG AA contrast for body text.
+pub const MIN_TEXT_CONTRAST: f32 = 4.5;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Mode {
+    Light,
+    Dark,
+}
+
+impl Mode {
+    pub fn name(self) -> &'static str {
+        match self {
+            Mode::Light => "light",
+            Mode::Dark => "dark",
+        }
+    }
+
+    /// The mode a palette already suits: the one whose band its mean
+    /// lightness sits closer to.
+    fn of_palette(palette: &[ColorMatch]) -> Self {
+        let lightness: Vec<f32> = palette
+            .iter()
+            .filter_map(|m| Some(m.color.to_oklch()?.l))
+            .collect();
+        if lightness.is_empty() {
+            return Mode::Light;
+        }
+        let mean = lightness.iter().sum::<f32>() / lightness.len() as f32;
+        let center = |mode: Mode| {
+            let (low, high) = mode.band();
+            (low + high) / 2.0
+        };
+        if (mean - center(Mode::Light)).abs() <= (mean - center(Mode::Dark)).abs() {
+            Mode::Light
+        } else {
+            Mode::Dark
+        }
+    }
+
+    /// OKLCH lightness a color needs to work as a surface in this mode.
+    fn band(self) -> (f32, f32) {
+        match self {
+            Mode::Light => (0.75, 0.95),
+            Mode::Dark => (0.25, 0.5),
+        }
+    }
+
+    /// Text drawn on this mode's colors.
+    pub fn text(self) -> (u8, u8, u8) {
+        match self {
+            Mode::Light => (0x1A, 0x1A, 0x1A),
+            Mode::Dark => (0xF5, 0xF5, 0xF5),
+        }
+    }
+}
+
+/// Which counterparts `--variant` asks for.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Variant {
+    /// The palette as generated is the light one; add a dark counterpart
+    Dark,
+    /// The palette as generated is the dark one; add a light counterpart
+    Light,
+    /// Move the palette into both bands
+    Both,
+}
+
+impl Variant {
+    pub fn parse(text: &str) -> Result<Self> {
+        match text.trim().to_lowercase().as_str() {
+            "dark" => Ok(Variant::Dark),
+            "light" => Ok(Variant::Light),
+            "both" => Ok(Variant::Both),
+            other => bail!("variant must be dark, light or both, not '{}'", other),
+        }
+    }
+}
+
+/// The same palette for light and for dark mode, color for color.
+#[derive(Debug, Clone)]
+pub struct ThemedPalette {
+    pub light: Vec<ColorMatch>,
+    pub dark: Vec<ColorMatch>,
+}
+
+impl ThemedPalette {
+    /// Pairs `palette` with its counterpart; with a `catalog`, moved colors
+    /// snap to distinct catalog colors of about the same hue inside the
+    /// target band.
+    pub fn new(palette: &[ColorMatch], variant: Variant, catalog: Option<&[Color]>) -> Self {
+        let moved = |from, to| -> Vec<ColorMatch> {
+            let mut moved: Vec<ColorMatch> = palette.iter().map(|m| to_mode(m, from, to)).collect();
+            if let Some(catalog) = catalog {
+                snap(&mut moved, palette, to, catalog);
+            }
+            moved
+        };
+        match variant {
+            Variant::Dark => Self {
+                light: palette.to_vec(),
+                dark: moved(Mode::Light, Mode::Dark),
+            },
+            Variant::Light => Self {
+                light: moved(Mode::Dark, Mode::Light),
+                dark: palette.to_vec(),
+            },
+            Variant::Both => {
+                let from = Mode::of_palette(palette);
+                Self {
+                    light: moved(from, Mode::Light),
+                    dark: moved(from, Mode::Dark),
+                }
+            }
+        }
+    }
+
+    pub fn palette(&self, mode: Mode) -> &[ColorMatch] {
+        match mode {
+            Mode::Light => &self.light,
+            Mode::Dark => &self.dark,
+        }
+    }
+}
+
+/// Moves the color into `to`'s band, keeping its hue and, as far as sRGB
+/// allows, its chroma. Between modes lightness is flipped, so the lightest
+/// light-mode color becomes the darkest dark-mode one; within a mode it is
+/// scaled into the band. Either way the palette keeps its ordering.
+fn to_mode(color_match: &ColorMatch, from: Mode, to: Mode) -> ColorMatch {
+    let color = &color_match.color;
+    let Some(lch) = color.to_oklch() else {
+        return color_match.clone();
+    };
+    let (low, high) = to.band();
+    let l = lch.l.clamp(0.0, 1.0);
+    let position = if from == to { l } else { 1.0 - l };
+    let moved = Oklch {
+        l: low + position * (high - low),
+        ..lch
+    };
+    let (r, g, b) = moved.to_rgb_in_gamut();
+    let hex = to_hex(r, g, b);
+    if hex.eq_ignore_ascii_case(&color.hex) {
+        return color_match.clone();
+    }
+    ColorMatch {
+        color: Color {
+            name: format!("{} ({})", color.name, to.name()),
+            hex,
+            ..color.clone()
+        },
+        ..color_match.clone()
+    }
+}
+
+/// Swaps each moved color for the closest catalog color in `mode`'s band
+/// whose hue stays within `SNAP_HUE_TOLERANCE` of the original (greys only
+/// match greys). No catalog color is used twice, and colors without a fit
+/// keep their computed value.
+fn snap(moved: &mut [ColorMatch], original: &[ColorMatch], mode: Mode, catalog: &[Color]) {
+    let (low, high) = mode.band();
+    let in_band: Vec<(&Color, Oklab)> = catalog
+        .iter()
+        .filter_map(|known| Some((known, known.to_oklab()?)))
+        .filter(|(_, lab)| (low..=high).contains(&lab.to_oklch().l))
+        .collect();
+    let mut used: HashSet<&str> = HashSet::new();
+
+    for (color_match, source) in moved.iter_mut().zip(original) {
+        let (Some(target), Some(hue)) = (color_match.color.to_oklab(), source.color.to_oklch())
+        else {
+            continue;
+        };
+        let best = in_band
+            .iter()
+            .filter(|(known, _)| !used.contains(known.hex.as_str()))
+            .filter(|(_, lab)| same_hue(&hue, &lab.to_oklch()))
+            .map(|(known, lab)| (*known, lab.delta_e(&target)))
+            .min_by(|a, b| a.1.total_cmp(&b.1));
+        if let Some((known, _)) = best {
+            used.insert(known.hex.as_str());
+            color_match.color = known.clone();
+        }
+    }
+}
+
+/// How far (degrees) a snapped color's hue may drift from the original's.
+const SNAP_HUE_TOLERANCE: f32 = 20.0;
+
+fn same_hue(a: &Oklch, b: &Oklch) -> bool {
+    match (a.c < Oklch::NEUTRAL_CHROMA, b.c < Oklch::NEUTRAL_CHROMA) {
+        (true, true) => true,
+        (false, false) => hue_distance(a.h, b.h) <= SNAP_HUE_TOLERANCE,
+        _ => false,
+    }
+}
+
+/// Contrast of the mode's text color on `color`.
+pub fn text_contrast(color: &Color, mode: Mode) -> Option<f32> {
+    Some(contrast_ratio(color.get_rgb()?, mode.text()))
+}
+
+/// Shows both palettes with the text contrast of each color in its mode.
+/// Returns false when any color falls short of `MIN_TEXT_CONTRAST`.
+pub fn show_themed(themed: &ThemedPalette, display: &DisplayOptions) -> bool {
+    let mut passes = true;
+    for (mode, title) in [
+        (Mode::Light, "☀️  Light mode"),
+        (Mode::Dark, "🌙 Dark mode"),
+    ] {
+        println!("\n{}", title.bold().bright_blue());
+        let palette = themed.palette(mode);
+        PaletteDisplay::show(palette, display);
+
+        let (r, g, b) = mode.text();
+        println!(
+            "   {}",
+            format!("contrast with {} text:", to_hex(r, g, b)).bright_black()
+        );
+        for color_match in palette {
+            let Some(ratio) = text_contrast(&color_match.color, mode) else {
+                continue;
+            };
+            let line = format!(
+                "   {} {:>5.2}:1  {}",
+                PaletteDisplay::swatches(std::slice::from_ref(color_match)),
+                ratio,
+                color_match.color.name
+            );
+            if ratio >= MIN_TEXT_CONTRAST {
+                println!("{} {}", line, "✅".green());
+            } else {
+                passes = false;
+                println!(
+                    "{} {}",
+                    line,
+                    format!("⚠️  below {}:1", MIN_TEXT_CONTRAST).yellow()
+                );
+            }
+        }
+    }
+    passes
+}
+
+/// CSS custom properties for light mode, overridden for dark mode under
+/// `prefers-color-scheme`.
+pub fn to_themed_css(themed: &ThemedPalette) -> String {
+    let mut css = String::from(":root {\n  color-scheme: light dark;\n");
+    push_properties(&mut css, &themed.light, Mode::Light, "  ");
+    css.push_str("}\n\n@media (prefers-color-scheme: dark) {\n  :root {\n");
+    push_properties(&mut css, &themed.dark, Mode::Dark, "    ");
+    css.push_str("  }\n}\n");
+    css
+}
+
+fn push_properties(css: &mut String, palette: &[ColorMatch], mode: Mode, indent: &str) {
+    let (r, g, b) = mode.text();
+    css.push_str(&format!("{}--text: {};\n", indent, to_hex(r, g, b)));
+    for (i, color_match) in palette.iter().enumerate() {
+        css.push_str(&format!(
+            "{}--color-{}: {}; /* {} */\n",
+            indent,
+            i + 1,
+            color_match.color.hex,
+            color_match.color.name
+        ));
+    }
+}
+
+pub fn export_themed(path: &Path, themed: &ThemedPalette) -> Result<()> {
+    fs::write(path, to_themed_css(themed))
+        .with_context(|| format!("failed to write {}", path.display()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn palette(hexes: &[&str]) -> Vec<ColorMatch> {
+        hexes
+            .iter()
+            .map(|hex| ColorMatch::unscored(Color::from_hex(hex).unwrap()))
+            .collect()
+    }
+
+    fn lightness(palette: &[ColorMatch]) -> Vec<f32> {
+        palette
+            .iter()
+            .map(|m| m.color.to_oklch().unwrap().l)
+            .collect()
+    }
+
+    /// Palette positions from darkest to lightest.
+    fn order(palette: &[ColorMatch]) -> Vec<usize> {
+        let l = lightness(palette);
+        let mut order: Vec<usize> = (0..l.len()).collect();
+        order.sort_by(|a, b| l[*a].total_cmp(&l[*b]));
+        order
+    }
+
+    // Cream, sky, coral, sage, slate: a light palette
+    const LIGHT: &[&str] = &["#FFFDD0", "#87CEEB", "#FF7F50", "#8FA67A", "#5A6E7F"];
+    // Navy, forest, plum, rust, teal: a dark palette
+    const DARK: &[&str] = &["#000080", "#1B4D2E", "#5E2750", "#8B3A1A", "#1F6F6F"];
+
+    fn hexes(palette: &[ColorMatch]) -> Vec<&str> {
+        palette.iter().map(|m| m.color.hex.as_str()).collect()
+    }
+
+    #[test]
+    fn places_colors_in_each_band() {
+        for hexes in [LIGHT, DARK] {
+            let themed = ThemedPalette::new(&palette(hexes), Variant::Both, None);
+            for mode in [Mode::Light, Mode::Dark] {
+                let (low, high) = mode.band();
+                for l in lightness(themed.palette(mode)) {
+                    // Rounding to 8-bit channels moves lightness a little
+                    assert!(low - 0.01 <= l && l <= high + 0.01, "{:?} {}", mode, l);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn keeps_ordering_within_a_mode_and_flips_it_across() {
+        let reversed = |mut order: Vec<usize>| {
+            order.reverse();
+            order
+        };
+
+        let light = palette(LIGHT);
+        let themed = ThemedPalette::new(&light, Variant::Both, None);
+        assert_eq!(order(&themed.light), order(&light));
+        assert_eq!(order(&themed.dark), reversed(order(&light)));
+
+        let dark = palette(DARK);
+        let themed = ThemedPalette::new(&dark, Variant::Both, None);
+        assert_eq!(order(&themed.dark), order(&dark));
+        assert_eq!(order(&themed.light), reversed(order(&dark)));
+
+        let themed = ThemedPalette::new(&light, Variant::Dark, None);
+        assert_eq!(hexes(&themed.light), LIGHT);
+        assert_eq!(order(&themed.dark), reversed(order(&light)));
+    }
+
+    #[test]
+    fn text_is_readable_in_both_modes() {
+        for hexes in [LIGHT, DARK] {
+            let themed = ThemedPalette::new(&palette(hexes), Variant::Both, None);
+            for mode in [Mode::Light, Mode::Dark] {
+                for color_match in themed.palette(mode) {
+                    let ratio = text_contrast(&color_match.color, mode).unwrap();
+                    assert!(
+                        ratio >= MIN_TEXT_CONTRAST,
+                        "{} {}",
+                        color_match.color.hex,
+                        ratio
+                    );
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn snaps_to_distinct_catalog_colors_of_the_same_hue() {
+        let catalog = [
+            Color::new("Harbor", "#0A3A4A", ""),
+            Color::new("Deep Sea", "#123C55", ""),
+            Color::new("Brick", "#6A2A10", ""),
+            Color::new("Plum", "#4A1A4A", ""),
+            Color::new("Ice", "#DDF4FF", ""),
+        ];
+        // Sky, a near-identical blue, coral and lemon
+        let light = palette(&["#87CEEB", "#8FCBE8", "#FF7F50", "#FFF44F"]);
+        let themed = ThemedPalette::new(&light, Variant::Dark, Some(&catalog));
+
+        let names: Vec<&str> = themed.dark.iter().map(|m| m.color.name.as_str()).collect();
+        assert_eq!(names[..3], ["Harbor", "Deep Sea", "Brick"]);
+        // No yellow in the dark band, so lemon keeps its computed value
+        assert_eq!(names[3], "#FFF44F (dark)");
+    }
+}